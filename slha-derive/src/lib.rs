@@ -21,37 +21,239 @@ pub fn slha_deserialize(input: proc_macro::TokenStream) -> proc_macro::TokenStre
     gen.parse().unwrap()
 }
 
-fn impl_slha_deserialize(ast: &syn::DeriveInput) -> quote::Tokens {
-    let name = &ast.ident;
+#[proc_macro_derive(SlhaDeserializeLenient, attributes(slha))]
+pub fn slha_deserialize_lenient(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let s = input.to_string();
+    let ast = syn::parse_derive_input(&s).unwrap();
+    let gen = impl_slha_deserialize_lenient(&ast);
+    gen.parse().unwrap()
+}
+
+/// The kind of code that is generated to handle errors while reading the blocks.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    /// Return the first error (`deserialize`).
+    Strict,
+    /// Collect all errors in `errors` and mark the failed fields (`deserialize_report`).
+    Report,
+    /// Store the first error of every field in `failed` (`deserialize_segments_lenient`).
+    Lenient,
+}
+
+fn struct_fields(ast: &syn::DeriveInput) -> &[syn::Field] {
     let body = match ast.body {
         syn::Body::Struct(ref body) => body,
         _ => panic!("#[derive(slha_deserialize)] is only defined for structs!"),
     };
-    let fields = match *body {
+    match *body {
         syn::VariantData::Struct(ref fields) => fields,
         _ => panic!("#[derive(slha_deserialize)] is not defined for tuple structs!"),
-    };
+    }
+}
+
+fn impl_slha_deserialize(ast: &syn::DeriveInput) -> quote::Tokens {
+    let name = &ast.ident;
+    let fields = struct_fields(ast);
+    let case_sensitive = extract_case_sensitive(&normalize_attrs(&ast.attrs));
     let (blocks, has_decays) = extract_decays(fields);
     let let_bindings = generate_let_bindings(&blocks, has_decays);
-    let match_arm_blocks = generate_match_arm_blocks(&blocks);
-    let match_arm_decays = generate_match_arm_decays(has_decays);
+    let match_arm_blocks = generate_match_arm_blocks(&blocks, Mode::Strict);
+    let match_arm_decays = generate_match_arm_decays(has_decays, Mode::Strict);
+    let unwrapped = generate_unwrapped_bindings(&blocks);
     let assignments = generate_struct_assignments(&blocks, has_decays);
+    let report = generate_deserialize_report(name, &blocks, has_decays, case_sensitive);
     quote! {
         impl slha::SlhaDeserialize for #name {
             fn deserialize(input: &str) -> slha::errors::Result<#name> {
+                let options = slha::ParseOptions {
+                    case_sensitive: #case_sensitive,
+                    ..slha::ParseOptions::default()
+                };
+                Self::deserialize_segments(slha::internal::Segments::new(input, options))
+            }
+
+            fn deserialize_segments<'a, I>(segments: I) -> slha::errors::Result<#name>
+            where
+                I: Iterator<Item = slha::errors::Result<slha::internal::Segment<'a>>>,
+            {
                 #(#let_bindings)*
-                let mut lines = input.lines().peekable();
-                while let Some(segment) = slha::internal::parse_segment(&mut lines) {
+                for segment in segments {
                     match segment? {
                         #match_arm_blocks
                         #match_arm_decays
+                        slha::internal::Segment::DisabledBlock { .. } => continue,
+                        slha::internal::Segment::Unknown { .. } => continue,
                     }
                 }
 
+                let mut missing_blocks = Vec::new();
+                #(#unwrapped)*
+                slha::internal::check_missing(missing_blocks)?;
+
                 Ok(#name {
                     #(#assignments)*
                 })
             }
+
+            #report
+        }
+    }
+}
+
+/// Generates `deserialize_report`, which reads all fields and collects their errors.
+///
+/// A field whose block could not be read is marked as failed, so that it is not also reported as
+/// missing afterwards.
+fn generate_deserialize_report(
+    name: &syn::Ident,
+    blocks: &[Block],
+    has_decays: bool,
+    case_sensitive: bool,
+) -> quote::Tokens {
+    let let_bindings = generate_let_bindings(blocks, has_decays);
+    let match_arm_blocks = generate_match_arm_blocks(blocks, Mode::Report);
+    let match_arm_decays = generate_match_arm_decays(has_decays, Mode::Report);
+    let unwrapped = generate_report_unwrapped_bindings(blocks);
+    let assignments = generate_struct_assignments(blocks, has_decays);
+    let n_blocks = blocks.len();
+    let failed = if blocks.is_empty() {
+        quote!{}
+    } else {
+        quote! {
+            let mut failed = [false; #n_blocks];
+        }
+    };
+    quote! {
+        fn deserialize_report(input: &str) -> (Option<#name>, Vec<slha::errors::Error>) {
+            let options = slha::ParseOptions {
+                case_sensitive: #case_sensitive,
+                ..slha::ParseOptions::default()
+            };
+            let mut errors = Vec::new();
+            #(#let_bindings)*
+            #failed
+            for segment in slha::internal::Segments::new(input, options) {
+                let segment = match segment {
+                    Ok(segment) => segment,
+                    Err(err) => {
+                        errors.push(err);
+                        return (None, errors);
+                    }
+                };
+                match segment {
+                    #match_arm_blocks
+                    #match_arm_decays
+                    slha::internal::Segment::DisabledBlock { .. } => continue,
+                    slha::internal::Segment::Unknown { .. } => continue,
+                }
+            }
+
+            let mut missing_blocks = Vec::new();
+            #(#unwrapped)*
+            if let Err(err) = slha::internal::check_missing(missing_blocks) {
+                errors.push(err);
+            }
+            if !errors.is_empty() {
+                return (None, errors);
+            }
+
+            (Some(#name {
+                #(#assignments)*
+            }), errors)
+        }
+    }
+}
+
+/// Generates the struct holding the result of every field, and the implementation of
+/// `SlhaDeserializeLenient`, which fills it.
+///
+/// The error of a block whose header could not be read is stored in all fields that read from the
+/// block, and the error of a decay table in the `decays` field.
+/// Errors that can not be attributed to any field are dropped.
+fn impl_slha_deserialize_lenient(ast: &syn::DeriveInput) -> quote::Tokens {
+    let name = &ast.ident;
+    let vis = &ast.vis;
+    let lenient_name = syn::Ident::new(format!("{}Lenient", name));
+    let fields = struct_fields(ast);
+    let case_sensitive = extract_case_sensitive(&normalize_attrs(&ast.attrs));
+    let (blocks, has_decays) = extract_decays(fields);
+    let let_bindings = generate_let_bindings(&blocks, has_decays);
+    let match_arm_blocks = generate_match_arm_blocks(&blocks, Mode::Lenient);
+    let match_arm_decays = generate_match_arm_decays(has_decays, Mode::Lenient);
+    let segment_error = generate_lenient_segment_error(&blocks, has_decays);
+    let unwrapped = generate_lenient_unwrapped_bindings(&blocks, has_decays);
+    let decay_ident = syn::Ident::new("decays");
+    let decays_field = fields.iter().find(|field| field.ident.as_ref() == Some(&decay_ident));
+    let lenient_fields = generate_lenient_fields(&blocks, decays_field);
+    let assignments = blocks.iter().map(|block| block.name).collect::<Vec<_>>();
+    let decays_assignment = if has_decays {
+        quote! { decays, }
+    } else {
+        quote!{}
+    };
+    let n_blocks = blocks.len();
+    let failed = if blocks.is_empty() {
+        quote!{}
+    } else {
+        quote! {
+            let mut failed: Vec<Option<slha::errors::Error>> = (0..#n_blocks).map(|_| None).collect();
+        }
+    };
+    let decays_failed = if has_decays {
+        quote! {
+            let mut decays_failed: Option<slha::errors::Error> = None;
+        }
+    } else {
+        quote!{}
+    };
+    quote! {
+        #vis struct #lenient_name {
+            #(#lenient_fields)*
+        }
+
+        impl slha::SlhaDeserializeLenient for #name {
+            type Lenient = #lenient_name;
+
+            fn deserialize_lenient(input: &str) -> #lenient_name {
+                let options = slha::ParseOptions {
+                    case_sensitive: #case_sensitive,
+                    ..slha::ParseOptions::default()
+                };
+                Self::deserialize_segments_lenient(
+                    slha::internal::LenientSegments::new(input, options),
+                )
+            }
+
+            fn deserialize_segments_lenient<'a, I>(segments: I) -> #lenient_name
+            where
+                I: Iterator<Item = slha::errors::Result<slha::internal::Segment<'a>>>,
+            {
+                #(#let_bindings)*
+                #failed
+                #decays_failed
+                for segment in segments {
+                    let segment = match segment {
+                        Ok(segment) => segment,
+                        Err(err) => {
+                            #segment_error
+                            continue;
+                        }
+                    };
+                    match segment {
+                        #match_arm_blocks
+                        #match_arm_decays
+                        slha::internal::Segment::DisabledBlock { .. } => continue,
+                        slha::internal::Segment::Unknown { .. } => continue,
+                    }
+                }
+
+                #(#unwrapped)*
+
+                #lenient_name {
+                    #(#assignments,)*
+                    #decays_assignment
+                }
+            }
         }
     }
 }
@@ -61,6 +263,7 @@ struct Block<'a> {
     name: &'a syn::Ident,
     ty: &'a syn::Ty,
     block_name: String,
+    index: Option<Vec<i64>>,
     attributes: Vec<&'a syn::NestedMetaItem>,
 }
 impl<'a> Block<'a> {
@@ -69,15 +272,70 @@ impl<'a> Block<'a> {
             "BUG: This should be a struct (with named fields)",
         );
         let attributes = normalize_attrs(&field.attrs);
-        let block_name = extract_block_name(&attributes).unwrap_or_else(|| name.to_string());
+        let block_name = extract_str_attr(&attributes, "block")
+            .or_else(|| extract_str_attr(&attributes, "rename"))
+            .unwrap_or_else(|| name.to_string());
+        let index = extract_index(&attributes);
         Block {
             field,
             name,
             ty: &field.ty,
             block_name,
+            index,
             attributes,
         }
     }
+
+    /// The type of the variable that collects the block while the segments are read.
+    ///
+    /// For fields with an `index` attribute this holds the value of the entry as read by
+    /// `parse_entry`, otherwise it is the `Wrapper` of the type of the field.
+    fn wrapper_ty(&self) -> quote::Tokens {
+        let ty = self.ty;
+        match self.index {
+            Some(_) => quote! { Option<Option<#ty>> },
+            None => quote! {
+                <#ty as slha::internal::WrappedBlock<slha::errors::Error>>::Wrapper
+            },
+        }
+    }
+
+    /// The expression that reads `block` into the variable of the field.
+    fn parse_into(&self) -> quote::Tokens {
+        let ty = self.ty;
+        let name = self.name;
+        let match_str = &self.block_name;
+        match self.index {
+            Some(ref index) => {
+                let key = index_key(index);
+                quote! {
+                    slha::internal::parse_entry(&block, &mut #name, #match_str, &#key)
+                }
+            }
+            None => quote! {
+                <#ty as slha::internal::WrappedBlock<slha::errors::Error>>::parse_into(&block, &mut #name, #match_str)
+            },
+        }
+    }
+
+    /// The expression that extracts the value of the field from its wrapper after all blocks have
+    /// been read.
+    fn unwrapped(&self) -> quote::Tokens {
+        let name = self.name;
+        let ty = self.ty;
+        let name_str = &self.block_name;
+        match self.index {
+            Some(ref index) => {
+                let key = index.iter().map(|i| i.to_string()).collect::<Vec<_>>().join(" ");
+                quote! {
+                    slha::internal::unwrap_entry(#name_str, #name, #key)
+                }
+            }
+            None => quote! {
+                <#ty as slha::internal::WrappedBlock<slha::errors::Error>>::unwrap(#name_str, #name)
+            },
+        }
+    }
 }
 
 fn normalize_attrs(attrs: &[syn::Attribute]) -> Vec<&syn::NestedMetaItem> {
@@ -96,26 +354,70 @@ fn normalize_attrs(attrs: &[syn::Attribute]) -> Vec<&syn::NestedMetaItem> {
     norm
 }
 
-fn extract_block_name(attrs: &[&syn::NestedMetaItem]) -> Option<String> {
-    let rename = syn::Ident::new("rename");
+fn extract_name_value<'a>(attrs: &[&'a syn::NestedMetaItem], key: &str) -> Option<&'a syn::Lit> {
+    let key = syn::Ident::new(key);
     for attr in attrs {
         let item = match **attr {
             syn::NestedMetaItem::Literal(_) => continue,
             syn::NestedMetaItem::MetaItem(ref item) => item,
         };
-        let new_lit = match *item {
-            syn::MetaItem::NameValue(ref name, ref lit) if name == &rename => lit,
+        match *item {
+            syn::MetaItem::NameValue(ref name, ref lit) if name == &key => return Some(lit),
             _ => continue,
-        };
-        let new_name = match *new_lit {
-            syn::Lit::Str(ref str, _) => str.clone(),
-            _ => panic!("Invalid block name, only strings are allowed in the 'rename' attribute"),
-        };
-        return Some(new_name);
+        }
     }
     None
 }
 
+fn extract_str_attr(attrs: &[&syn::NestedMetaItem], key: &str) -> Option<String> {
+    extract_name_value(attrs, key).map(|lit| match *lit {
+        syn::Lit::Str(ref str, _) => str.clone(),
+        _ => panic!("Invalid block name, only strings are allowed in the '{}' attribute", key),
+    })
+}
+
+/// Reads the key given in the `index` attribute.
+///
+/// The key is either an integer or a string containing one or more integers separated by
+/// whitespace, like the key of a line in an SLHA file.
+fn extract_index(attrs: &[&syn::NestedMetaItem]) -> Option<Vec<i64>> {
+    let invalid = "Invalid index, the 'index' attribute must be an integer or a string of integers";
+    extract_name_value(attrs, "index").map(|lit| match *lit {
+        syn::Lit::Int(index, _) => vec![index as i64],
+        syn::Lit::Str(ref str, _) => {
+            let index: Vec<i64> = str.split_whitespace()
+                .map(|i| i.parse().expect(invalid))
+                .collect();
+            if index.is_empty() {
+                panic!("{}", invalid);
+            }
+            index
+        }
+        _ => panic!("{}", invalid),
+    })
+}
+
+/// The key of the entry read by a field with an `index` attribute.
+///
+/// A single integer is an `i64`, while several integers give a tuple of `i64`.
+fn index_key(index: &[i64]) -> quote::Tokens {
+    match index.len() {
+        1 => {
+            let index = index[0];
+            quote! { #index }
+        }
+        _ => quote! { (#(#index),*) },
+    }
+}
+
+fn extract_case_sensitive(attrs: &[&syn::NestedMetaItem]) -> bool {
+    let case_sensitive = syn::Ident::new("case_sensitive");
+    attrs.iter().any(|attr| match **attr {
+        syn::NestedMetaItem::MetaItem(syn::MetaItem::Word(ref name)) => name == &case_sensitive,
+        _ => false,
+    })
+}
+
 fn extract_decays<'a>(fields: &'a [syn::Field]) -> (Vec<Block<'a>>, bool) {
     let mut blocks = Vec::new();
     let mut decay = false;
@@ -135,10 +437,9 @@ fn generate_let_bindings(blocks: &[Block], has_decays: bool) -> Vec<quote::Token
         .iter()
         .map(|field| {
             let name = field.name;
-            let ty = &field.ty;
+            let ty = field.wrapper_ty();
             quote! {
-                let mut #name: <#ty as slha::internal::WrappedBlock<slha::errors::Error>>::Wrapper =
-                    <#ty as slha::internal::WrappedBlock<slha::errors::Error>>::Wrapper::default();
+                let mut #name: #ty = ::std::default::Default::default();
             }
         })
         .collect();
@@ -152,8 +453,26 @@ fn generate_let_bindings(blocks: &[Block], has_decays: bool) -> Vec<quote::Token
     bindings
 }
 
-fn generate_match_arm_decays(has_decays: bool) -> quote::Tokens {
-    if has_decays {
+fn generate_match_arm_decays(has_decays: bool, mode: Mode) -> quote::Tokens {
+    if has_decays && mode == Mode::Lenient {
+        quote! {
+            slha::internal::Segment::Decay { pdg_id, width, decays: decay_table } => {
+                let duplicate = decays.insert(pdg_id, slha::DecayTable { width, decays: decay_table });
+                if duplicate.is_some() && decays_failed.is_none() {
+                    decays_failed = Some(slha::errors::ErrorKind::DuplicateDecay(pdg_id).into());
+                }
+            },
+        }
+    } else if has_decays && mode == Mode::Report {
+        quote! {
+            slha::internal::Segment::Decay { pdg_id, width, decays: decay_table } => {
+                let duplicate = decays.insert(pdg_id, slha::DecayTable { width, decays: decay_table });
+                if duplicate.is_some() {
+                    errors.push(slha::errors::ErrorKind::DuplicateDecay(pdg_id).into());
+                }
+            },
+        }
+    } else if has_decays {
         quote! {
             slha::internal::Segment::Decay { pdg_id, width, decays: decay_table } => {
                 let duplicate = decays.insert(pdg_id, slha::DecayTable { width, decays: decay_table });
@@ -169,8 +488,8 @@ fn generate_match_arm_decays(has_decays: bool) -> quote::Tokens {
     }
 }
 
-fn generate_match_arm_blocks(blocks: &[Block]) -> quote::Tokens {
-    let arms = generate_match_arms_block_name(blocks);
+fn generate_match_arm_blocks(blocks: &[Block], mode: Mode) -> quote::Tokens {
+    let arms = generate_match_arms_block_name(blocks, mode);
     if arms.is_empty() {
         quote!{
             slha::internal::Segment::Block { .. } => continue,
@@ -187,20 +506,182 @@ fn generate_match_arm_blocks(blocks: &[Block]) -> quote::Tokens {
     }
 }
 
-fn generate_match_arms_block_name(blocks: &[Block]) -> Vec<quote::Tokens> {
+fn generate_match_arms_block_name(blocks: &[Block], mode: Mode) -> Vec<quote::Tokens> {
+    // Several fields may read from the same block, so all of them go into a single match arm.
+    let mut arms: Vec<(&str, Vec<quote::Tokens>)> = Vec::new();
+    for (i, block) in blocks.iter().enumerate() {
+        let match_str = &block.block_name;
+        let parse_into = block.parse_into();
+        let parse = match mode {
+            Mode::Strict => quote! {
+                #parse_into?;
+            },
+            Mode::Report => quote! {
+                if let Err(err) = #parse_into {
+                    errors.push(err);
+                    failed[#i] = true;
+                }
+            },
+            Mode::Lenient => quote! {
+                if failed[#i].is_none() {
+                    if let Err(err) = #parse_into {
+                        failed[#i] = Some(err);
+                    }
+                }
+            },
+        };
+        match arms.iter().position(|&(arm, _)| arm == match_str) {
+            Some(i) => arms[i].1.push(parse),
+            None => arms.push((match_str, vec![parse])),
+        }
+    }
+    arms.into_iter()
+        .map(|(match_str, parse)| {
+            quote! {
+                #match_str => {
+                    #(#parse)*
+                }
+            }
+        })
+        .collect()
+}
+
+fn generate_unwrapped_bindings(blocks: &[Block]) -> Vec<quote::Tokens> {
+    blocks
+        .iter()
+        .map(|field| {
+            let name = field.name;
+            let unwrapped = field.unwrapped();
+            quote! {
+                let #name = slha::internal::collect_missing(#unwrapped, &mut missing_blocks)?;
+            }
+        })
+        .collect()
+}
+
+fn generate_report_unwrapped_bindings(blocks: &[Block]) -> Vec<quote::Tokens> {
     blocks
         .iter()
-        .map(|block| {
-            let ty = block.ty;
-            let name = block.name;
-            let match_str = &block.block_name;
+        .enumerate()
+        .map(|(i, field)| {
+            let name = field.name;
+            let unwrapped = field.unwrapped();
+            quote! {
+                let #name = if failed[#i] {
+                    None
+                } else {
+                    match slha::internal::collect_missing(#unwrapped, &mut missing_blocks) {
+                        Ok(value) => value,
+                        Err(err) => {
+                            errors.push(err);
+                            None
+                        }
+                    }
+                };
+            }
+        })
+        .collect()
+}
+
+/// Generates the code that stores an error returned for a whole segment in the affected fields.
+fn generate_lenient_segment_error(blocks: &[Block], has_decays: bool) -> quote::Tokens {
+    // Several fields may read from the same block, so all of them go into a single match arm.
+    let mut arms: Vec<(&str, Vec<quote::Tokens>)> = Vec::new();
+    for (i, block) in blocks.iter().enumerate() {
+        let match_str = &block.block_name;
+        let store = quote! {
+            if failed[#i].is_none() {
+                failed[#i] = Some(slha::internal::take_block_error(&mut err, #match_str));
+            }
+        };
+        match arms.iter().position(|&(arm, _)| arm == match_str) {
+            Some(i) => arms[i].1.push(store),
+            None => arms.push((match_str, vec![store])),
+        }
+    }
+    let arms: Vec<_> = arms.into_iter()
+        .map(|(match_str, store)| {
             quote! {
                 #match_str => {
-                    <#ty as slha::internal::WrappedBlock<slha::errors::Error>>::parse_into(&block, &mut #name, #match_str)?;
+                    let mut err = Some(err);
+                    #(#store)*
                 }
             }
         })
-        .collect()
+        .collect();
+    let blocks = if arms.is_empty() {
+        quote!{}
+    } else {
+        quote! {
+            if let Some(name) = slha::internal::failed_block_name(&err) {
+                match name.as_ref() {
+                    #(#arms)*
+                    _ => {}
+                }
+                continue;
+            }
+        }
+    };
+    let decays = if has_decays {
+        quote! {
+            if slha::internal::is_decay_error(&err) && decays_failed.is_none() {
+                decays_failed = Some(err);
+            }
+        }
+    } else {
+        quote!{}
+    };
+    quote! {
+        #blocks
+        #decays
+    }
+}
+
+fn generate_lenient_unwrapped_bindings(blocks: &[Block], has_decays: bool) -> Vec<quote::Tokens> {
+    let mut bindings: Vec<_> = blocks
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            let name = field.name;
+            let unwrapped = field.unwrapped();
+            quote! {
+                let #name = match failed[#i].take() {
+                    Some(err) => Err(err),
+                    None => #unwrapped,
+                };
+            }
+        })
+        .collect();
+    if has_decays {
+        bindings.push(quote! {
+            let decays = match decays_failed {
+                Some(err) => Err(err),
+                None => Ok(decays),
+            };
+        });
+    }
+    bindings
+}
+
+fn generate_lenient_fields(blocks: &[Block], decays: Option<&syn::Field>) -> Vec<quote::Tokens> {
+    let mut fields: Vec<_> = blocks
+        .iter()
+        .map(|field| {
+            let name = field.name;
+            let vis = &field.field.vis;
+            let ty = field.ty;
+            quote! {
+                #vis #name: slha::errors::Result<#ty>,
+            }
+        })
+        .collect();
+    if let Some(decays) = decays {
+        let vis = &decays.vis;
+        fields.push(quote! {
+            #vis decays: slha::errors::Result<::std::collections::HashMap<i64, slha::DecayTable>>,
+        });
+    }
+    fields
 }
 
 fn generate_struct_assignments(blocks: &[Block], has_decays: bool) -> Vec<quote::Tokens> {
@@ -208,10 +689,8 @@ fn generate_struct_assignments(blocks: &[Block], has_decays: bool) -> Vec<quote:
         .iter()
         .map(|field| {
             let name = field.name;
-            let ty = field.ty;
-            let name_str = &field.block_name;
             quote! {
-                #name: <#ty as slha::internal::WrappedBlock<slha::errors::Error>>::unwrap(#name_str, #name)?,
+                #name: #name.expect("BUG: Missing blocks should have been reported"),
             }
         })
         .collect();
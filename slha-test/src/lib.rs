@@ -1110,6 +1110,45 @@ Block MINPAR  # SUSY breaking input parameters
     assert_eq!(minpar.map[&5], -100.0);
 }
 
+#[test]
+fn test_case_sensitive() {
+    let input = "\
+Block MASS
+     6    174.3
+Block mass
+     6    172.5
+Block Mass
+     6    173.2
+";
+
+    #[derive(Debug, SlhaDeserialize)]
+    #[slha(case_sensitive)]
+    struct MySlha {
+        mass: Block<i64, f64>,
+        #[slha(rename = "MASS")]
+        mass_upper: Block<i64, f64>,
+        #[slha(rename = "mAsS")]
+        mass_mixed: Option<Block<i64, f64>>,
+    }
+
+    let slha = MySlha::deserialize(input).unwrap();
+    assert_eq!(slha.mass.map[&6], 172.5);
+    assert_eq!(slha.mass_upper.map[&6], 174.3);
+    assert_eq!(slha.mass_mixed, None);
+
+    #[derive(Debug, SlhaDeserialize)]
+    struct MySlhaInsensitive {
+        mass: Option<Block<i64, f64>>,
+    }
+
+    let err = MySlhaInsensitive::deserialize(input).unwrap_err();
+    if let Error(ErrorKind::DuplicateBlock(name), _) = err {
+        assert_eq!(&name, "mass");
+    } else {
+        panic!("Wrong error variant {:?} instead of DuplicateBlock", err);
+    }
+}
+
 macro_rules! expect_invalid_block {
     ($name:expr, $inp:expr, $obj:ident) => {
         let err = $obj::deserialize($inp).unwrap_err();
@@ -2464,4 +2503,732 @@ Block flup Q= 4.64649125e+03
             panic!("Wrong error variant {:?} instead of MissingBlock", err);
         }
     }
+
+    #[test]
+    fn several() {
+        #[derive(Debug, SlhaDeserialize)]
+        struct MySlha {
+            yu: Block<(i8, i8), f64>,
+            yd: Block<(i8, i8), f64>,
+            ye: BlockSingle<f64>,
+            yf: Block<(i8, i8), f64>,
+            yg: TakeFirst<Block<(i8, i8), f64>>,
+            yh: Option<Block<(i8, i8), f64>>,
+        }
+
+        let err = MySlha::deserialize(INPUT).unwrap_err();
+        if let Error(ErrorKind::MissingBlocks(names), _) = err {
+            assert_eq!(names, vec!["yu", "ye", "yg"]);
+        } else {
+            panic!("Wrong error variant {:?} instead of MissingBlocks", err);
+        }
+    }
+}
+
+mod sum {
+    use slha::{SlhaDeserialize, Block, BlockSingle};
+    use slha::modifier::Sum;
+    use slha::errors::*;
+
+    #[test]
+    fn block_single() {
+        let input = "\
+Block WIDTHQCD Q= 100
+     1.25e-01
+Block MASS
+     6    173.2
+Block WIDTHQCD Q= 200
+     2.5e-01
+";
+
+        #[derive(Debug, SlhaDeserialize)]
+        struct MySlha {
+            widthqcd: Sum<BlockSingle<f64>>,
+        }
+
+        let slha = MySlha::deserialize(input).unwrap();
+        assert_eq!(slha.widthqcd.value, 3.75e-01);
+        assert_eq!(slha.widthqcd.scale, Some(100.));
+    }
+
+    #[test]
+    fn block() {
+        let input = "\
+Block PARTIAL
+     1     0.5
+     2     0.25
+Block PARTIAL
+     2     0.5
+     3     1.0
+";
+
+        #[derive(Debug, SlhaDeserialize)]
+        struct MySlha {
+            partial: Sum<Block<i8, f64>>,
+        }
+
+        let slha = MySlha::deserialize(input).unwrap();
+        assert_eq!(slha.partial.map.len(), 3);
+        assert_eq!(slha.partial.map[&1], 0.5);
+        assert_eq!(slha.partial.map[&2], 0.75);
+        assert_eq!(slha.partial.map[&3], 1.0);
+    }
+
+    #[test]
+    fn missing() {
+        #[derive(Debug, SlhaDeserialize)]
+        struct MySlha {
+            widthqcd: Sum<BlockSingle<f64>>,
+        }
+
+        let err = MySlha::deserialize("").unwrap_err();
+        if let Error(ErrorKind::MissingBlock(name), _) = err {
+            assert_eq!(&name, "widthqcd");
+        } else {
+            panic!("Wrong error variant {:?} instead of MissingBlock", err);
+        }
+    }
+}
+
+mod exact_len {
+    use slha::{SlhaDeserialize, Block};
+    use slha::modifier::ExactLen;
+    use slha::errors::*;
+
+    type Mixing2x2 = ExactLen<4, Block<(u8, u8), f64>>;
+
+    #[test]
+    fn exact() {
+        let input = "\
+Block stopmix  # stop mixing matrix
+  1  1     5.37975095e-01   # O_{11}
+  1  2     8.42960733e-01   # O_{12}
+  2  1     8.42960733e-01   # O_{21}
+  2  2    -5.37975095e-01   # O_{22}
+";
+
+        #[derive(Debug, SlhaDeserialize)]
+        struct MySlha {
+            stopmix: Mixing2x2,
+        }
+
+        let slha = MySlha::deserialize(input).unwrap();
+        assert_eq!(slha.stopmix.map.len(), 4);
+        assert_eq!(slha.stopmix.map[&(1, 2)], 8.42960733e-01);
+    }
+
+    #[test]
+    fn missing_entry() {
+        let input = "\
+Block stopmix  # stop mixing matrix
+  1  1     5.37975095e-01   # O_{11}
+  1  2     8.42960733e-01   # O_{12}
+  2  2    -5.37975095e-01   # O_{22}
+";
+
+        #[derive(Debug, SlhaDeserialize)]
+        struct MySlha {
+            stopmix: Mixing2x2,
+        }
+
+        let err = MySlha::deserialize(input).unwrap_err();
+        if let Error(ErrorKind::InvalidBlock(ref name), _) = err {
+            assert_eq!(name, "stopmix");
+        } else {
+            panic!("Wrong error variant {:?} instead of InvalidBlock", err);
+        }
+        assert_eq!(
+            err.iter().nth(1).unwrap().to_string(),
+            "Expected 4 entries in the block, but found 3"
+        );
+    }
+}
+
+mod tensor3 {
+    use slha::{SlhaDeserialize, Block};
+
+    #[test]
+    fn rvlamlle() {
+        let input = "\
+Block RVLAMLLE Q= 1.0e+03   # lambda_{ijk}
+   1  2  1    1.0e-02
+   1  2  2    2.0e-02
+   1  1  1    0.0
+   1  1  2    0.0
+   2  1  1   -1.0e-02
+   2  1  2   -2.0e-02
+   2  2  1    0.0
+   2  2  2    0.0
+";
+
+        #[derive(Debug, SlhaDeserialize)]
+        struct MySlha {
+            rvlamlle: Block<(i8, i8, i8), f64>,
+        }
+
+        let slha = MySlha::deserialize(input).unwrap();
+        assert_eq!(slha.rvlamlle.scale, Some(1000.));
+        assert_eq!(slha.rvlamlle.map.len(), 8);
+        assert_eq!(slha.rvlamlle.map[&(1, 2, 2)], 2.0e-02);
+        let (n, tensor) = slha.rvlamlle.to_tensor3().unwrap();
+        assert_eq!(n, 2);
+        assert_eq!(tensor[1][0][0], -1.0e-02);
+    }
+}
+
+mod to_typed {
+    use slha::{Slha, SlhaDeserialize, Block, BlockSingle, DecayTable};
+    use slha::modifier::TakeLast;
+    use slha::errors::*;
+    use std::collections::HashMap;
+
+    const INPUT: &'static str = "\
+Block MASS
+    6    173.2
+Block ALPHA
+    -1.1e-01
+Block ALPHA
+    -1.2e-01
+Block ye Q= 4.64649125e+02
+    3  3 9.97405356e-02
+Block ye Q= 4.64649125e+03
+    3  3 9.97405356e-03
+DECAY 6 1.35
+    1   2   5   24
+DECAY 25 4.07e-03
+    1   2   5   -5
+";
+
+    #[derive(Debug, SlhaDeserialize)]
+    struct MySlha {
+        mass: Block<i64, f64>,
+        alpha: TakeLast<BlockSingle<f64>>,
+        ye: Vec<Block<(u8, u8), f64>>,
+        yu: Option<Block<(u8, u8), f64>>,
+        decays: HashMap<i64, DecayTable>,
+    }
+
+    #[test]
+    fn same_as_deserialize() {
+        let slha = Slha::parse(INPUT).unwrap();
+        let typed: MySlha = slha.to_typed().unwrap();
+        let deserialized = MySlha::deserialize(INPUT).unwrap();
+        assert_eq!(typed.mass, deserialized.mass);
+        assert_eq!(typed.mass.map[&6], 173.2);
+        assert_eq!(typed.alpha.value, -1.2e-01);
+        assert_eq!(typed.ye, deserialized.ye);
+        assert_eq!(typed.ye.len(), 2);
+        assert_eq!(typed.ye[1].scale, Some(4.64649125e+03));
+        assert_eq!(typed.yu, None);
+        assert_eq!(typed.decays, deserialized.decays);
+        assert_eq!(typed.decays.len(), 2);
+    }
+
+    #[test]
+    fn missing_block() {
+        #[derive(Debug, SlhaDeserialize)]
+        struct Other {
+            mass: Block<i64, f64>,
+            yu: Block<(u8, u8), f64>,
+        }
+
+        let slha = Slha::parse(INPUT).unwrap();
+        let err = slha.to_typed::<Other>().unwrap_err();
+        if let Error(ErrorKind::MissingBlock(name), _) = err {
+            assert_eq!(&name, "yu");
+        } else {
+            panic!("Wrong error variant {:?} instead of MissingBlock", err);
+        }
+    }
+
+    #[test]
+    fn converted_lines() {
+        use slha::ParseOptions;
+        use slha::internal::Segments;
+
+        #[derive(Debug, SlhaDeserialize)]
+        struct Masses {
+            mass: Block<i64, f64>,
+        }
+
+        let input = "\
+Block MASS
+    6    \\
+         173,2
+    5    4,25
+";
+        let options = ParseOptions {
+            line_continuation: true,
+            decimal_comma: true,
+            ..ParseOptions::default()
+        };
+        let slha = Slha::parse_with_options(input, &options).unwrap();
+        let typed: Masses = slha.to_typed().unwrap();
+        assert_eq!(typed.mass.map[&6], 173.2);
+        assert_eq!(typed.mass.map[&5], 4.25);
+        let deserialized = Masses::deserialize_segments(Segments::new(input, options)).unwrap();
+        assert_eq!(deserialized.mass, typed.mass);
+    }
+}
+
+mod index {
+    use slha::{SlhaDeserialize, Block};
+    use slha::errors::*;
+
+    const INPUT: &'static str = "\
+Block SMINPUTS   # Standard Model inputs
+    1   1.27934000e+02   # alpha_em^-1(M_Z)^MSbar
+    3   1.17200000e-01   # alpha_s(M_Z)^MSbar
+    4   9.11876000e+01   # M_Z pole mass
+Block MINPAR
+   -1   2.0e+02
+    3   1.0e+01
+";
+
+    #[derive(Debug, SlhaDeserialize)]
+    struct MySlha {
+        #[slha(block = "sminputs", index = 3)]
+        alpha_s: f64,
+        #[slha(block = "sminputs", index = 4)]
+        mz: f64,
+        #[slha(block = "minpar", index = "-1")]
+        m0: f64,
+        sminputs: Block<i64, f64>,
+    }
+
+    #[test]
+    fn read_index() {
+        let slha = MySlha::deserialize(INPUT).unwrap();
+        assert_eq!(slha.alpha_s, 1.17200000e-01);
+        assert_eq!(slha.mz, 9.11876000e+01);
+        assert_eq!(slha.m0, 2.0e+02);
+        assert_eq!(slha.sminputs.map.len(), 3);
+    }
+
+    #[test]
+    fn missing_key() {
+        #[derive(Debug, SlhaDeserialize)]
+        struct Other {
+            #[slha(block = "sminputs", index = 5)]
+            mb: f64,
+        }
+
+        let err = Other::deserialize(INPUT).unwrap_err();
+        if let Error(ErrorKind::MissingKey(name, key), _) = err {
+            assert_eq!(&name, "sminputs");
+            assert_eq!(&key, "5");
+        } else {
+            panic!("Wrong error variant {:?} instead of MissingKey", err);
+        }
+    }
+
+    #[test]
+    fn tuple_key() {
+        #[derive(Debug, SlhaDeserialize)]
+        struct Other {
+            #[slha(block = "nmix", index = "1 2")]
+            n12: f64,
+            #[slha(block = "nmix", index = "2 1")]
+            n21: f64,
+            #[slha(block = "nmix", index = "1 3")]
+            n13: f64,
+        }
+
+        let input = "\
+Block NMIX
+    1  1   9.9e-01
+    1  2  -1.0e-01
+    2  1   1.0e-01
+";
+        let err = Other::deserialize(input).unwrap_err();
+        if let Error(ErrorKind::MissingKey(name, key), _) = err {
+            assert_eq!(&name, "nmix");
+            assert_eq!(&key, "1 3");
+        } else {
+            panic!("Wrong error variant {:?} instead of MissingKey", err);
+        }
+        let input = format!("{}    1  3   0.0e+00\n", input);
+        let slha = Other::deserialize(&input).unwrap();
+        assert_eq!(slha.n12, -1.0e-01);
+        assert_eq!(slha.n21, 1.0e-01);
+        assert_eq!(slha.n13, 0.);
+    }
+
+    #[test]
+    fn only_entry_is_read() {
+        #[derive(Debug, SlhaDeserialize)]
+        struct Other {
+            #[slha(block = "spinfo", index = 3)]
+            version: i64,
+            #[slha(block = "spinfo", index = 1)]
+            program: String,
+        }
+
+        let input = "\
+Block SPINFO
+    1   SOFTSUSY
+    2   3.4.0
+    3   7
+";
+        let slha = Other::deserialize(input).unwrap();
+        assert_eq!(slha.version, 7);
+        assert_eq!(&slha.program, "SOFTSUSY");
+
+        let input = "\
+Block SPINFO
+    1   SOFTSUSY
+    3   foo
+";
+        let err = Other::deserialize(input).unwrap_err();
+        if let Error(ErrorKind::InvalidBlock(ref name), _) = err {
+            assert_eq!(name, "spinfo");
+        } else {
+            panic!("Wrong error variant {:?} instead of InvalidBlock", err);
+        }
+        assert_eq!(
+            err.iter().nth(1).unwrap().to_string(),
+            "Failed to parse the 2th data line in the body"
+        );
+
+        let input = "\
+Block SPINFO
+    1   SOFTSUSY
+    3   7
+    3   8
+";
+        let err = Other::deserialize(input).unwrap_err();
+        assert_eq!(
+            err.iter().nth(1).unwrap().to_string(),
+            "The key in line 3 appears more than once in the block"
+        );
+    }
+
+    #[test]
+    fn missing_block() {
+        #[derive(Debug, SlhaDeserialize)]
+        struct Other {
+            #[slha(block = "mass", index = 6)]
+            mt: f64,
+        }
+
+        let err = Other::deserialize(INPUT).unwrap_err();
+        if let Error(ErrorKind::MissingBlock(name), _) = err {
+            assert_eq!(&name, "mass");
+        } else {
+            panic!("Wrong error variant {:?} instead of MissingBlock", err);
+        }
+    }
+}
+
+mod report {
+    use slha::{SlhaDeserialize, Block, BlockSingle, DecayTable};
+    use slha::errors::*;
+    use std::collections::HashMap;
+
+    #[derive(Debug, SlhaDeserialize)]
+    struct MySlha {
+        sminputs: Block<i64, f64>,
+        mass: Block<i64, f64>,
+        alpha: BlockSingle<f64>,
+        hmix: Option<Block<i64, f64>>,
+        decays: HashMap<i64, DecayTable>,
+    }
+
+    #[test]
+    fn report_success() {
+        let input = "\
+Block SMINPUTS
+    3   1.17200000e-01
+Block MASS
+    6   1.72e+02
+Block ALPHA
+    -1.13825210e-01
+DECAY   6   1.5
+    1.0   2   5   24
+";
+        let (slha, errors) = MySlha::deserialize_report(input);
+        assert!(errors.is_empty());
+        let slha = slha.unwrap();
+        assert_eq!(slha.sminputs.map[&3], 1.17200000e-01);
+        assert_eq!(slha.mass.map[&6], 1.72e+02);
+        assert_eq!(slha.alpha.value, -1.13825210e-01);
+        assert!(slha.hmix.is_none());
+        assert_eq!(slha.decays[&6].width, 1.5);
+    }
+
+    #[test]
+    fn report_two_bad_blocks() {
+        let input = "\
+Block SMINPUTS
+    3   foo
+Block MASS
+    6   1.72e+02
+Block ALPHA
+    1   -1.13825210e-01
+";
+        let (slha, errors) = MySlha::deserialize_report(input);
+        assert!(slha.is_none());
+        assert_eq!(errors.len(), 2);
+        match errors[0] {
+            Error(ErrorKind::InvalidBlock(ref name), _) => assert_eq!(name, "sminputs"),
+            ref err => panic!("Wrong error variant {:?} instead of InvalidBlock", err),
+        }
+        match errors[1] {
+            Error(ErrorKind::InvalidBlock(ref name), _) => assert_eq!(name, "alpha"),
+            ref err => panic!("Wrong error variant {:?} instead of InvalidBlock", err),
+        }
+    }
+
+    #[test]
+    fn report_bad_and_missing_blocks() {
+        let input = "\
+Block SMINPUTS
+    3   foo
+";
+        let (slha, errors) = MySlha::deserialize_report(input);
+        assert!(slha.is_none());
+        assert_eq!(errors.len(), 2);
+        match errors[0] {
+            Error(ErrorKind::InvalidBlock(ref name), _) => assert_eq!(name, "sminputs"),
+            ref err => panic!("Wrong error variant {:?} instead of InvalidBlock", err),
+        }
+        match errors[1] {
+            Error(ErrorKind::MissingBlocks(ref names), _) => {
+                assert_eq!(names, &["mass".to_string(), "alpha".to_string()])
+            }
+            ref err => panic!("Wrong error variant {:?} instead of MissingBlocks", err),
+        }
+    }
+}
+
+mod lenient {
+    use slha::{Slha, SlhaDeserialize, SlhaDeserializeLenient, Block, BlockSingle, DecayTable};
+    use slha::errors::*;
+    use std::collections::HashMap;
+
+    #[derive(Debug, SlhaDeserialize, SlhaDeserializeLenient)]
+    struct MySlha {
+        sminputs: Block<i64, f64>,
+        #[slha(block = "sminputs", index = 3)]
+        alpha_s: f64,
+        mass: Block<i64, f64>,
+        alpha: BlockSingle<f64>,
+        ye: Block<(u8, u8), f64>,
+        hmix: Option<Block<i64, f64>>,
+        decays: HashMap<i64, DecayTable>,
+    }
+
+    #[test]
+    fn lenient_success() {
+        let input = "\
+Block SMINPUTS
+    3   1.17200000e-01
+Block MASS
+    6   1.72e+02
+Block ALPHA
+    -1.13825210e-01
+Block ye Q= 4.64649125e+02
+    3  3 9.97405356e-02
+DECAY   6   1.5
+    1.0   2   5   24
+";
+        let slha = MySlha::deserialize_lenient(input);
+        let strict = MySlha::deserialize(input).unwrap();
+        assert_eq!(slha.sminputs.unwrap(), strict.sminputs);
+        assert_eq!(slha.alpha_s.unwrap(), strict.alpha_s);
+        assert_eq!(slha.mass.unwrap(), strict.mass);
+        assert_eq!(slha.alpha.unwrap(), strict.alpha);
+        assert_eq!(slha.ye.unwrap(), strict.ye);
+        assert_eq!(slha.hmix.unwrap(), strict.hmix);
+        assert_eq!(slha.decays.unwrap(), strict.decays);
+        assert_eq!(strict.alpha_s, 1.17200000e-01);
+        assert_eq!(strict.ye.map[&(3, 3)], 9.97405356e-02);
+        assert_eq!(strict.decays[&6].width, 1.5);
+    }
+
+    #[test]
+    fn lenient_bad_block() {
+        let input = "\
+Block SMINPUTS
+    3   1.17200000e-01
+Block MASS
+    6   foo
+Block ALPHA
+    -1.13825210e-01
+";
+        let slha = MySlha::deserialize_lenient(input);
+        assert_eq!(slha.sminputs.unwrap().map[&3], 1.17200000e-01);
+        assert_eq!(slha.alpha_s.unwrap(), 1.17200000e-01);
+        match slha.mass {
+            Err(Error(ErrorKind::InvalidBlock(ref name), _)) => assert_eq!(name, "mass"),
+            ref result => panic!("Wrong result {:?} instead of InvalidBlock", result),
+        }
+        assert_eq!(slha.alpha.unwrap().value, -1.13825210e-01);
+        match slha.ye {
+            Err(Error(ErrorKind::MissingBlock(ref name), _)) => assert_eq!(name, "ye"),
+            ref result => panic!("Wrong result {:?} instead of MissingBlock", result),
+        }
+        assert!(slha.hmix.unwrap().is_none());
+        assert!(slha.decays.unwrap().is_empty());
+    }
+
+    #[test]
+    fn lenient_bad_segments() {
+        let input = "\
+Block SMINPUTS Q= foo
+    3   1.17200000e-01
+Block MASS
+    6   1.72e+02
+DECAY   6   1.5
+    1.0   2   5   bar
+Block ALPHA
+    -1.13825210e-01
+";
+        let slha = MySlha::deserialize_lenient(input);
+        match slha.sminputs {
+            Err(Error(ErrorKind::InvalidBlock(ref name), _)) => assert_eq!(name, "sminputs"),
+            ref result => panic!("Wrong result {:?} instead of InvalidBlock", result),
+        }
+        match slha.alpha_s {
+            Err(Error(ErrorKind::InvalidBlock(ref name), _)) => assert_eq!(name, "sminputs"),
+            ref result => panic!("Wrong result {:?} instead of InvalidBlock", result),
+        }
+        assert_eq!(slha.mass.unwrap().map[&6], 1.72e+02);
+        assert_eq!(slha.alpha.unwrap().value, -1.13825210e-01);
+        match slha.decays {
+            Err(Error(ErrorKind::InvalidDecay(6), _)) => (),
+            ref result => panic!("Wrong result {:?} instead of InvalidDecay", result),
+        }
+    }
+
+    #[test]
+    fn to_typed_lenient() {
+        let input = "\
+Block MASS
+    6   1.72e+02
+Block ALPHA
+    1   -1.13825210e-01
+";
+        let slha = Slha::parse(input).unwrap();
+        let typed: MySlhaLenient = slha.to_typed_lenient::<MySlha>();
+        assert_eq!(typed.mass.unwrap().map[&6], 1.72e+02);
+        match typed.alpha {
+            Err(Error(ErrorKind::InvalidBlock(ref name), _)) => assert_eq!(name, "alpha"),
+            ref result => panic!("Wrong result {:?} instead of InvalidBlock", result),
+        }
+        assert!(typed.sminputs.is_err());
+        assert!(typed.decays.unwrap().is_empty());
+    }
+}
+
+mod from_env {
+    use slha::{SlhaDeserialize, Block};
+    use slha::errors::*;
+    use std::{env, fs, process};
+
+    #[derive(Debug, SlhaDeserialize)]
+    struct MySlha {
+        mass: Block<i64, f64>,
+    }
+
+    #[test]
+    fn read_file() {
+        // Include the process id so that concurrent test runs don't share the file.
+        let path = env::temp_dir().join(format!("slha-test-from-env-{}.slha", process::id()));
+        fs::write(&path, "Block MASS\n    6    173.2\n").unwrap();
+        env::set_var("SLHA_TEST_FROM_ENV_READ_FILE", &path);
+        let slha = MySlha::from_env("SLHA_TEST_FROM_ENV_READ_FILE");
+        // Remove the file before anything can panic.
+        let removed = fs::remove_file(&path);
+        assert_eq!(slha.unwrap().mass.map[&6], 173.2);
+        removed.unwrap();
+    }
+
+    #[test]
+    fn unset_var() {
+        env::remove_var("SLHA_TEST_FROM_ENV_UNSET_VAR");
+        let err = MySlha::from_env("SLHA_TEST_FROM_ENV_UNSET_VAR").unwrap_err();
+        if let Error(ErrorKind::EnvVar(name), _) = err {
+            assert_eq!(&name, "SLHA_TEST_FROM_ENV_UNSET_VAR");
+        } else {
+            panic!("Wrong error variant {:?} instead of EnvVar", err);
+        }
+    }
+
+    #[test]
+    fn missing_file() {
+        let path = env::temp_dir().join(format!(
+            "slha-test-from-env-missing-{}.slha",
+            process::id()
+        ));
+        env::set_var("SLHA_TEST_FROM_ENV_MISSING_FILE", &path);
+        let err = MySlha::from_env("SLHA_TEST_FROM_ENV_MISSING_FILE").unwrap_err();
+        if let Error(ErrorKind::ReadFile(name), _) = err {
+            assert_eq!(name, path.to_str().unwrap());
+        } else {
+            panic!("Wrong error variant {:?} instead of ReadFile", err);
+        }
+    }
+}
+
+mod scale_modifiers {
+    use slha::{SlhaDeserialize, Block, BlockSingle};
+    use slha::modifier::{HighestScale, LowestScale};
+    use slha::errors::*;
+
+    const INPUT: &'static str = "\
+Block ye Q= 4.64649125e+02
+    3  3 9.97405356e-02   # Ytau(Q)MSSM DRbar
+Block ye Q= 1.0e+03
+    3  3 1.00000000e-01
+Block ye
+    3  3 1.1e-01
+Block ye Q= 1.0e+03
+    3  3 1.2e-01
+Block ye Q= 9.1e+01
+    3  3 1.3e-01
+Block alpha Q= 1.0e+03
+    -1.1e-01
+";
+
+    #[test]
+    fn highest_scale() {
+        #[derive(Debug, SlhaDeserialize)]
+        struct MySlha {
+            ye: HighestScale<Block<(u8, u8), f64>>,
+            alpha: HighestScale<BlockSingle<f64>>,
+        }
+
+        let slha = MySlha::deserialize(INPUT).unwrap();
+        assert_eq!(slha.ye.scale, Some(1.0e+03));
+        assert_eq!(slha.ye.map[&(3, 3)], 1.00000000e-01);
+        assert_eq!(slha.alpha.value, -1.1e-01);
+    }
+
+    #[test]
+    fn lowest_scale() {
+        #[derive(Debug, SlhaDeserialize)]
+        struct MySlha {
+            ye: LowestScale<Block<(u8, u8), f64>>,
+        }
+
+        let slha = MySlha::deserialize(INPUT).unwrap();
+        assert_eq!(slha.ye.scale, None);
+        assert_eq!(slha.ye.map[&(3, 3)], 1.1e-01);
+    }
+
+    #[test]
+    fn missing_block() {
+        #[derive(Debug, SlhaDeserialize)]
+        struct MySlha {
+            yu: HighestScale<Block<(u8, u8), f64>>,
+        }
+
+        let err = MySlha::deserialize(INPUT).unwrap_err();
+        if let Error(ErrorKind::MissingBlock(name), _) = err {
+            assert_eq!(&name, "yu");
+        } else {
+            panic!("Wrong error variant {:?} instead of MissingBlock", err);
+        }
+    }
 }
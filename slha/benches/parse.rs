@@ -0,0 +1,98 @@
+// Copyright 2017 Torsten Weber
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#[macro_use]
+extern crate criterion;
+extern crate slha;
+
+use criterion::Criterion;
+use slha::{Block, BlockStr, ParseOptions, Slha};
+
+use std::fmt::Write;
+
+/// Generates a `MASS`-like block with `n` entries.
+fn mass_block(n: usize) -> String {
+    let mut input = String::from("BLOCK MASS   # Mass spectrum\n");
+    for i in 0..n {
+        writeln!(input, "   {:>8}     {:.8e}   # particle {}", 1000000 + i, i as f64 * 1.5, i)
+            .unwrap();
+    }
+    input
+}
+
+/// Generates `n` decay tables with 20 decay channels each.
+fn decay_tables(n: usize) -> String {
+    let mut input = mass_block(10);
+    for i in 0..n {
+        writeln!(input, "DECAY   {}   {:.8e}   # particle {}", 1000000 + i, 1.5, i).unwrap();
+        for j in 0..20 {
+            writeln!(input, "   {:.8e}   2   {:>8}   {:>8}", 0.05, 1000000 + j, -24).unwrap();
+        }
+    }
+    input
+}
+
+fn parse_block(c: &mut Criterion) {
+    let input = mass_block(10000);
+    let slha = Slha::parse(&input).unwrap();
+    c.bench_function("Slha::parse (10000 lines)", |b| {
+        b.iter(|| Slha::parse(&input).unwrap())
+    });
+    c.bench_function("Block<i64, f64> (10000 lines)", |b| {
+        b.iter(|| {
+            let block: Block<i64, f64> = slha.get_block("mass").unwrap().unwrap();
+            block
+        })
+    });
+    c.bench_function("BlockStr<f64> (10000 lines)", |b| {
+        b.iter(|| {
+            let block: BlockStr<f64> = slha.get_block("mass").unwrap().unwrap();
+            block
+        })
+    });
+}
+
+fn parse_decays(c: &mut Criterion) {
+    let input = decay_tables(500);
+    c.bench_function("Slha::parse (500 decay tables)", |b| {
+        b.iter(|| Slha::parse(&input).unwrap())
+    });
+    c.bench_function("Slha::parse_blocks_only (500 decay tables)", |b| {
+        b.iter(|| Slha::parse_blocks_only(&input, &ParseOptions::default()).unwrap())
+    });
+    c.bench_function("Slha::parse_blocks_only + get_decay (500 decay tables)", |b| {
+        b.iter(|| {
+            let slha = Slha::parse_blocks_only(&input, &ParseOptions::default()).unwrap();
+            slha.get_decay(1000000).unwrap().width
+        })
+    });
+}
+
+fn build_block(c: &mut Criterion) {
+    c.bench_function("Block::insert (10000 entries)", |b| {
+        b.iter(|| {
+            let mut block = Block { scale: None, map: Default::default() };
+            for i in 0..10000_i64 {
+                block.map.insert(i, i as f64);
+            }
+            block
+        })
+    });
+    c.bench_function("Block::with_capacity + insert (10000 entries)", |b| {
+        b.iter(|| {
+            let mut block = Block::with_capacity(10000);
+            for i in 0..10000_i64 {
+                block.map.insert(i, i as f64);
+            }
+            block
+        })
+    });
+}
+
+criterion_group!(benches, parse_block, parse_decays, build_block);
+criterion_main!(benches);
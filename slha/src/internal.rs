@@ -9,10 +9,11 @@
 //! A module containing parts of the internal API that have to be public for them to be usable by
 //! the code generated by the `slha-derive` crate.
 
-use {Line, SlhaBlock, RawBlock, Decay, ParseResult, Parseable};
+use {Line, SlhaBlock, RawBlock, Decay, ParseOptions, ParseResult, Parseable};
 use errors::*;
 
 use std::{iter, result, str};
+use std::borrow::Cow;
 
 /// A segment read from an SLHA file, i.e. either a block or a decay table.
 #[derive(Clone, Debug, PartialEq)]
@@ -23,6 +24,25 @@ pub enum Segment<'a> {
         width: f64,
         decays: Vec<Decay>,
     },
+    /// A block that has been commented out, i.e. whose header reads `# BLOCK ...`.
+    ///
+    /// This is only returned if `recognize_commented_blocks` is set.
+    DisabledBlock { name: String, block: RawBlock<'a> },
+    /// A segment that is neither a block nor a decay table, e.g. `INFO`.
+    ///
+    /// This is only returned if `keep_unknown_segments` is set.
+    Unknown { name: String, lines: Vec<Line<'a>> },
+}
+
+/// A segment read from an SLHA file, where the body of decay tables has not been parsed yet.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LazySegment<'a> {
+    Segment(Segment<'a>),
+    Decay {
+        pdg_id: i64,
+        width: f64,
+        lines: Vec<&'a str>,
+    },
 }
 
 /// A trait for blocks for which `SlhaDeserialize` can be automatically derived.
@@ -64,6 +84,123 @@ where
     }
 }
 
+/// Collects the name of the block if `result` is a `MissingBlock` error.
+///
+/// All other errors are passed on, while successfully read blocks are returned wrapped in a
+/// `Some`.
+/// This allows the derived code to report all missing blocks at once using `check_missing`.
+pub fn collect_missing<T>(result: Result<T>, missing: &mut Vec<String>) -> Result<Option<T>> {
+    match result {
+        Ok(block) => Ok(Some(block)),
+        Err(Error(ErrorKind::MissingBlock(name), _)) => {
+            missing.push(name);
+            Ok(None)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// Returns an error if any of the blocks collected by `collect_missing` is missing.
+///
+/// If only a single block is missing, a `MissingBlock` error is returned, otherwise a
+/// `MissingBlocks` error containing the names of all missing blocks.
+pub fn check_missing(mut missing: Vec<String>) -> Result<()> {
+    match missing.len() {
+        0 => Ok(()),
+        1 => Err(ErrorKind::MissingBlock(missing.remove(0)).into()),
+        _ => Err(ErrorKind::MissingBlocks(missing).into()),
+    }
+}
+
+/// Returns the name of the block if `err` was returned for the header of a block.
+///
+/// This is used by the derived `deserialize_segments_lenient` to store the error in the fields
+/// that read from the block.
+pub fn failed_block_name(err: &Error) -> Option<String> {
+    match *err.kind() {
+        ErrorKind::InvalidBlock(ref name) => Some(name.clone()),
+        _ => None,
+    }
+}
+
+/// Returns true if `err` was returned while reading a decay table.
+pub fn is_decay_error(err: &Error) -> bool {
+    matches!(*err.kind(), ErrorKind::InvalidDecay(_) | ErrorKind::InvalidDecayingPdgId)
+}
+
+/// Takes the error of a block that could not be read.
+///
+/// The first call returns the original error, while every further call for a block read into
+/// several fields returns a new `InvalidBlock` error.
+pub fn take_block_error(err: &mut Option<Error>, name: &str) -> Error {
+    err.take().unwrap_or_else(|| ErrorKind::InvalidBlock(name.to_string()).into())
+}
+
+/// Reads the entry with the given key of a block into a field with an `index` attribute.
+///
+/// The wrapper is `None` as long as the block has not been found, and contains the value of the
+/// entry once it has been read, or `None` if the block does not contain the key.
+/// Only the keys of the lines are read to find the entry, and only the value of the entry
+/// itself, so the other lines of the block do not have to be readable.
+/// A `DuplicateBlock` error is returned if the block is found twice.
+pub fn parse_entry<'a, K, V>(
+    block: &RawBlock<'a>,
+    wrapped: &mut Option<Option<V>>,
+    name: &str,
+    key: &K,
+) -> Result<()>
+where
+    K: Parseable + PartialEq,
+    V: Parseable,
+{
+    if wrapped.is_some() {
+        bail!(ErrorKind::DuplicateBlock(name.to_string()));
+    }
+    let value = block.parse_logical(|lines| find_entry(lines, key))
+        .chain_err(|| ErrorKind::InvalidBlock(name.to_string()))?;
+    *wrapped = Some(value);
+    Ok(())
+}
+
+/// Reads the value of the line with the given key, if there is one.
+///
+/// Lines whose key can not be read as a `K` are skipped, while a `DuplicateKey` error is returned
+/// if the key is found on more than one line.
+fn find_entry<K, V>(lines: &[Line], key: &K) -> Result<Option<V>>
+where
+    K: Parseable + PartialEq,
+    V: Parseable,
+{
+    let mut value = None;
+    for (i, line) in lines.iter().enumerate() {
+        let rest = match K::parse(line.data.trim_start()).to_result() {
+            Ok((rest, ref found)) if found == key => rest,
+            _ => continue,
+        };
+        if value.is_some() {
+            bail!(ErrorKind::DuplicateKey(i + 1));
+        }
+        let parsed = V::parse(rest)
+            .end()
+            .chain_err(|| ErrorKind::InvalidBlockValue)
+            .chain_err(|| ErrorKind::InvalidBlockLine(i + 1))?;
+        value = Some(parsed);
+    }
+    Ok(value)
+}
+
+/// Returns the value read by `parse_entry`.
+///
+/// A `MissingBlock` error is returned if the block has not been found, and a `MissingKey` error
+/// containing `key` if it does not contain the entry.
+pub fn unwrap_entry<V>(name: &str, wrapped: Option<Option<V>>, key: &str) -> Result<V> {
+    match wrapped {
+        None => Err(ErrorKind::MissingBlock(name.to_string()).into()),
+        Some(None) => Err(ErrorKind::MissingKey(name.to_string(), key.to_string()).into()),
+        Some(Some(value)) => Ok(value),
+    }
+}
+
 impl<T> WrappedBlock<Error> for Option<T>
 where
     T: SlhaBlock,
@@ -118,29 +255,93 @@ where
     }
 }
 
+/// An iterator over the segments of an SLHA file.
+pub struct Segments<'a> {
+    lines: iter::Peekable<str::Lines<'a>>,
+    options: ParseOptions,
+}
+impl<'a> Segments<'a> {
+    pub fn new(input: &'a str, options: ParseOptions) -> Segments<'a> {
+        Segments {
+            lines: input.lines().peekable(),
+            options,
+        }
+    }
+}
+impl<'a> Iterator for Segments<'a> {
+    type Item = Result<Segment<'a>>;
+    fn next(&mut self) -> Option<Result<Segment<'a>>> {
+        parse_segment_with_options(&mut self.lines, &self.options)
+    }
+}
+
+/// An iterator over the segments of an SLHA file that continues after a segment could not be read.
+///
+/// After an error, the rest of the failed segment is skipped, so that the next item is the
+/// segment following it.
+pub struct LenientSegments<'a> {
+    lines: iter::Peekable<str::Lines<'a>>,
+    options: ParseOptions,
+}
+impl<'a> LenientSegments<'a> {
+    pub fn new(input: &'a str, options: ParseOptions) -> LenientSegments<'a> {
+        LenientSegments {
+            lines: input.lines().peekable(),
+            options,
+        }
+    }
+}
+impl<'a> Iterator for LenientSegments<'a> {
+    type Item = Result<Segment<'a>>;
+    fn next(&mut self) -> Option<Result<Segment<'a>>> {
+        let segment = parse_segment_with_options(&mut self.lines, &self.options);
+        if let Some(Err(_)) = segment {
+            skip_segment_body(&mut self.lines);
+        }
+        segment
+    }
+}
+
 /// Parses a segment from an SLHA file, i.e. either a block or a decay table.
 pub fn parse_segment<'a>(
     input: &mut iter::Peekable<str::Lines<'a>>,
 ) -> Option<Result<Segment<'a>>> {
-    skip_empty_lines(input);
-    match input.next() {
-        Some(line) => Some(parse_segment_line(line, input)),
-        None => None,
-    }
+    parse_segment_with_options(input, &ParseOptions::default())
+}
+
+/// Parses a segment from an SLHA file using non-default `ParseOptions`.
+pub fn parse_segment_with_options<'a>(
+    input: &mut iter::Peekable<str::Lines<'a>>,
+    options: &ParseOptions,
+) -> Option<Result<Segment<'a>>> {
+    skip_empty_lines_with_options(input, options);
+    input.next().map(|line| parse_segment_line(line, input, options))
 }
 
 fn parse_segment_line<'a>(
     line: &'a str,
     input: &mut iter::Peekable<str::Lines<'a>>,
+    options: &ParseOptions,
 ) -> Result<Segment<'a>> {
+    if options.recognize_commented_blocks {
+        if let Some(header) = commented_block_header(line, options) {
+            return parse_disabled_block(header, input, options);
+        }
+    }
     if line.starts_with(|c: char| c.is_whitespace()) {
+        if options.blank_terminates {
+            return Ok(parse_anonymous_block(line, input, options));
+        }
         bail!(ErrorKind::UnexpectedIdent(line.to_string()));
     }
     match next_word(line) {
         Some((kw, rest)) => {
             match kw.to_lowercase().as_ref() {
-                "block" => parse_block(rest, input),
-                "decay" => parse_decay_table(rest, input),
+                "block" => parse_block(rest, input, options, true),
+                "decay" => parse_decay_table(rest, input, options),
+                _ if options.keep_unknown_segments => {
+                    Ok(parse_unknown_segment(kw, rest, input, options))
+                }
                 kw => bail!(ErrorKind::UnknownSegment(kw.to_string())),
             }
         }
@@ -148,46 +349,399 @@ fn parse_segment_line<'a>(
     }
 }
 
-fn parse_block<'a, Iter>(header: &str, input: &mut iter::Peekable<Iter>) -> Result<Segment<'a>>
+/// Reads a block, where `active` is not set for blocks that have been commented out.
+fn parse_block<'a, Iter>(
+    header: &str,
+    input: &mut iter::Peekable<Iter>,
+    options: &ParseOptions,
+    active: bool,
+) -> Result<Segment<'a>>
+where
+    Iter: Iterator<Item = &'a str>,
+{
+    let (name, scale) = parse_block_header(&convert_data(header, options), options)?;
+    let mut lines = if !active && input.peek().is_some_and(|line| is_commented_data_line(line)) {
+        parse_commented_block_body(input, options)
+    } else {
+        parse_block_body(Vec::new(), input, options)
+    };
+    if options.skip_block_header_row && lines.first().is_some_and(is_header_row) {
+        lines.remove(0);
+    }
+    Ok(Segment::Block {
+        name,
+        block: RawBlock {
+            logical: logical_data(&lines, options),
+            lines,
+            scale,
+        },
+    })
+}
+
+/// Checks if a line is a header row naming the columns, i.e. if it does not start with a number.
+fn is_header_row(line: &Line) -> bool {
+    match next_word(line.data) {
+        Some((word, _)) => f64::parse(word).end().is_err(),
+        None => false,
+    }
+}
+
+/// Reads a segment that is neither a block nor a decay table.
+///
+/// The rest of the header line, if it is not empty, is the first line of the segment.
+/// The segment extends up to the next `BLOCK` or `DECAY` line and, unlike the body of a block, may
+/// contain lines that are not indented.
+/// Comments and empty lines are skipped as usual.
+fn parse_unknown_segment<'a, Iter>(
+    name: &str,
+    header: &'a str,
+    input: &mut iter::Peekable<Iter>,
+    options: &ParseOptions,
+) -> Segment<'a>
 where
     Iter: Iterator<Item = &'a str>,
 {
-    let (name, scale) = parse_block_header(header)?;
     let mut lines = Vec::new();
-    loop {
-        {
-            skip_empty_lines(input);
-            let line = match input.peek() {
-                Some(line) => line,
-                None => break,
-            };
-            if !line.starts_with(|c: char| c.is_whitespace()) {
-                break;
+    let mut current = Some(header);
+    while let Some(line) = current {
+        let (data, comment) = split_comment(line.trim());
+        if !data.is_empty() {
+            lines.push(Line { data, comment });
+        }
+        skip_empty_lines_with_options(input, options);
+        current = match input.peek() {
+            // The only comments left are commented out block headers.
+            Some(line) if !is_segment_header(line) && !line.trim_start().starts_with('#') => {
+                input.next()
             }
-            let (data, comment) = split_comment(line.trim());
+            _ => None,
+        };
+    }
+    Segment::Unknown {
+        name: name.to_string(),
+        lines,
+    }
+}
+
+/// Checks whether `line` starts a block or a decay table.
+fn is_segment_header(line: &str) -> bool {
+    if line.starts_with(|c: char| c.is_whitespace()) {
+        return false;
+    }
+    match next_word(line) {
+        Some((kw, _)) => kw.eq_ignore_ascii_case("block") || kw.eq_ignore_ascii_case("decay"),
+        None => false,
+    }
+}
+
+/// Reads a block that has been commented out, i.e. everything after the `# BLOCK` of its header.
+///
+/// If the header is directly followed by commented out data lines, these make up the body,
+/// see `parse_commented_block_body`.
+/// Otherwise only the header has been commented out and the body is read exactly like that of an
+/// active block.
+fn parse_disabled_block<'a, Iter>(
+    header: &str,
+    input: &mut iter::Peekable<Iter>,
+    options: &ParseOptions,
+) -> Result<Segment<'a>>
+where
+    Iter: Iterator<Item = &'a str>,
+{
+    match parse_block(header, input, options, false)? {
+        Segment::Block { name, block } => Ok(Segment::DisabledBlock { name, block }),
+        _ => unreachable!("BUG: parse_block MUST return a block."),
+    }
+}
+
+/// Returns the rest of the header if `line` is the header of a commented out block.
+///
+/// A commented out block header is a comment whose first word is `BLOCK` (ignoring case),
+/// followed by a valid block header, e.g. `# BLOCK MASS`.
+/// A comment like `# Block masses in GeV` is not a block header, since `in GeV` is not a scale.
+fn commented_block_header<'a>(line: &'a str, options: &ParseOptions) -> Option<&'a str> {
+    let line = line.trim_start();
+    if !line.starts_with('#') {
+        return None;
+    }
+    match next_word(line.trim_start_matches('#')) {
+        Some((kw, rest)) if kw.eq_ignore_ascii_case("block") => {
+            parse_block_header(rest, options).ok().map(|_| rest)
+        }
+        _ => None,
+    }
+}
+
+/// Checks whether `line` is a data line that has been commented out, e.g. `#   6    173.2`.
+fn is_commented_data_line(line: &str) -> bool {
+    let line = line.trim_start();
+    if !line.starts_with('#') {
+        return false;
+    }
+    match next_word(line.trim_start_matches('#')) {
+        Some((word, _)) => f64::parse(word).end().is_ok(),
+        None => false,
+    }
+}
+
+/// Checks whether the next line of `input` is a commented out block header starting a disabled
+/// block, if `recognize_commented_blocks` is set.
+fn at_disabled_block<'a, Iter>(input: &mut iter::Peekable<Iter>, options: &ParseOptions) -> bool
+where
+    Iter: Iterator<Item = &'a str>,
+{
+    options.recognize_commented_blocks &&
+        input.peek().is_some_and(|line| {
+            commented_block_header(line, options).is_some()
+        })
+}
+
+/// Reads the commented out data lines making up the body of a disabled block, with the leading
+/// `#` removed.
+///
+/// Empty lines and ordinary comments are skipped as in the body of an active block.
+/// The body ends at the first line that is not commented out, so that data lines of the file are
+/// never moved into a disabled block, and at the next commented out block header.
+fn parse_commented_block_body<'a, Iter>(
+    input: &mut iter::Peekable<Iter>,
+    options: &ParseOptions,
+) -> Vec<Line<'a>>
+where
+    Iter: Iterator<Item = &'a str>,
+{
+    let mut lines = Vec::new();
+    while let Some(&line) = input.peek() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() && !options.blank_terminates {
+            input.next();
+            continue;
+        }
+        if !trimmed.starts_with('#') || at_disabled_block(input, options) {
+            break;
+        }
+        input.next();
+        if is_commented_data_line(trimmed) {
+            let (data, comment) = split_comment(trimmed.trim_start_matches('#').trim_start());
             lines.push(Line { data, comment });
         }
+    }
+    lines
+}
+
+/// Reads the data lines following a blank line that terminated a block.
+///
+/// This is only used if `blank_terminates` is set, in which case the lines are collected into a
+/// block with an empty name.
+fn parse_anonymous_block<'a, Iter>(
+    first: &'a str,
+    input: &mut iter::Peekable<Iter>,
+    options: &ParseOptions,
+) -> Segment<'a>
+where
+    Iter: Iterator<Item = &'a str>,
+{
+    let (data, comment) = split_comment(first.trim());
+    let lines = parse_block_body(vec![Line { data, comment }], input, options);
+    Segment::Block {
+        name: String::new(),
+        block: RawBlock {
+            logical: logical_data(&lines, options),
+            lines,
+            scale: None,
+        },
+    }
+}
+
+fn parse_block_body<'a, Iter>(
+    mut lines: Vec<Line<'a>>,
+    input: &mut iter::Peekable<Iter>,
+    options: &ParseOptions,
+) -> Vec<Line<'a>>
+where
+    Iter: Iterator<Item = &'a str>,
+{
+    // Every line is only trimmed once here, since this loop dominates the time needed to read
+    // large blocks.
+    while let Some(&line) = input.peek() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('#') {
+            if at_disabled_block(input, options) {
+                break;
+            }
+            end_continuation(&mut lines, options);
+            input.next();
+            continue;
+        }
+        if trimmed.is_empty() {
+            if options.blank_terminates {
+                break;
+            }
+            end_continuation(&mut lines, options);
+            input.next();
+            continue;
+        }
+        if !line.starts_with(|c: char| c.is_whitespace()) {
+            break;
+        }
         input.next();
+        let (data, comment) = split_comment(trimmed);
+        lines.push(Line { data, comment });
+    }
+    end_continuation(&mut lines, options);
+    lines
+}
+
+/// Returns the data of a line ending with a backslash without the backslash.
+fn strip_continuation(data: &str) -> Option<&str> {
+    data.trim_end().strip_suffix('\\')
+}
+
+/// Removes the backslash from the end of the last line if `line_continuation` is set.
+///
+/// A line can only be continued by the data line directly following it, so this is called
+/// whenever a block ends or a comment or an empty line follows.
+fn end_continuation(lines: &mut [Line], options: &ParseOptions) {
+    if !options.line_continuation {
+        return;
+    }
+    if let Some(line) = lines.last_mut() {
+        if let Some(data) = strip_continuation(line.data) {
+            line.data = data.trim_end();
+        }
     }
-    Ok(Segment::Block {
-        name,
-        block: RawBlock { lines, scale },
-    })
 }
 
-fn parse_block_header(header: &str) -> Result<(String, Option<f64>)> {
+/// How a data line of a `RawBlock` is read into a rust type.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LogicalData {
+    /// The line is read as it is found in the file.
+    Unchanged,
+    /// The line is read as the given data, e.g. after replacing decimal commas or joining the
+    /// following lines with it.
+    Replaced(String),
+    /// The line has been joined with the line before it.
+    Joined,
+}
+impl LogicalData {
+    /// Returns the data `line` is read as.
+    pub fn data<'b>(&'b self, line: &Line<'b>) -> &'b str {
+        match *self {
+            LogicalData::Replaced(ref data) => data,
+            _ => line.data,
+        }
+    }
+}
+
+/// Applies the conversions of the data lines of a block requested in `ParseOptions`.
+///
+/// Lines ending with a backslash are joined with the following line if `line_continuation` is
+/// set, with the backslash replaced by a space.
+/// Decimal commas are replaced if `decimal_comma` is set.
+/// The returned vector holds how each line is read, and is empty if all lines are read as they
+/// are.
+fn logical_data(lines: &[Line], options: &ParseOptions) -> Vec<LogicalData> {
+    if !options.line_continuation && !options.decimal_comma {
+        return Vec::new();
+    }
+    let mut logical = Vec::with_capacity(lines.len());
+    // The index of the first line of the line that is currently being read.
+    let mut start = 0;
+    let mut continued: Option<String> = None;
+    for line in lines {
+        let data = match continued.take() {
+            Some(mut data) => {
+                data.push_str(line.data);
+                logical.push(LogicalData::Joined);
+                Cow::Owned(data)
+            }
+            None => {
+                start = logical.len();
+                logical.push(LogicalData::Unchanged);
+                Cow::Borrowed(line.data)
+            }
+        };
+        if options.line_continuation {
+            if let Some(stripped) = strip_continuation(&data) {
+                continued = Some(format!("{} ", stripped));
+                continue;
+            }
+        }
+        let converted = match convert_data(&data, options) {
+            Cow::Owned(converted) => Some(converted),
+            Cow::Borrowed(_) => None,
+        };
+        match (converted, data) {
+            (Some(data), _) | (None, Cow::Owned(data)) => {
+                logical[start] = LogicalData::Replaced(data)
+            }
+            (None, Cow::Borrowed(_)) => (),
+        }
+    }
+    if let Some(data) = continued {
+        logical[start] = LogicalData::Replaced(data);
+    }
+    if logical.iter().all(|logical| *logical == LogicalData::Unchanged) {
+        return Vec::new();
+    }
+    logical
+}
+
+/// Applies the conversions requested in `options` to the data of a line.
+///
+/// Currently this only replaces decimal commas if `decimal_comma` is set.
+fn convert_data<'a>(data: &'a str, options: &ParseOptions) -> Cow<'a, str> {
+    if !options.decimal_comma || !data.contains(',') {
+        return Cow::Borrowed(data);
+    }
+    let mut converted = String::with_capacity(data.len());
+    let mut rest = data;
+    while let Some(start) = rest.find(|c: char| !c.is_whitespace()) {
+        converted.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = rest.find(|c: char| c.is_whitespace()).unwrap_or(rest.len());
+        let word = &rest[..end];
+        let replaced = word.replacen(',', ".", 1);
+        if word.matches(',').count() == 1 && replaced.parse::<f64>().is_ok() {
+            converted.push_str(&replaced);
+        } else {
+            converted.push_str(word);
+        }
+        rest = &rest[end..];
+    }
+    converted.push_str(rest);
+    Cow::Owned(converted)
+}
+
+fn parse_block_header(header: &str, options: &ParseOptions) -> Result<(String, Option<f64>)> {
     let (data, _) = split_comment(header);
     let (name, rest) = match next_word(data) {
         None => bail!(ErrorKind::MissingBlockName),
+        Some((name, rest)) if options.case_sensitive => (name.to_string(), rest),
         Some((name, rest)) => (name.to_lowercase(), rest),
     };
-    let scale = parse_block_scale(rest).chain_err(|| {
-        ErrorKind::InvalidBlock(name.clone())
-    })?;
+    let scale = match parse_bare_scale(rest, options) {
+        Some(scale) => Some(scale),
+        None => parse_block_scale(rest).chain_err(|| ErrorKind::InvalidBlock(name.clone()))?,
+    };
     Ok((name, scale))
 }
 
+/// Reads a scale given without `Q=` if `bare_scale` is set.
+///
+/// `None` is returned if the rest of the header is not a single floating point number.
+fn parse_bare_scale(header: &str, options: &ParseOptions) -> Option<f64> {
+    if !options.bare_scale {
+        return None;
+    }
+    f64::parse(header.trim()).end().ok()
+}
+
 fn parse_block_scale(header: &str) -> Result<Option<f64>> {
+    let trimmed = header.trim();
+    if trimmed.len() > 1 && trimmed.starts_with('(') && trimmed.ends_with(')') {
+        // Some tools write the scale in parentheses, i.e. `Block ye (Q= 464.6)`.
+        return parse_block_scale(&trimmed[1..trimmed.len() - 1]);
+    }
     let mut has_scale = false;
     let mut chars = header.char_indices();
     while let Some((_,c)) = chars.next() {
@@ -223,49 +777,104 @@ fn parse_block_scale(header: &str) -> Result<Option<f64>> {
 fn parse_decay_table<'a, Iter>(
     header: &str,
     input: &mut iter::Peekable<Iter>,
+    options: &ParseOptions,
 ) -> Result<Segment<'a>>
 where
     Iter: Iterator<Item = &'a str>,
 {
-    let (pdg_id, width) = parse_decay_table_header(header)?;
-    let mut decays = Vec::new();
+    let (pdg_id, width) = parse_decay_table_header(&convert_data(header, options), options)?;
+    let lines = collect_decay_lines(input, options);
+    let decays = parse_decay_lines(pdg_id, &lines, options)?;
+    Ok(Segment::Decay {
+        pdg_id,
+        width,
+        decays,
+    })
+}
+
+/// Collects the data of all lines belonging to the body of a decay table.
+///
+/// Comments are not included.
+fn collect_decay_lines<'a, Iter>(
+    input: &mut iter::Peekable<Iter>,
+    options: &ParseOptions,
+) -> Vec<&'a str>
+where
+    Iter: Iterator<Item = &'a str>,
+{
+    let mut lines = Vec::new();
     loop {
-        {
-            skip_empty_lines(input);
-            let line = match input.peek() {
-                Some(line) => line,
-                None => break,
-            };
-            if !line.starts_with(|c: char| c.is_whitespace()) {
-                break;
+        skip_empty_lines_with_options(input, options);
+        match input.peek() {
+            Some(line) if line.starts_with(|c: char| c.is_whitespace()) => {
+                lines.push(split_comment(line.trim()).0)
             }
-            let (data, _) = split_comment(line.trim());
-            let n = decays.len() + 1;
-            decays.push(parse_decay(data)
-                .chain_err(|| ErrorKind::InvalidDecayLine(n))
-                .chain_err(|| ErrorKind::InvalidDecay(pdg_id))?);
+            _ => break,
         }
         input.next();
     }
-    Ok(Segment::Decay {
-        pdg_id,
-        width,
-        decays,
+    lines
+}
+
+/// Parses the lines of the body of the decay table of the particle `pdg_id`.
+pub fn parse_decay_lines(
+    pdg_id: i64,
+    lines: &[&str],
+    options: &ParseOptions,
+) -> Result<Vec<Decay>> {
+    let mut decays = Vec::with_capacity(lines.len());
+    for (i, data) in lines.iter().enumerate() {
+        let mut decay = parse_decay(&convert_data(data, options), options.max_daughters)
+            .chain_err(|| ErrorKind::InvalidDecayLine(i + 1))
+            .chain_err(|| ErrorKind::InvalidDecay(pdg_id))?;
+        if options.br_as_percent {
+            decay.branching_ratio /= 100.;
+        }
+        decays.push(decay);
+    }
+    Ok(decays)
+}
+
+/// Parses a segment from an SLHA file without parsing the bodies of decay tables.
+///
+/// Instead of the decays, the data of the lines making up a decay table is returned as
+/// `LazySegment::Decay`, which can be parsed later using `parse_decay_lines`.
+/// All other segments are parsed as by `parse_segment_with_options`.
+pub fn parse_segment_lazy<'a>(
+    input: &mut iter::Peekable<str::Lines<'a>>,
+    options: &ParseOptions,
+) -> Option<Result<LazySegment<'a>>> {
+    skip_empty_lines_with_options(input, options);
+    input.next().map(|line| {
+        let indented = line.starts_with(|c: char| c.is_whitespace());
+        let header = match next_word(line) {
+            Some((kw, rest)) if !indented && kw.eq_ignore_ascii_case("decay") => rest,
+            _ => return parse_segment_line(line, input, options).map(LazySegment::Segment),
+        };
+        let (pdg_id, width) = parse_decay_table_header(&convert_data(header, options), options)?;
+        Ok(LazySegment::Decay {
+            pdg_id,
+            width,
+            lines: collect_decay_lines(input, options),
+        })
     })
 }
 
-fn parse_decay_table_header(header: &str) -> Result<(i64, f64)> {
+fn parse_decay_table_header(header: &str, options: &ParseOptions) -> Result<(i64, f64)> {
     let (data, _) = split_comment(header);
     let (rest, pdg_id) = i64::parse(data).to_result().chain_err(|| {
         ErrorKind::InvalidDecayingPdgId
     })?;
+    if options.optional_width && rest.trim().is_empty() {
+        return Ok((pdg_id, 0.));
+    }
     let width = f64::parse(rest).end().chain_err(
         || ErrorKind::InvalidDecay(pdg_id),
     )?;
     Ok((pdg_id, width))
 }
 
-fn parse_decay(line: &str) -> Result<Decay> {
+fn parse_decay(line: &str, max_daughters: Option<u8>) -> Result<Decay> {
     let mut rest = line;
     let branching_ratio = match f64::parse(rest) {
         ParseResult::Done(r, value) => {
@@ -281,6 +890,11 @@ fn parse_decay(line: &str) -> Result<Decay> {
         }
         ParseResult::Error(e) => bail!(e.chain_err(|| ErrorKind::InvalidNumOfDaughters)),
     };
+    if let Some(max) = max_daughters {
+        if n_daughters > max {
+            bail!(ErrorKind::TooManyDaughters(n_daughters as usize, max as usize));
+        }
+    }
     let mut daughters = Vec::new();
     for i in 0..n_daughters {
         rest = rest.trim();
@@ -306,11 +920,80 @@ fn parse_decay(line: &str) -> Result<Decay> {
     })
 }
 
-fn skip_empty_lines<'a, Iter>(input: &mut iter::Peekable<Iter>)
+/// Skips empty lines and comments.
+pub fn skip_empty_lines<'a, Iter>(input: &mut iter::Peekable<Iter>)
+where
+    Iter: Iterator<Item = &'a str>,
+{
+    loop {
+        match input.peek() {
+            Some(line) if line.trim().is_empty() || line.trim().starts_with('#') => {}
+            _ => break,
+        }
+        input.next();
+    }
+}
+
+/// Splits the header of a segment into its keyword and the first word following it.
+///
+/// For blocks this is the name of the block and for decay tables the pdg id of the particle.
+/// Comments are ignored.
+/// `None` is returned if the line is indented, i.e. not a header, or has less than two words.
+pub fn segment_header(line: &str) -> Option<(&str, &str)> {
+    if line.starts_with(|c: char| c.is_whitespace()) {
+        return None;
+    }
+    let (data, _) = split_comment(line);
+    let (kw, rest) = next_word(data)?;
+    let (first, _) = next_word(rest)?;
+    Some((kw, first))
+}
+
+/// Skips the rest of a segment that could not be read.
+///
+/// All lines up to the next line that is neither indented, empty nor a comment, i.e. the next
+/// segment header, are skipped.
+pub fn skip_segment_body<'a, Iter>(input: &mut iter::Peekable<Iter>)
 where
     Iter: Iterator<Item = &'a str>,
+{
+    skip_segment_body_with_options(input, &ParseOptions::default())
+}
+
+/// Skips the rest of a segment like `skip_segment_body`, but stops at commented out block
+/// headers if `recognize_commented_blocks` is set.
+pub fn skip_segment_body_with_options<'a, Iter>(
+    input: &mut iter::Peekable<Iter>,
+    options: &ParseOptions,
+) where
+    Iter: Iterator<Item = &'a str>,
+{
+    while let Some(&line) = input.peek() {
+        if at_disabled_block(input, options) {
+            break;
+        }
+        let trimmed = line.trim();
+        if !trimmed.is_empty() && !trimmed.starts_with('#') &&
+            !line.starts_with(|c: char| c.is_whitespace())
+        {
+            break;
+        }
+        input.next();
+    }
+}
+
+/// Skips empty lines and comments, but stops at commented out block headers if
+/// `recognize_commented_blocks` is set.
+pub fn skip_empty_lines_with_options<'a, Iter>(
+    input: &mut iter::Peekable<Iter>,
+    options: &ParseOptions,
+) where
+    Iter: Iterator<Item = &'a str>,
 {
     loop {
+        if at_disabled_block(input, options) {
+            break;
+        }
         let line = match input.peek() {
             Some(line) => line.trim(),
             None => break,
@@ -336,6 +1019,20 @@ pub fn next_word(input: &str) -> Option<(&str, &str)> {
     Some(input.split_at(index))
 }
 
+/// Splits the body of a block, given without the header, into lines.
+///
+/// Empty lines and lines containing only a comment are skipped.
+pub fn split_body(body: &str) -> Vec<Line<'_>> {
+    body.lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let (data, comment) = split_comment(line);
+            Line { data, comment }
+        })
+        .collect()
+}
+
 fn split_comment(line: &str) -> (&str, Option<&str>) {
     let start = match line.find('#') {
         None => return (line, None),
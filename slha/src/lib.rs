@@ -95,10 +95,14 @@
 //! Each field of the struct is treated as a block in the SLHA file with the same
 //! (case-insensitive) name as the field. The block-name that should be deserialized into a field
 //! can be customized using the `rename` attribute.
+//! If the struct is marked with the `#[slha(case_sensitive)]` attribute, the names of the blocks
+//! have to match the field names (or the names given with `rename`) exactly.
 //!
 //! While the fields can be of any type that implements the [`SlhaBlock`] trait, the most common
 //! blocks, including all blocks defined in the SLHA 1 and 2 papers, can be expressed using two
 //! block types defined in this crate, [`Block`] and [`BlockSingle`].
+//! For some frequently used blocks, like `MINPAR` or `HMIX`, the `standard` module contains types
+//! with named fields for the entries defined in the standards.
 //!
 //! All blocks declared in the struct must be present in the SLHA file, or an error is
 //! returned.
@@ -147,6 +151,46 @@
 //! # }
 //! ```
 //!
+//! ### Single entries
+//!
+//! Sometimes only a single value of a block is needed, e.g. the strong coupling from `SMINPUTS`.
+//! Instead of declaring the whole block, a field can be marked with the `block` and `index`
+//! attributes to read the entry with the given key of the block into the field.
+//! The key is either an integer or, for blocks with several indices, a string of integers
+//! separated by whitespace, e.g. `index = "1 2"`.
+//! The field can be of any type that implements the `Parseable` trait.
+//! Only the value of this entry is read, so the other entries of the block can have other types.
+//! If the block does not contain the key, a `MissingKey` error is returned.
+//! Several fields can read entries from the same block.
+//!
+//! ```rust
+//! # extern crate slha;
+//! # #[macro_use]
+//! # extern crate slha_derive;
+//! #
+//! # use slha::SlhaDeserialize;
+//! #
+//! #[derive(Debug, SlhaDeserialize)]
+//! struct Slha {
+//!     #[slha(block = "sminputs", index = 3)]
+//!     alpha_s: f64,
+//!     #[slha(block = "sminputs", index = 4)]
+//!     mz: f64,
+//! }
+//! #
+//! # fn main() {
+//! let input = "
+//! Block SMINPUTS   # Standard Model inputs
+//!      3      0.1172  # alpha_s(MZ) SM MSbar
+//!      4     91.1876  # M_Z pole mass
+//! ";
+//!
+//! let slha = Slha::deserialize(input).unwrap();
+//! assert_eq!(slha.alpha_s, 0.1172);
+//! assert_eq!(slha.mz, 91.1876);
+//! # }
+//! ```
+//!
 //! ### Optional blocks
 //!
 //! The default behaviour is to return an error if a block declared in the struct is not present in
@@ -229,6 +273,8 @@
 //!   As such this type can be used to really collect all occurences of a block.
 //! * The `TakeFirst` and `TakeLast` wrappers keep the first and last occurence of a block
 //!   respectively.
+//! * The `HighestScale` and `LowestScale` wrappers keep the occurence of a block with the highest
+//!   and lowest scale respectively.
 //!
 //!
 //! Taking the first...
@@ -417,6 +463,8 @@
 //! * The [`get_blocks`] method. This method returns all occurences of a block in an SLHA file, as
 //!   long as these blocks have different scales.
 //!   As such, this method can be used to access e.g. grids for running parameters.
+//!   The [`get_scale_grid`] method collects these blocks into a [`ScaleGrid`], which can
+//!   interpolate the values between the scales.
 //! * The [`get_blocks_unchecked`] method is similar to the `get_blocks` method, but does not perform
 //!   any sanity checks.
 //!   Using this function it is for example possible to read duplicate blocks without scale.
@@ -523,25 +571,41 @@
 //! [`get_block`]: struct.Slha.html#method.get_decay
 //! [`get_blocks`]: struct.Slha.html#method.get_decay
 //! [`get_blocks_unchecked`]: struct.Slha.html#method.get_decay
+//! [`get_scale_grid`]: struct.Slha.html#method.get_scale_grid
+//! [`ScaleGrid`]: struct.ScaleGrid.html
 //! [`get_raw_blocks`]: struct.Slha.html#method.get_decay
 //! [`get_decay`]: struct.Slha.html#method.get_decay
 
-#![recursion_limit="256"]
+#![recursion_limit="512"]
 
 #[macro_use]
 extern crate error_chain;
 
-use std::collections::HashMap;
-use std::hash::Hash;
-use std::str;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::collections::hash_map::ValuesMut;
+use std::error::Error as StdError;
+use std::{env, fmt, fs, mem};
+use std::hash::{Hash, Hasher};
+use std::num::{IntErrorKind, ParseFloatError, ParseIntError};
+use std::{iter, slice, str};
+use std::sync::{Arc, OnceLock};
 
 pub mod internal;
 pub mod modifier;
+pub mod schema;
+pub mod standard;
+pub mod value;
 
-use internal::{Segment, next_word};
+use internal::{LazySegment, LogicalData, Segment, next_word};
 
 pub mod errors {
     //! Errors that may occur when parsing an SLHA file into rust types.
+
+    // `error_chain!` checks a cfg that is only set by the build script of `error-chain` itself.
+    #![allow(unexpected_cfgs)]
+
+    use std::error::Error as StdError;
     use std::num::{ParseFloatError, ParseIntError};
 
     error_chain!{
@@ -589,14 +653,37 @@ pub mod errors {
                 description("The parser reached the end of the line before finishing")
             }
             /// An integer type was expected but could not be read from the file.
+            ///
+            /// The original error is chained as the source, so it is not part of the message.
             InvalidInt(err: ParseIntError) {
                 description("Failed to parse an integer")
-                display("Failed to parse an integer: {}", err)
+                display("Failed to parse an integer")
+            }
+            /// An integer was read from the file, but it does not fit into the expected type.
+            ///
+            /// The fields give the original error, the integer as it appears in the file and the
+            /// name of the expected type.
+            IntOutOfRange(err: ParseIntError, value: String, ty: &'static str) {
+                description("An integer is out of range for its type")
+                display("The integer '{}' is out of range for {}", value, ty)
             }
             /// An floating point type was expected but could not be read from the file.
+            ///
+            /// The original error is chained as the source, so it is not part of the message.
             InvalidFloat(err: ParseFloatError) {
                 description("Failed to parse a floating point number")
-                display("Failed to parse a floating point number: {}", err)
+                display("Failed to parse a floating point number")
+            }
+            /// The two values of a `SortedPair` are in the wrong order.
+            UnsortedPair {
+                description("The first value of a sorted pair is larger than the second")
+            }
+            /// There were less values in a `CountedVec` than declared.
+            ///
+            /// The fields give the number of expected and found values in that order.
+            NotEnoughValues(expected: usize, found: usize) {
+                description("Did not find enough values")
+                display("Did not find enough values, expected {} but found {}", expected, found)
             }
             /// A top level segment other than "BLOCK" or "DECAY" was encountered.
             UnknownSegment(segment: String) {
@@ -654,6 +741,13 @@ pub mod errors {
                 description("Found a duplicate block with and without scale")
                 display("Found a duplicate block with and without scale: '{}'", name)
             }
+            /// A block that must not have a scale was defined with a scale.
+            ///
+            /// The field contains the name of the block.
+            ForbiddenScale(name: String) {
+                description("Found a scale on a block that must not have one")
+                display("Found a scale on a block that must not have one: '{}'", name)
+            }
             /// The scale of a block could not be read.
             ///
             /// The field contains the name of the block.
@@ -667,6 +761,13 @@ pub mod errors {
                 description("Found multiple decay tables for the same particle")
                 display("Found multiple decay tables for the same particle: '{}'", pdg_id)
             }
+            /// The same decay channel appears in both decay tables passed to `DecayTable::merge`.
+            ///
+            /// The field contains the daughters of the decay channel.
+            DuplicateChannel(daughters: Vec<i64>) {
+                description("Found the same decay channel in both decay tables")
+                display("Found the same decay channel in both decay tables: {:?}", daughters)
+            }
             /// A data line from a DECAY table could not be read.
             ///
             /// The field contains the number of the _data_ line _in the DECAY table_.
@@ -697,6 +798,28 @@ pub mod errors {
             InvalidDaughterId {
                 description("Failed to parse the pdg id of a daughter particle")
             }
+            /// The branching ratio of a decay is infinite or not a number.
+            NonFiniteBranchingRatio(br: f64) {
+                description("The branching ratio is not a finite number")
+                display("The branching ratio is not a finite number: {}", br)
+            }
+            /// A decay has no daughter particles.
+            NoDaughters {
+                description("The decay has no daughter particles")
+            }
+            /// A decay has more daughter particles than allowed.
+            ///
+            /// The fields give the number of daughters and the largest number allowed, which is
+            /// 255 for decays written to an SLHA file, or `max_daughters` from the `ParseOptions`.
+            TooManyDaughters(n: usize, max: usize) {
+                description("The decay has too many daughter particles")
+                display("The decay has {} daughter particles, but at most {} are allowed", n, max)
+            }
+            /// The width of a decay table is negative, infinite or not a number.
+            InvalidWidthValue(width: f64) {
+                description("The width is not a finite, non-negative number")
+                display("The width is not a finite, non-negative number: {}", width)
+            }
             /// A block read into a `BlockSingle` contains more than one data line.
             ///
             /// The field gives the number of data lines found.
@@ -704,6 +827,62 @@ pub mod errors {
                 description("Found too many values in a single valued block")
                 display("Found {} values in a single valued block", n)
             }
+            /// A block read into an `ExactLen` does not contain the expected number of entries.
+            ///
+            /// The fields give the expected and the actual number of entries.
+            WrongNumberOfEntries(expected: usize, found: usize) {
+                description("Found an unexpected number of entries in a block")
+                display("Expected {} entries in the block, but found {}", expected, found)
+            }
+            /// The key of an entry of a matrix block is not a valid index.
+            ///
+            /// The fields give the row and column index of the entry.
+            InvalidMatrixIndex(row: i64, col: i64) {
+                description("Found an invalid index in a matrix block")
+                display("Found an invalid index ({}, {}) in a matrix block, indices start at 1", row, col)
+            }
+            /// An entry of a matrix block is missing.
+            ///
+            /// The fields give the row and column index of the missing entry.
+            MissingMatrixEntry(row: usize, col: usize) {
+                description("An entry of a matrix block is missing")
+                display("The entry ({}, {}) of the matrix block is missing", row, col)
+            }
+            /// The key of an entry of a block with three indices is not a valid index.
+            ///
+            /// The fields give the three indices of the entry.
+            InvalidTensorIndex(i: i64, j: i64, k: i64) {
+                description("Found an invalid index in a block with three indices")
+                display("Found an invalid index ({}, {}, {}) in a block with three indices, indices start at 1", i, j, k)
+            }
+            /// An entry of a block with three indices is missing.
+            ///
+            /// The fields give the three indices of the missing entry.
+            MissingTensorEntry(i: usize, j: usize, k: usize) {
+                description("An entry of a block with three indices is missing")
+                display("The entry ({}, {}, {}) of the block with three indices is missing", i, j, k)
+            }
+            /// A block or decay table has fewer entries than required by a `Schema`.
+            ///
+            /// The fields give the minimal and the actual number of entries.
+            TooFewEntries(min: usize, found: usize) {
+                description("Found too few entries")
+                display("Expected at least {} entries, but found {}", min, found)
+            }
+            /// A block or decay table has more entries than allowed by a `Schema`.
+            ///
+            /// The fields give the maximal and the actual number of entries.
+            TooManyEntries(max: usize, found: usize) {
+                description("Found too many entries")
+                display("Expected at most {} entries, but found {}", max, found)
+            }
+            /// One of several SLHA documents contained in a single input could not be read.
+            ///
+            /// The field is the number of the document in the input, starting at one.
+            InvalidDocument(n: usize) {
+                description("Failed to parse an SLHA document")
+                display("Failed to parse SLHA document {}", n)
+            }
             /// A required block was not included in the SLHA file.
             ///
             /// The field gives the name of the block.
@@ -711,6 +890,309 @@ pub mod errors {
                 description("A block is missing")
                 display("Did not find the block with name '{}'", name)
             }
+            /// A block did not contain the entry that should be read into a field.
+            ///
+            /// The fields give the name of the block and the key of the missing entry.
+            MissingKey(name: String, key: String) {
+                description("An entry of a block is missing")
+                display("Did not find the entry with key '{}' in the block '{}'", key, name)
+            }
+            /// A required decay table was not included in the SLHA file.
+            ///
+            /// The field gives the pdg id of the decaying particle.
+            MissingDecay(pdg_id: i64) {
+                description("A decay table is missing")
+                display("Did not find the decay table of the particle {}", pdg_id)
+            }
+            /// More than one required block was not included in the SLHA file.
+            ///
+            /// The field gives the names of all missing blocks.
+            MissingBlocks(names: Vec<String>) {
+                description("Several blocks are missing")
+                display("Did not find the blocks with names '{}'", names.join("', '"))
+            }
+            /// An environment variable could not be read, because it is not set or not valid
+            /// unicode.
+            ///
+            /// The field gives the name of the variable.
+            EnvVar(name: String) {
+                description("Failed to read an environment variable")
+                display("Failed to read the environment variable '{}'", name)
+            }
+            /// A file could not be read.
+            ///
+            /// The field gives the path of the file.
+            ReadFile(path: String) {
+                description("Failed to read a file")
+                display("Failed to read the file '{}'", path)
+            }
+        }
+    }
+
+    /// A stable code identifying the kind of an error.
+    ///
+    /// Unlike `ErrorKind`, the variants of `ErrorCode` carry no data, so they can be compared,
+    /// hashed and matched on without caring about the details of the error.
+    /// There is one variant for every variant of `ErrorKind`, with the same name.
+    /// The code of an error can be obtained using `Error::code` or `ErrorKind::code`.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub enum ErrorCode {
+        /// See `ErrorKind::Msg`.
+        Msg,
+        /// See `ErrorKind::MissingBlockName`.
+        MissingBlockName,
+        /// See `ErrorKind::InvalidBlock`.
+        InvalidBlock,
+        /// See `ErrorKind::InvalidBlockSingle`.
+        InvalidBlockSingle,
+        /// See `ErrorKind::InvalidDecayingPdgId`.
+        InvalidDecayingPdgId,
+        /// See `ErrorKind::InvalidDecay`.
+        InvalidDecay,
+        /// See `ErrorKind::IncompleteParse`.
+        IncompleteParse,
+        /// See `ErrorKind::UnexpectedEol`.
+        UnexpectedEol,
+        /// See `ErrorKind::InvalidInt`.
+        InvalidInt,
+        /// See `ErrorKind::IntOutOfRange`.
+        IntOutOfRange,
+        /// See `ErrorKind::InvalidFloat`.
+        InvalidFloat,
+        /// See `ErrorKind::UnsortedPair`.
+        UnsortedPair,
+        /// See `ErrorKind::NotEnoughValues`.
+        NotEnoughValues,
+        /// See `ErrorKind::UnknownSegment`.
+        UnknownSegment,
+        /// See `ErrorKind::UnexpectedIdent`.
+        UnexpectedIdent,
+        /// See `ErrorKind::MalformedBlockHeader`.
+        MalformedBlockHeader,
+        /// See `ErrorKind::InvalidBlockLine`.
+        InvalidBlockLine,
+        /// See `ErrorKind::InvalidBlockKey`.
+        InvalidBlockKey,
+        /// See `ErrorKind::InvalidBlockValue`.
+        InvalidBlockValue,
+        /// See `ErrorKind::DuplicateKey`.
+        DuplicateKey,
+        /// See `ErrorKind::DuplicateBlock`.
+        DuplicateBlock,
+        /// See `ErrorKind::DuplicateBlockScale`.
+        DuplicateBlockScale,
+        /// See `ErrorKind::RedefinedBlockWithQ`.
+        RedefinedBlockWithQ,
+        /// See `ErrorKind::ForbiddenScale`.
+        ForbiddenScale,
+        /// See `ErrorKind::InvalidScale`.
+        InvalidScale,
+        /// See `ErrorKind::DuplicateDecay`.
+        DuplicateDecay,
+        /// See `ErrorKind::DuplicateChannel`.
+        DuplicateChannel,
+        /// See `ErrorKind::InvalidDecayLine`.
+        InvalidDecayLine,
+        /// See `ErrorKind::InvalidWidth`.
+        InvalidWidth,
+        /// See `ErrorKind::InvalidBranchingRatio`.
+        InvalidBranchingRatio,
+        /// See `ErrorKind::InvalidNumOfDaughters`.
+        InvalidNumOfDaughters,
+        /// See `ErrorKind::NotEnoughDaughters`.
+        NotEnoughDaughters,
+        /// See `ErrorKind::InvalidDaughterId`.
+        InvalidDaughterId,
+        /// See `ErrorKind::NonFiniteBranchingRatio`.
+        NonFiniteBranchingRatio,
+        /// See `ErrorKind::NoDaughters`.
+        NoDaughters,
+        /// See `ErrorKind::TooManyDaughters`.
+        TooManyDaughters,
+        /// See `ErrorKind::InvalidWidthValue`.
+        InvalidWidthValue,
+        /// See `ErrorKind::WrongNumberOfValues`.
+        WrongNumberOfValues,
+        /// See `ErrorKind::WrongNumberOfEntries`.
+        WrongNumberOfEntries,
+        /// See `ErrorKind::InvalidMatrixIndex`.
+        InvalidMatrixIndex,
+        /// See `ErrorKind::MissingMatrixEntry`.
+        MissingMatrixEntry,
+        /// See `ErrorKind::InvalidTensorIndex`.
+        InvalidTensorIndex,
+        /// See `ErrorKind::MissingTensorEntry`.
+        MissingTensorEntry,
+        /// See `ErrorKind::TooFewEntries`.
+        TooFewEntries,
+        /// See `ErrorKind::TooManyEntries`.
+        TooManyEntries,
+        /// See `ErrorKind::InvalidDocument`.
+        InvalidDocument,
+        /// See `ErrorKind::MissingBlock`.
+        MissingBlock,
+        /// See `ErrorKind::MissingKey`.
+        MissingKey,
+        /// See `ErrorKind::MissingDecay`.
+        MissingDecay,
+        /// See `ErrorKind::MissingBlocks`.
+        MissingBlocks,
+        /// See `ErrorKind::EnvVar`.
+        EnvVar,
+        /// See `ErrorKind::ReadFile`.
+        ReadFile,
+    }
+
+    impl ErrorKind {
+        /// Returns the code identifying this kind of error.
+        pub fn code(&self) -> ErrorCode {
+            match *self {
+                ErrorKind::Msg(_) => ErrorCode::Msg,
+                ErrorKind::MissingBlockName => ErrorCode::MissingBlockName,
+                ErrorKind::InvalidBlock(..) => ErrorCode::InvalidBlock,
+                ErrorKind::InvalidBlockSingle(..) => ErrorCode::InvalidBlockSingle,
+                ErrorKind::InvalidDecayingPdgId => ErrorCode::InvalidDecayingPdgId,
+                ErrorKind::InvalidDecay(..) => ErrorCode::InvalidDecay,
+                ErrorKind::IncompleteParse(..) => ErrorCode::IncompleteParse,
+                ErrorKind::UnexpectedEol => ErrorCode::UnexpectedEol,
+                ErrorKind::InvalidInt(..) => ErrorCode::InvalidInt,
+                ErrorKind::IntOutOfRange(..) => ErrorCode::IntOutOfRange,
+                ErrorKind::InvalidFloat(..) => ErrorCode::InvalidFloat,
+                ErrorKind::UnsortedPair => ErrorCode::UnsortedPair,
+                ErrorKind::NotEnoughValues(..) => ErrorCode::NotEnoughValues,
+                ErrorKind::UnknownSegment(..) => ErrorCode::UnknownSegment,
+                ErrorKind::UnexpectedIdent(..) => ErrorCode::UnexpectedIdent,
+                ErrorKind::MalformedBlockHeader(..) => ErrorCode::MalformedBlockHeader,
+                ErrorKind::InvalidBlockLine(..) => ErrorCode::InvalidBlockLine,
+                ErrorKind::InvalidBlockKey => ErrorCode::InvalidBlockKey,
+                ErrorKind::InvalidBlockValue => ErrorCode::InvalidBlockValue,
+                ErrorKind::DuplicateKey(..) => ErrorCode::DuplicateKey,
+                ErrorKind::DuplicateBlock(..) => ErrorCode::DuplicateBlock,
+                ErrorKind::DuplicateBlockScale(..) => ErrorCode::DuplicateBlockScale,
+                ErrorKind::RedefinedBlockWithQ(..) => ErrorCode::RedefinedBlockWithQ,
+                ErrorKind::ForbiddenScale(..) => ErrorCode::ForbiddenScale,
+                ErrorKind::InvalidScale => ErrorCode::InvalidScale,
+                ErrorKind::DuplicateDecay(..) => ErrorCode::DuplicateDecay,
+                ErrorKind::DuplicateChannel(..) => ErrorCode::DuplicateChannel,
+                ErrorKind::InvalidDecayLine(..) => ErrorCode::InvalidDecayLine,
+                ErrorKind::InvalidWidth => ErrorCode::InvalidWidth,
+                ErrorKind::InvalidBranchingRatio => ErrorCode::InvalidBranchingRatio,
+                ErrorKind::InvalidNumOfDaughters => ErrorCode::InvalidNumOfDaughters,
+                ErrorKind::NotEnoughDaughters(..) => ErrorCode::NotEnoughDaughters,
+                ErrorKind::InvalidDaughterId => ErrorCode::InvalidDaughterId,
+                ErrorKind::NonFiniteBranchingRatio(..) => ErrorCode::NonFiniteBranchingRatio,
+                ErrorKind::NoDaughters => ErrorCode::NoDaughters,
+                ErrorKind::TooManyDaughters(..) => ErrorCode::TooManyDaughters,
+                ErrorKind::InvalidWidthValue(..) => ErrorCode::InvalidWidthValue,
+                ErrorKind::WrongNumberOfValues(..) => ErrorCode::WrongNumberOfValues,
+                ErrorKind::WrongNumberOfEntries(..) => ErrorCode::WrongNumberOfEntries,
+                ErrorKind::InvalidMatrixIndex(..) => ErrorCode::InvalidMatrixIndex,
+                ErrorKind::MissingMatrixEntry(..) => ErrorCode::MissingMatrixEntry,
+                ErrorKind::InvalidTensorIndex(..) => ErrorCode::InvalidTensorIndex,
+                ErrorKind::MissingTensorEntry(..) => ErrorCode::MissingTensorEntry,
+                ErrorKind::TooFewEntries(..) => ErrorCode::TooFewEntries,
+                ErrorKind::TooManyEntries(..) => ErrorCode::TooManyEntries,
+                ErrorKind::InvalidDocument(..) => ErrorCode::InvalidDocument,
+                ErrorKind::MissingBlock(..) => ErrorCode::MissingBlock,
+                ErrorKind::MissingKey(..) => ErrorCode::MissingKey,
+                ErrorKind::MissingDecay(..) => ErrorCode::MissingDecay,
+                ErrorKind::MissingBlocks(..) => ErrorCode::MissingBlocks,
+                ErrorKind::EnvVar(..) => ErrorCode::EnvVar,
+                ErrorKind::ReadFile(..) => ErrorCode::ReadFile,
+                ErrorKind::__Nonexhaustive {} => {
+                    unreachable!("BUG: __Nonexhaustive is never constructed.")
+                }
+            }
+        }
+    }
+
+    impl Error {
+        /// Returns the code identifying the kind of this error.
+        ///
+        /// Only the outermost error is considered, not the errors that caused it.
+        ///
+        /// # Examples
+        ///
+        /// ```rust
+        /// use slha::Slha;
+        /// use slha::errors::ErrorCode;
+        ///
+        /// let input = "\
+        /// BLOCK MASS
+        ///    6    173.2
+        /// BLOCK MASS
+        ///    5    4.25
+        /// ";
+        ///
+        /// let slha = Slha::parse(input).unwrap();
+        /// let err = slha.get_block::<slha::Block<i64, f64>>("mass").unwrap().unwrap_err();
+        /// assert_eq!(err.code(), ErrorCode::DuplicateBlock);
+        /// ```
+        pub fn code(&self) -> ErrorCode {
+            self.kind().code()
+        }
+
+        /// Returns the error from parsing a number that caused this error, if any.
+        ///
+        /// The error chain is searched for an `InvalidInt`, `IntOutOfRange` or `InvalidFloat` error
+        /// and the contained `ParseIntError` or `ParseFloatError` is returned.
+        ///
+        /// The `ParseIntError` or `ParseFloatError` is also the `source` of these errors, so it can
+        /// be reached by following `source` as well.
+        /// This method saves walking the chain by hand.
+        ///
+        /// # Examples
+        ///
+        /// ```rust
+        /// use slha::{Slha, Block};
+        /// use std::num::ParseIntError;
+        ///
+        /// let input = "\
+        /// BLOCK MASS
+        ///    6.5    173.2
+        /// ";
+        ///
+        /// let slha = Slha::parse(input).unwrap();
+        /// let err = slha.get_block::<Block<i64, f64>>("mass").unwrap().unwrap_err();
+        /// let source = err.parse_error().unwrap();
+        /// assert!(source.downcast_ref::<ParseIntError>().is_some());
+        /// ```
+        pub fn parse_error(&self) -> Option<&(dyn StdError + 'static)> {
+            let mut current = self;
+            loop {
+                match *current.kind() {
+                    ErrorKind::InvalidInt(ref err) => return Some(err),
+                    ErrorKind::IntOutOfRange(ref err, _, _) => return Some(err),
+                    ErrorKind::InvalidFloat(ref err) => return Some(err),
+                    _ => (),
+                }
+                current = current.1.next_error.as_ref()?.downcast_ref::<Error>()?;
+            }
+        }
+    }
+
+    // These conversions allow custom `Parseable` implementations to report errors from parsing
+    // numbers with the standard library using the same error variants as the built-in types.
+    impl From<ParseIntError> for ErrorKind {
+        fn from(err: ParseIntError) -> ErrorKind {
+            ErrorKind::InvalidInt(err)
+        }
+    }
+    impl From<ParseFloatError> for ErrorKind {
+        fn from(err: ParseFloatError) -> ErrorKind {
+            ErrorKind::InvalidFloat(err)
+        }
+    }
+    // The conversions into `Error` also chain the original error, so that it is returned by
+    // `source`.
+    impl From<ParseIntError> for Error {
+        fn from(err: ParseIntError) -> Error {
+            Error::with_chain(err.clone(), ErrorKind::from(err))
+        }
+    }
+    impl From<ParseFloatError> for Error {
+        fn from(err: ParseFloatError) -> Error {
+            Error::with_chain(err.clone(), ErrorKind::from(err))
         }
     }
 }
@@ -728,6 +1210,152 @@ pub trait SlhaDeserialize: Sized {
     ///
     /// If the deserialization fails an `Error` should be returned.
     fn deserialize(&str) -> Result<Self>;
+
+    /// Deserialize a rust struct from blocks and decay tables that have already been read.
+    ///
+    /// This is used by `Slha::to_typed` to convert an `Slha` object into a rust struct.
+    /// The `deserialize` method generated by `slha-derive` also reads the file into segments and
+    /// passes them to this method.
+    ///
+    /// # Errors
+    ///
+    /// If one of the segments is an error or the deserialization fails an `Error` should be
+    /// returned.
+    ///
+    /// The default implementation writes the blocks and decay tables back into an SLHA file and
+    /// passes it to `deserialize`.
+    /// Disabled blocks and unknown segments are skipped.
+    fn deserialize_segments<'a, I>(segments: I) -> Result<Self>
+    where
+        I: Iterator<Item = Result<internal::Segment<'a>>>,
+    {
+        let mut input = String::new();
+        for segment in segments {
+            match segment? {
+                internal::Segment::Block { name, block } => {
+                    input.push_str("BLOCK ");
+                    input.push_str(&name);
+                    if let Some(scale) = block.scale {
+                        input.push_str(&format!(" Q= {:e}", scale));
+                    }
+                    input.push('\n');
+                    for line in block.logical_lines() {
+                        input.push_str("   ");
+                        input.push_str(line.data);
+                        input.push('\n');
+                    }
+                }
+                internal::Segment::Decay {
+                    pdg_id,
+                    width,
+                    decays,
+                } => {
+                    input.push_str(&format!("DECAY {} {:e}\n", pdg_id, width));
+                    for decay in &decays {
+                        input.push_str(&format!(
+                            "   {:e} {}",
+                            decay.branching_ratio,
+                            decay.daughters.len()
+                        ));
+                        for daughter in &decay.daughters {
+                            input.push_str(&format!(" {}", daughter));
+                        }
+                        input.push('\n');
+                    }
+                }
+                internal::Segment::DisabledBlock { .. } | internal::Segment::Unknown { .. } => {}
+            }
+        }
+        Self::deserialize(&input)
+    }
+
+    /// Deserialize a SLHA file into a rust struct, collecting all errors instead of stopping at
+    /// the first one.
+    ///
+    /// The struct is only returned if no error occurred.
+    /// The implementation generated by `slha-derive` tries to read every field and reports an
+    /// error for each field that could not be read, while a syntax error in the file itself still
+    /// aborts the deserialization.
+    /// The default implementation just wraps the result of `deserialize`.
+    fn deserialize_report(input: &str) -> (Option<Self>, Vec<Error>) {
+        match Self::deserialize(input) {
+            Ok(value) => (Some(value), Vec::new()),
+            Err(err) => (None, vec![err]),
+        }
+    }
+
+    /// Deserialize the SLHA file whose path is given by the environment variable `var`.
+    ///
+    /// Tools like MadGraph or Pythia often pass the path of the parameter card to other
+    /// programs in an environment variable.
+    ///
+    /// # Errors
+    ///
+    /// An `EnvVar` error is returned if the variable is not set, and a `ReadFile` error if the
+    /// file could not be read.
+    /// Otherwise, the same errors as for `deserialize` are returned.
+    fn from_env(var: &str) -> Result<Self> {
+        let path = env::var(var).chain_err(|| ErrorKind::EnvVar(var.to_string()))?;
+        let input = fs::read_to_string(&path).chain_err(|| ErrorKind::ReadFile(path.clone()))?;
+        Self::deserialize(&input)
+    }
+}
+
+/// A trait for structs that can be deserialized from an SLHA file field by field.
+///
+/// Instead of failing as a whole, every field is read into a `Result` of its own, so that it is
+/// possible to see which blocks could be read and which could not in a single pass.
+/// This trait should not be implemented manually, instead it should be derived using
+/// `#[derive(SlhaDeserializeLenient)]` from the `slha-derive` crate.
+/// The derive generates a struct named after the deriving struct with `Lenient` appended, with
+/// the same fields wrapped in `Result`s, which is used as the `Lenient` type.
+///
+/// # Examples
+///
+/// ```rust
+/// extern crate slha;
+/// #[macro_use]
+/// extern crate slha_derive;
+///
+/// use slha::{Block, SlhaDeserializeLenient};
+///
+/// #[derive(Debug, SlhaDeserializeLenient)]
+/// struct MySlha {
+///     mass: Block<i64, f64>,
+///     sminputs: Block<i8, f64>,
+/// }
+///
+/// fn main() {
+///     let input = "\
+/// BLOCK MASS
+///    6    173.2
+/// BLOCK SMINPUTS
+///    3    foo
+/// ";
+///
+///     let lenient: MySlhaLenient = MySlha::deserialize_lenient(input);
+///     assert_eq!(lenient.mass.unwrap().map[&6], 173.2);
+///     assert!(lenient.sminputs.is_err());
+/// }
+/// ```
+pub trait SlhaDeserializeLenient: Sized {
+    /// The struct holding the result of every field.
+    type Lenient;
+
+    /// Deserialize every field of the struct from a SLHA file.
+    ///
+    /// This never fails.
+    /// A block or decay table that could not be read only causes an error in the fields that
+    /// read from it, while segments that can not be attributed to any field are skipped.
+    fn deserialize_lenient(input: &str) -> Self::Lenient;
+
+    /// Deserialize every field of the struct from blocks and decay tables that have already been
+    /// read.
+    ///
+    /// This is used by `Slha::to_typed_lenient` and works like `deserialize_lenient`.
+    fn deserialize_segments_lenient<'a, I>(segments: I) -> Self::Lenient
+    where
+        I: Iterator<Item = Result<internal::Segment<'a>>>;
 }
 
 /// A trait for types that can be created from a block in an SLHA file.
@@ -791,6 +1419,16 @@ impl<'input, T> ParseResult<'input, T> {
             ParseResult::Done(_, value) => Ok(value),
         }
     }
+    /// Extract the parsed value if the parser consumed all (non whitespace) input.
+    ///
+    /// This is a cheaper version of `end` for callers that are not interested in the reason why
+    /// the value could not be extracted.
+    fn complete(self) -> Option<T> {
+        match self {
+            ParseResult::Done(input, value) if input.trim().is_empty() => Some(value),
+            _ => None,
+        }
+    }
     /// Turn a `ParseResult` into a normal result.
     ///
     /// This method can be used together with the '?' operator to chain parsers.
@@ -809,6 +1447,52 @@ impl<'input, T> ParseResult<'input, T> {
 
 /// A trait used by the various `Block`s to read the key and value from a line in
 /// an SLHA file.
+///
+/// Implementing this trait for your own types allows to use them as keys or values of a `Block`,
+/// e.g. to read values into an arbitrary precision number type.
+///
+/// Errors from reading numbers with `str::parse` can be converted into an `Error` using `into`,
+/// which results in an `InvalidInt` or `InvalidFloat` error just like for the built-in number
+/// types.
+/// Other problems with the input can be reported using the existing `ErrorKind` variants, like
+/// `UnexpectedEol` for missing input, or a message given as a `String`.
+///
+/// # Examples
+///
+/// ```rust
+/// use slha::{Slha, Block, Parseable, ParseResult};
+/// use slha::errors::ErrorKind;
+///
+/// /// A float that keeps the exact decimal representation used in the file.
+/// #[derive(Debug, PartialEq)]
+/// struct ExactFloat(String);
+///
+/// impl Parseable for ExactFloat {
+///     fn parse<'input>(input: &'input str) -> ParseResult<'input, ExactFloat> {
+///         let input = input.trim_left();
+///         if input.is_empty() {
+///             return ParseResult::Error(ErrorKind::UnexpectedEol.into());
+///         }
+///         let end = input.find(char::is_whitespace).unwrap_or_else(|| input.len());
+///         let (word, rest) = input.split_at(end);
+///         if let Err(err) = word.parse::<f64>() {
+///             return ParseResult::Error(err.into());
+///         }
+///         ParseResult::Done(rest, ExactFloat(word.to_string()))
+///     }
+/// }
+///
+/// let input = "\
+/// BLOCK SMINPUTS
+///     1   1.27934000000000000001e+02
+///     3   1.172e-01
+/// ";
+///
+/// let slha = Slha::parse(input).unwrap();
+/// let sminputs: Block<u8, ExactFloat> = slha.get_block("sminputs").unwrap().unwrap();
+/// assert_eq!(sminputs.map[&1], ExactFloat("1.27934000000000000001e+02".to_string()));
+/// assert_eq!(sminputs.map[&3], ExactFloat("1.172e-01".to_string()));
+/// ```
 pub trait Parseable: Sized {
     /// Parse a value from the input string.
     ///
@@ -828,8 +1512,10 @@ impl Parseable for String {
     }
 }
 
+// Integers and floats may be preceded by an explicit `+` sign, as found e.g. in the keys of some
+// UFO parameter cards. This is accepted by `str::parse` for all of these types.
 macro_rules! impl_parseable {
-    ($int:ty, $err:ident) => {
+    ($int:ty, $to_error:ident) => {
         impl Parseable for $int {
             fn parse<'input>(input: &'input str) -> ParseResult<'input, $int> {
                 let (word, rest) = match next_word(input) {
@@ -838,23 +1524,44 @@ macro_rules! impl_parseable {
                 };
                 let value: $int = match word.parse() {
                     Ok(value) => value,
-                    Err(err) => return ParseResult::Error(ErrorKind::$err(err).into()),
+                    Err(err) => {
+                        return ParseResult::Error($to_error(err, word, stringify!($int)))
+                    }
                 };
                 ParseResult::Done(rest, value)
             }
         }
     }
 }
-impl_parseable!(i8, InvalidInt);
-impl_parseable!(i16, InvalidInt);
-impl_parseable!(i32, InvalidInt);
-impl_parseable!(i64, InvalidInt);
-impl_parseable!(u8, InvalidInt);
-impl_parseable!(u16, InvalidInt);
-impl_parseable!(u32, InvalidInt);
-impl_parseable!(u64, InvalidInt);
-impl_parseable!(f32, InvalidFloat);
-impl_parseable!(f64, InvalidFloat);
+impl_parseable!(i8, int_error);
+impl_parseable!(i16, int_error);
+impl_parseable!(i32, int_error);
+impl_parseable!(i64, int_error);
+impl_parseable!(u8, int_error);
+impl_parseable!(u16, int_error);
+impl_parseable!(u32, int_error);
+impl_parseable!(u64, int_error);
+impl_parseable!(f32, float_error);
+impl_parseable!(f64, float_error);
+
+/// Converts the error from parsing `word` as the integer type `ty`.
+///
+/// Integers that are too large or too small for `ty` are reported as `IntOutOfRange`, all other
+/// errors as `InvalidInt`.
+/// In both cases `err` is chained as the source of the returned error.
+fn int_error(err: ParseIntError, word: &str, ty: &'static str) -> Error {
+    match *err.kind() {
+        IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => Error::with_chain(
+            err.clone(),
+            ErrorKind::IntOutOfRange(err, word.to_string(), ty),
+        ),
+        _ => err.into(),
+    }
+}
+
+fn float_error(err: ParseFloatError, _: &str, _: &'static str) -> Error {
+    err.into()
+}
 
 macro_rules! impl_parseable_tuple {
     ($($name:ident),+) => {
@@ -902,9 +1609,14 @@ impl_parseable_tuple!(K1, K2, K3, K4, K5, K6, K7, K8, K9, K10, K11, K12);
 /// included in this crate, which is enough to cover all blocks defined in the SLHA 1 and 2 papers.
 /// There is however one restriction when using Strings. The parseable impl of String takes the
 /// whole line, which means that String can not be used as a key.
+/// Additional types for blocks with a less common structure can be found in the `value` module.
 ///
 /// Duplicate keys in a block are treated as a parse error.
 ///
+/// The derived `Debug` output of a `Block` depends on the order of the entries in the underlying
+/// `HashMap`.
+/// Use `Block::sorted_debug` to print the entries sorted by their keys instead.
+///
 /// # Reading blocks
 ///
 /// `Block` implements the `SlhaBlock` trait and therefore can be read from an SLHA file.
@@ -1023,6 +1735,70 @@ where
     /// The map from keys to values.
     pub map: HashMap<Key, Value>,
 }
+impl<Key, Value> Block<Key, Value>
+where
+    Key: Hash + Eq + Ord + fmt::Debug,
+    Value: fmt::Debug,
+{
+    /// Returns an adapter whose `Debug` output lists the entries of the block sorted by key.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, Block};
+    ///
+    /// let input = "\
+    /// Block stopmix
+    ///    2  2    -5.37975095e-01
+    ///    1  1     5.37975095e-01
+    /// ";
+    ///
+    /// let slha = Slha::parse(input).unwrap();
+    /// let stopmix: Block<(u8, u8), f64> = slha.get_block("stopmix").unwrap().unwrap();
+    /// assert_eq!(
+    ///     format!("{:?}", stopmix.sorted_debug()),
+    ///     "Block { scale: None, map: {(1, 1): 0.537975095, (2, 2): -0.537975095} }"
+    /// );
+    /// ```
+    pub fn sorted_debug(&self) -> SortedDebug<'_, Key, Value> {
+        SortedDebug(self)
+    }
+}
+
+/// An adapter around a `Block` whose `Debug` output lists the entries sorted by key.
+///
+/// This is returned by `Block::sorted_debug`.
+pub struct SortedDebug<'a, Key, Value>(&'a Block<Key, Value>)
+where
+    Key: 'a + Hash + Eq,
+    Value: 'a;
+impl<'a, Key, Value> fmt::Debug for SortedDebug<'a, Key, Value>
+where
+    Key: Hash + Eq + Ord + fmt::Debug,
+    Value: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Block")
+            .field("scale", &self.0.scale)
+            .field("map", &SortedMap(&self.0.map))
+            .finish()
+    }
+}
+
+/// A wrapper around a `HashMap` whose `Debug` output is sorted by key.
+struct SortedMap<'a, K: 'a, V: 'a>(&'a HashMap<K, V>);
+impl<'a, K, V> fmt::Debug for SortedMap<'a, K, V>
+where
+    K: Ord + fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut entries: Vec<_> = self.0.iter().collect();
+        entries.sort_by_key(|&(key, _)| key);
+        f.debug_map().entries(entries).finish()
+    }
+}
+
 impl<Key, Value> SlhaBlock for Block<Key, Value>
 where
     Key: Hash + Eq + Parseable,
@@ -1036,175 +1812,748 @@ where
         self.scale
     }
 }
-
-fn parse_line_block<'input, K, V>(input: &'input str) -> Result<(K, V)>
+impl<Key, Value> Block<Key, Value>
 where
-    K: Parseable,
-    V: Parseable,
+    Key: Hash + Eq + Parseable,
+    Value: Parseable,
 {
-    let input = input.trim_left();
-    let (input, key) = K::parse(input).to_result().chain_err(
-        || ErrorKind::InvalidBlockKey,
-    )?;
-    let value = V::parse(input).end().chain_err(
-        || ErrorKind::InvalidBlockValue,
-    )?;
-    Ok((key, value))
+    /// Parses a block from its body, i.e. the lines following the `BLOCK` header.
+    ///
+    /// The body must not contain the header line itself, since the scale of the block is passed
+    /// explicitly.
+    /// Empty lines and lines containing only a comment are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::Block;
+    ///
+    /// let mass = Block::<i64, f64>::from_body("6 173.2\n5 4.25", None).unwrap();
+    /// assert_eq!(mass.map.len(), 2);
+    /// assert_eq!(mass.map[&6], 173.2);
+    /// assert_eq!(mass.map[&5], 4.25);
+    /// assert_eq!(mass.scale, None);
+    /// ```
+    pub fn from_body(body: &str, scale: Option<f64>) -> Result<Block<Key, Value>> {
+        <Block<Key, Value> as SlhaBlock>::parse(&internal::split_body(body), scale)
+    }
 }
-
-fn parse_lines_helper<'input, K, V>(
-    lines: &[Line<'input>],
-    parser: fn(&str) -> Result<(K, V)>,
-) -> Result<HashMap<K, V>>
+impl<Key, Value> Block<Key, Value>
 where
-    K: Hash + Eq,
+    Key: Hash + Eq,
 {
-    let mut map = HashMap::new();
-    for (i, line) in lines.iter().enumerate() {
-        let (key, value) = parser(line.data).chain_err(
-            || ErrorKind::InvalidBlockLine(i + 1),
-        )?;
-        let dup = map.insert(key, value);
-        if dup.is_some() {
-            bail!(ErrorKind::DuplicateKey(i + 1));
+    /// Creates an empty block with the given scale.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::Block;
+    ///
+    /// let mut ye: Block<(u8, u8), f64> = Block::new(Some(4.64649125e+02));
+    /// assert!(ye.map.is_empty());
+    /// ye.map.insert((3, 3), 9.97405356e-02);
+    /// assert_eq!(ye.scale, Some(4.64649125e+02));
+    /// assert_eq!(ye.map[&(3, 3)], 9.97405356e-02);
+    /// ```
+    pub fn new(scale: Option<f64>) -> Block<Key, Value> {
+        Block {
+            scale,
+            map: HashMap::new(),
         }
     }
-    Ok(map)
-}
 
-/// `BlockStr` is a more flexible but less typesafe version of `Block`.
-///
-/// It represents a block from an SLHA file as a map from a vector of string keys to a value.
-/// This type of `Block` can still be used even if only the output type is known at compile time while the
-/// key types (and the number of keys) are only known at runtime.
-/// This is for example the case when working with the `UFO` format, where each external parameter contains a block name in an SLHA file and
-/// the keys into the block in string form.
-///
-/// The split between value and key is such that the value is the longest expression at the end of
-/// of a line that can be parsed to the `Value` type, and everything before, split at whitespace,
-/// is used as the key.
-/// For example, the line
-///
-/// ```none
-/// 1 this    5   bar   7.3 3
-/// ```
-///
-/// would be split into `vec!["1", "this", "5", "bar"]` and `(7.3, 3.0)` if `Value` = `(f64, f64)`
-/// or `vec!["1", "this", "5", "bar", "7.3"]` and `3` if `Value` = `i8`.
-///
-/// Duplicate keys in a block are treated as a parse error.
-///
-/// # Example
-///
-/// ```rust
-/// use slha::{Slha, BlockStr};
-/// # fn main() {
-/// let input = "\
-/// BLOCK TEST
-///    1 3
-///    4 6
-/// block Mass
-///    6  173.2
-/// BloCk FooBar
-///    1 2 3 4 0.5
-///    1 assdf 3 4 8
-///    1 2 4 8.98
-/// ";
-/// let slha = Slha::parse(input).unwrap();
-///
-/// let test: BlockStr<i64> = match slha.get_block("test") {
-///     Some(block) => match block {
-///         Ok(test) => test,
-///         Err(err) => panic!("There was a parse error while parsing block 'test': {}", err),
-///     },
-///     None => panic!("Missing block 'test'"),
-/// };
-/// assert_eq!(test.map.len(), 2);
-/// assert_eq!(test.map[&vec!["1".to_string()]], 3);
-/// assert_eq!(test.map[&vec!["4".to_string()]], 6);
-///
-/// let mass: BlockStr<(i64, f64)> = match slha.get_block("mass") {
-///     Some(block) => match block {
-///         Ok(mass) => mass,
-///         Err(err) => panic!("There was a parse error while parsing block 'mass': {}", err),
-///     },
-///     None => panic!("Missing block 'mass'"),
-/// };
-/// assert_eq!(mass.map.len(), 1);
-/// assert_eq!(mass.map[&Vec::new()], (6, 173.2));
-///
-/// let foobar: BlockStr<f64> = match slha.get_block("foobar") {
-///     Some(block) => match block {
-///         Ok(foobar) => foobar,
-///         Err(err) => panic!("There was a parse error while parsing block 'foobar': {}", err),
-///     },
-///     None => panic!("Missing block 'foobar'"),
-/// };
-/// assert_eq!(foobar.map.len(), 3);
-/// assert_eq!(
-///     foobar.map[&vec![
-///         "1".to_string(),
-///         "2".to_string(),
-///         "3".to_string(),
-///         "4".to_string(),
-///     ]],
-///     0.5
-/// );
-/// assert_eq!(
-///     foobar.map[&vec![
-///         "1".to_string(),
-///         "assdf".to_string(),
-///         "3".to_string(),
-///         "4".to_string(),
-///     ]],
-///     8.
-///     );
-/// assert_eq!(
-///     foobar.map[&vec!["1".to_string(), "2".to_string(), "4".to_string()]],
-///     8.98
-/// );
-/// # }
-/// ```
-#[derive(Clone, Debug, PartialEq)]
-pub struct BlockStr<Value> {
-    pub scale: Option<f64>,
-    pub map: HashMap<Vec<String>, Value>,
+    /// Creates an empty block without a scale that can hold at least `capacity` entries without
+    /// reallocating.
+    ///
+    /// Blocks read from an SLHA file are already allocated with the number of data lines as
+    /// capacity.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::Block;
+    ///
+    /// let mut mass: Block<i64, f64> = Block::with_capacity(2);
+    /// assert!(mass.map.capacity() >= 2);
+    /// mass.map.insert(6, 173.2);
+    /// mass.map.insert(5, 4.25);
+    /// assert_eq!(mass.scale, None);
+    /// assert_eq!(mass.map.len(), 2);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Block<Key, Value> {
+        Block {
+            scale: None,
+            map: HashMap::with_capacity(capacity),
+        }
+    }
+
+    /// Reserves capacity for at least `additional` more entries in the block.
+    pub fn reserve(&mut self, additional: usize) {
+        self.map.reserve(additional);
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    ///
+    /// If the key is not contained in the block, `None` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, Block};
+    ///
+    /// let input = "\
+    /// BLOCK MASS
+    ///    6    173.2    # M_t
+    /// ";
+    ///
+    /// let slha = Slha::parse(input).unwrap();
+    /// let mut mass: Block<i64, f64> = slha.get_block("mass").unwrap().unwrap();
+    /// if let Some(mt) = mass.get_mut(&6) {
+    ///     *mt = 172.5;
+    /// }
+    /// assert_eq!(mass.map[&6], 172.5);
+    /// assert_eq!(mass.get_mut(&5), None);
+    /// ```
+    pub fn get_mut(&mut self, key: &Key) -> Option<&mut Value> {
+        self.map.get_mut(key)
+    }
+
+    /// Returns an iterator over mutable references to all values in the block.
+    ///
+    /// The values are visited in arbitrary order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, Block};
+    ///
+    /// let input = "\
+    /// Block SMINPUTS   # Standard Model inputs
+    ///      3      0.1172  # alpha_s(MZ) SM MSbar
+    ///      5      4.25    # Mb(mb) SM MSbar
+    ///      6    174.3     # Mtop(pole)
+    /// ";
+    ///
+    /// let slha = Slha::parse(input).unwrap();
+    /// let mut sminputs: Block<i8, f64> = slha.get_block("sminputs").unwrap().unwrap();
+    /// for value in sminputs.values_mut() {
+    ///     *value *= 2.;
+    /// }
+    /// assert_eq!(sminputs.map[&3], 0.2344);
+    /// assert_eq!(sminputs.map[&5], 8.5);
+    /// assert_eq!(sminputs.map[&6], 348.6);
+    /// ```
+    pub fn values_mut(&mut self) -> ValuesMut<'_, Key, Value> {
+        self.map.values_mut()
+    }
+
+    /// Compares the contents of two blocks, ignoring their scales.
+    ///
+    /// Unlike `==`, which also requires the scales to be equal, this method only compares the
+    /// maps of the two blocks.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, Block};
+    ///
+    /// let input = "\
+    /// Block ye Q= 20
+    ///     3  3 9.0e-02
+    /// Block ye Q= 40
+    ///     3  3 9.0e-02
+    /// ";
+    ///
+    /// let slha = Slha::parse(input).unwrap();
+    /// let ye: Vec<Block<(u8, u8), f64>> = slha.get_blocks("ye").unwrap();
+    /// assert!(ye[0] != ye[1]);
+    /// assert!(ye[0].content_eq(&ye[1]));
+    /// ```
+    pub fn content_eq(&self, other: &Block<Key, Value>) -> bool
+    where
+        Value: PartialEq,
+    {
+        self.map == other.map
+    }
+
+    /// Consumes the block and returns the map of its entries.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, Block};
+    /// use std::collections::HashMap;
+    ///
+    /// let input = "\
+    /// BLOCK MASS
+    ///    6    173.2    # M_t
+    ///    5    4.25     # M_b
+    /// ";
+    ///
+    /// let slha = Slha::parse(input).unwrap();
+    /// let mass: Block<i64, f64> = slha.get_block("mass").unwrap().unwrap();
+    /// let map: HashMap<i64, f64> = mass.into_map();
+    /// assert_eq!(map.len(), 2);
+    /// assert_eq!(map[&6], 173.2);
+    /// ```
+    pub fn into_map(self) -> HashMap<Key, Value> {
+        self.map
+    }
+
+    /// Returns the entries of the block sorted by key.
+    ///
+    /// This gives a deterministic order of the entries, e.g. for writing a block.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, Block};
+    ///
+    /// let input = "\
+    /// BLOCK MASS
+    ///    25   125.09
+    ///     6   173.2
+    ///    23   91.1876
+    /// ";
+    ///
+    /// let slha = Slha::parse(input).unwrap();
+    /// let mass: Block<i64, f64> = slha.get_block("mass").unwrap().unwrap();
+    /// assert_eq!(
+    ///     mass.to_sorted_vec(),
+    ///     vec![(&6, &173.2), (&23, &91.1876), (&25, &125.09)]
+    /// );
+    /// ```
+    pub fn to_sorted_vec(&self) -> Vec<(&Key, &Value)>
+    where
+        Key: Ord,
+    {
+        let mut entries: Vec<_> = self.map.iter().collect();
+        entries.sort_by_key(|&(key, _)| key);
+        entries
+    }
+
+    /// Checks whether the block is defined at approximately the scale `q`.
+    ///
+    /// This is the case if the scale of the block differs from `q` by at most `rel_tol * |q|`.
+    /// A block without a scale is never considered to be at the scale `q`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, Block};
+    ///
+    /// let input = "\
+    /// Block ye Q= 4.64649125e+02
+    ///     3  3 9.97405356e-02   # Ytau(Q)MSSM DRbar
+    /// ";
+    ///
+    /// let slha = Slha::parse(input).unwrap();
+    /// let ye: Block<(u8, u8), f64> = slha.get_block("ye").unwrap().unwrap();
+    /// assert!(ye.scale_approx(464.649, 1e-5));
+    /// assert!(!ye.scale_approx(464.649, 1e-9));
+    /// ```
+    pub fn scale_approx(&self, q: f64, rel_tol: f64) -> bool {
+        scale_approx(self.scale, q, rel_tol)
+    }
 }
-impl<Value> SlhaBlock for BlockStr<Value>
+
+impl<Key> Block<Key, f64>
 where
-    Value: Parseable,
+    Key: Hash + Ord,
 {
-    fn parse<'input>(lines: &[Line<'input>], scale: Option<f64>) -> Result<Self> {
-        let map = parse_lines_helper(lines, parse_line_block_str)?;
-        Ok(BlockStr { scale, map })
+    /// Adds up the values of all entries of the block.
+    ///
+    /// The values are summed in the order of their keys, so that the result does not depend on
+    /// the order of the entries in the SLHA file.
+    /// An empty block sums to `0`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, Block};
+    ///
+    /// let input = "\
+    /// Block XSECTIONS
+    ///    1    0.25
+    ///    2    1.5
+    ///    3    0.125
+    /// ";
+    ///
+    /// let slha = Slha::parse(input).unwrap();
+    /// let xsections: Block<i64, f64> = slha.get_block("xsections").unwrap().unwrap();
+    /// assert_eq!(xsections.sum_values(), 1.875);
+    /// ```
+    pub fn sum_values(&self) -> f64 {
+        let mut entries: Vec<_> = self.map.iter().collect();
+        entries.sort_by_key(|&(key, _)| key);
+        entries.into_iter().map(|(_, value)| value).sum()
     }
-    fn scale(&self) -> Option<f64> {
-        self.scale
+
+    /// Computes a fingerprint of the scale and the entries of the block.
+    ///
+    /// Since `f64` does not implement `Hash`, the values are hashed using their bit patterns.
+    /// The entries are hashed in the order of their keys, so two blocks with equal scales and
+    /// entries always have the same `content_hash`, independently of the order of the entries in
+    /// the SLHA file.
+    /// The hash is computed with the 64 bit FNV-1a algorithm from the little endian bytes of the
+    /// scale, keys and values, so it is the same on every platform and can be stored, as long as
+    /// the `Hash` implementation of the keys does not change.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, Block};
+    ///
+    /// let input = "\
+    /// Block MASS
+    ///    6    173.2
+    ///    5    4.25
+    /// Block MASSREVERSED
+    ///    5    4.25
+    ///    6    173.2
+    /// ";
+    ///
+    /// let slha = Slha::parse(input).unwrap();
+    /// let mass: Block<i64, f64> = slha.get_block("mass").unwrap().unwrap();
+    /// let reversed: Block<i64, f64> = slha.get_block("massreversed").unwrap().unwrap();
+    /// assert_eq!(mass.content_hash(), reversed.content_hash());
+    /// ```
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = FnvHasher::new();
+        match self.scale {
+            Some(scale) => {
+                hasher.write_u8(1);
+                hasher.write_u64(scale.to_bits());
+            }
+            None => hasher.write_u8(0),
+        }
+        let mut entries: Vec<_> = self.map.iter().collect();
+        entries.sort_by_key(|&(key, _)| key);
+        for (key, value) in entries {
+            key.hash(&mut hasher);
+            hasher.write_u64(value.to_bits());
+        }
+        hasher.finish()
     }
 }
-
-fn parse_line_block_str<'input, Value>(line: &'input str) -> Result<(Vec<String>, Value)>
+impl<Index> Block<(Index, Index), f64>
 where
-    Value: Parseable,
+    Index: Hash + Eq + Copy + Into<i64>,
 {
-    let mut val = Value::parse(line).end();
-    let mut keys = Vec::new();
-    let mut rest = line;
-    while let Err(_) = val {
-        if let Some((key, line)) = next_word(rest) {
-            keys.push(key.to_string());
-            val = Value::parse(line).end();
-            rest = line;
-        } else {
-            return Err(ErrorKind::InvalidBlockValue.into());
+    /// Converts a block of matrix entries into a square matrix.
+    ///
+    /// The keys of the block are the row and column indices of the entries, starting at 1, as
+    /// used e.g. for the mixing matrices in the SLHA standards.
+    /// The dimension of the matrix is inferred from the largest index in the block and is returned
+    /// together with the matrix, which is stored as a vector of rows.
+    /// An empty block gives a matrix of dimension 0.
+    ///
+    /// # Errors
+    ///
+    /// An `InvalidMatrixIndex` error is returned if any of the indices is smaller than 1.
+    /// A `MissingMatrixEntry` error is returned if the block does not contain all entries of the
+    /// matrix.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, Block};
+    ///
+    /// let input = "\
+    /// Block stopmix  # stop mixing matrix
+    ///    1  1     5.37975095e-01   # O_{11}
+    ///    1  2     8.42960733e-01   # O_{12}
+    ///    2  1     8.42960733e-01   # O_{21}
+    ///    2  2    -5.37975095e-01   # O_{22}
+    /// ";
+    ///
+    /// let slha = Slha::parse(input).unwrap();
+    /// let stopmix: Block<(u8, u8), f64> = slha.get_block("stopmix").unwrap().unwrap();
+    /// let (n, matrix) = stopmix.to_matrix().unwrap();
+    /// assert_eq!(n, 2);
+    /// assert_eq!(matrix[0], vec![5.37975095e-01, 8.42960733e-01]);
+    /// assert_eq!(matrix[1], vec![8.42960733e-01, -5.37975095e-01]);
+    /// ```
+    pub fn to_matrix(&self) -> Result<(usize, Vec<Vec<f64>>)> {
+        let mut entries = HashMap::with_capacity(self.map.len());
+        let mut dim = 0;
+        for (&(row, col), &value) in &self.map {
+            let (row, col) = (row.into(), col.into());
+            if row < 1 || col < 1 {
+                bail!(ErrorKind::InvalidMatrixIndex(row, col));
+            }
+            dim = dim.max(row as usize).max(col as usize);
+            entries.insert((row as usize, col as usize), value);
         }
+        let values = dense_entries(entries.len(), dim, 2, |index| {
+            entries.get(&(index[0], index[1])).cloned()
+        }).map_err(|index| ErrorKind::MissingMatrixEntry(index[0], index[1]))?;
+        if dim == 0 {
+            return Ok((0, Vec::new()));
+        }
+        let matrix = values.chunks(dim).map(|row| row.to_vec()).collect();
+        Ok((dim, matrix))
     }
-    Ok((keys, val.expect("BUG: This should be impossible.")))
 }
-
-/// A block type that only contains a single value.
-///
-/// This type of block does not represent a map like `Block` but just a single value and an
+impl<Index> Block<(Index, Index, Index), f64>
+where
+    Index: Hash + Eq + Copy + Into<i64>,
+{
+    /// Converts a block with three indices into a cubic tensor.
+    ///
+    /// This works like `to_matrix`, but for blocks keyed by three indices, like the R-parity
+    /// violating couplings in `RVLAMLLE` from SLHA2.
+    /// The tensor is returned as nested vectors, such that the entry `(i, j, k)` of the block is
+    /// found at `tensor[i - 1][j - 1][k - 1]`.
+    ///
+    /// # Errors
+    ///
+    /// An `InvalidTensorIndex` error is returned if any of the indices is smaller than 1.
+    /// A `MissingTensorEntry` error is returned if the block does not contain all entries of the
+    /// tensor.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, Block};
+    ///
+    /// let mut input = String::from("Block RVLAMLLE\n");
+    /// for i in 1..3 {
+    ///     for j in 1..3 {
+    ///         for k in 1..3 {
+    ///             input.push_str(&format!("  {} {} {}   {}\n", i, j, k, 100 * i + 10 * j + k));
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let slha = Slha::parse(&input).unwrap();
+    /// let lambda: Block<(i8, i8, i8), f64> = slha.get_block("rvlamlle").unwrap().unwrap();
+    /// let (n, tensor) = lambda.to_tensor3().unwrap();
+    /// assert_eq!(n, 2);
+    /// assert_eq!(tensor[0][1][0], 121.);
+    /// assert_eq!(tensor[1][1][1], 222.);
+    /// ```
+    pub fn to_tensor3(&self) -> Result<(usize, Vec<Vec<Vec<f64>>>)> {
+        let mut entries = HashMap::with_capacity(self.map.len());
+        let mut dim = 0;
+        for (&(i, j, k), &value) in &self.map {
+            let (i, j, k) = (i.into(), j.into(), k.into());
+            if i < 1 || j < 1 || k < 1 {
+                bail!(ErrorKind::InvalidTensorIndex(i, j, k));
+            }
+            dim = dim.max(i as usize).max(j as usize).max(k as usize);
+            entries.insert((i as usize, j as usize, k as usize), value);
+        }
+        let values = dense_entries(entries.len(), dim, 3, |index| {
+            entries.get(&(index[0], index[1], index[2])).cloned()
+        }).map_err(|index| ErrorKind::MissingTensorEntry(index[0], index[1], index[2]))?;
+        if dim == 0 {
+            return Ok((0, Vec::new()));
+        }
+        let tensor = values
+            .chunks(dim * dim)
+            .map(|matrix| matrix.chunks(dim).map(|row| row.to_vec()).collect())
+            .collect();
+        Ok((dim, tensor))
+    }
+}
+
+/// Collects the entries of a dense tensor with `rank` indices running from 1 to `dim`.
+///
+/// The entries are looked up using `get` and returned in row-major order.
+/// If an entry is missing, the indices of the first missing entry are returned instead.
+/// Since `dim` is the largest index found in a file, the number of entries `n_entries` is
+/// checked to match the size of the tensor before any memory is allocated for it.
+fn dense_entries(
+    n_entries: usize,
+    dim: usize,
+    rank: u32,
+    get: impl Fn(&[usize]) -> Option<f64>,
+) -> ::std::result::Result<Vec<f64>, Vec<usize>> {
+    let complete = dim.checked_pow(rank) == Some(n_entries);
+    let mut values = Vec::with_capacity(if complete { n_entries } else { 0 });
+    if dim == 0 {
+        return Ok(values);
+    }
+    // All indices are between 1 and `dim`, so if the tensor is not complete, a missing entry is
+    // found after at most `n_entries + 1` steps.
+    let mut index = vec![1; rank as usize];
+    loop {
+        match get(&index) {
+            Some(value) if complete => values.push(value),
+            Some(_) => {}
+            None => return Err(index),
+        }
+        match index.iter().rposition(|&i| i < dim) {
+            Some(pos) => {
+                index[pos] += 1;
+                for i in &mut index[pos + 1..] {
+                    *i = 1;
+                }
+            }
+            None => return Ok(values),
+        }
+    }
+}
+
+/// The relative tolerance used by `Slha::get_block_by_scale` to compare scales.
+const SCALE_REL_TOL: f64 = 1e-6;
+
+fn scale_approx(scale: Option<f64>, q: f64, rel_tol: f64) -> bool {
+    match scale {
+        Some(scale) => (scale - q).abs() <= rel_tol * q.abs(),
+        None => false,
+    }
+}
+
+fn parse_line_block<'input, K, V>(input: &'input str) -> Result<(K, V)>
+where
+    K: Parseable,
+    V: Parseable,
+{
+    let input = input.trim_left();
+    let (input, key) = K::parse(input).to_result().chain_err(
+        || ErrorKind::InvalidBlockKey,
+    )?;
+    let value = V::parse(input).end().chain_err(
+        || ErrorKind::InvalidBlockValue,
+    )?;
+    Ok((key, value))
+}
+
+fn parse_lines_helper<'input, K, V>(
+    lines: &[Line<'input>],
+    parser: fn(&str) -> Result<(K, V)>,
+) -> Result<HashMap<K, V>>
+where
+    K: Hash + Eq,
+{
+    // Every line holds one entry, so the map never has to grow while it is filled.
+    let mut map = HashMap::with_capacity(lines.len());
+    for (i, line) in lines.iter().enumerate() {
+        let (key, value) = parser(line.data).chain_err(
+            || ErrorKind::InvalidBlockLine(i + 1),
+        )?;
+        let dup = map.insert(key, value);
+        if dup.is_some() {
+            bail!(ErrorKind::DuplicateKey(i + 1));
+        }
+    }
+    Ok(map)
+}
+
+/// Finds the input left over at the end of a line, i.e. an `IncompleteParse` error, by following
+/// the `source` of `err`.
+fn incomplete_parse(err: &Error) -> Option<&str> {
+    let mut current: &(dyn StdError + 'static) = err;
+    loop {
+        if let Some(&Error(ErrorKind::IncompleteParse(ref rest), _)) = current.downcast_ref() {
+            return Some(rest);
+        }
+        current = current.source()?;
+    }
+}
+
+/// Returns the number of the data line in the body of a segment that could not be read, if `err`
+/// is caused by an `InvalidBlockLine` or `InvalidDecayLine` error.
+fn failed_data_line(err: &Error) -> Option<usize> {
+    let mut current: &(dyn StdError + 'static) = err;
+    loop {
+        match current.downcast_ref() {
+            Some(&Error(ErrorKind::InvalidBlockLine(n), _)) |
+            Some(&Error(ErrorKind::InvalidDecayLine(n), _)) => return Some(n),
+            _ => (),
+        }
+        current = current.source()?;
+    }
+}
+
+/// Returns how many lines after the first line of `segment` its `n`th data line appears, not
+/// counting empty lines and comments.
+fn data_line_offset(segment: &str, n: usize) -> Option<usize> {
+    segment
+        .lines()
+        .enumerate()
+        .skip(1)
+        .filter(|&(_, line)| {
+            let line = line.trim();
+            !line.is_empty() && !line.starts_with('#')
+        })
+        .nth(n.checked_sub(1)?)
+        .map(|(i, _)| i)
+}
+
+/// Returns the offset in `input` of the next line of `lines`, or the length of `input` if there
+/// are no lines left.
+fn line_offset(input: &str, lines: &mut iter::Peekable<str::Lines>) -> usize {
+    lines
+        .peek()
+        .map_or(input.len(), |line| line.as_ptr() as usize - input.as_ptr() as usize)
+}
+
+/// Counts the lines in `input[begin..end]` that only contain a comment.
+///
+/// The lines have been read as `segment`, whose header starts at the offset `header`, together
+/// with the comments and empty lines preceding it.
+/// The header and the commented out data lines of a disabled block are not counted.
+fn comment_lines(
+    input: &str,
+    begin: usize,
+    end: usize,
+    header: usize,
+    segment: Option<&Segment>,
+) -> usize {
+    let data: Vec<usize> = match segment {
+        Some(Segment::DisabledBlock { block, .. }) => {
+            block.lines.iter().map(|line| line.data.as_ptr() as usize).collect()
+        }
+        _ => Vec::new(),
+    };
+    input[begin..end]
+        .lines()
+        .filter(|line| {
+            let start = line.as_ptr() as usize;
+            line.trim_start().starts_with('#') && start - input.as_ptr() as usize != header &&
+                !data.iter().any(|&ptr| ptr >= start && ptr < start + line.len())
+        })
+        .count()
+}
+
+/// `BlockStr` is a more flexible but less typesafe version of `Block`.
+///
+/// It represents a block from an SLHA file as a map from a vector of string keys to a value.
+/// This type of `Block` can still be used even if only the output type is known at compile time while the
+/// key types (and the number of keys) are only known at runtime.
+/// This is for example the case when working with the `UFO` format, where each external parameter contains a block name in an SLHA file and
+/// the keys into the block in string form.
+///
+/// The split between value and key is such that the value is the longest expression at the end of
+/// of a line that can be parsed to the `Value` type, and everything before, split at whitespace,
+/// is used as the key.
+/// For example, the line
+///
+/// ```none
+/// 1 this    5   bar   7.3 3
+/// ```
+///
+/// would be split into `vec!["1", "this", "5", "bar"]` and `(7.3, 3.0)` if `Value` = `(f64, f64)`
+/// or `vec!["1", "this", "5", "bar", "7.3"]` and `3` if `Value` = `i8`.
+///
+/// Duplicate keys in a block are treated as a parse error.
+///
+/// # Example
+///
+/// ```rust
+/// use slha::{Slha, BlockStr};
+/// # fn main() {
+/// let input = "\
+/// BLOCK TEST
+///    1 3
+///    4 6
+/// block Mass
+///    6  173.2
+/// BloCk FooBar
+///    1 2 3 4 0.5
+///    1 assdf 3 4 8
+///    1 2 4 8.98
+/// ";
+/// let slha = Slha::parse(input).unwrap();
+///
+/// let test: BlockStr<i64> = match slha.get_block("test") {
+///     Some(block) => match block {
+///         Ok(test) => test,
+///         Err(err) => panic!("There was a parse error while parsing block 'test': {}", err),
+///     },
+///     None => panic!("Missing block 'test'"),
+/// };
+/// assert_eq!(test.map.len(), 2);
+/// assert_eq!(test.map[&vec!["1".to_string()]], 3);
+/// assert_eq!(test.map[&vec!["4".to_string()]], 6);
+///
+/// let mass: BlockStr<(i64, f64)> = match slha.get_block("mass") {
+///     Some(block) => match block {
+///         Ok(mass) => mass,
+///         Err(err) => panic!("There was a parse error while parsing block 'mass': {}", err),
+///     },
+///     None => panic!("Missing block 'mass'"),
+/// };
+/// assert_eq!(mass.map.len(), 1);
+/// assert_eq!(mass.map[&Vec::new()], (6, 173.2));
+///
+/// let foobar: BlockStr<f64> = match slha.get_block("foobar") {
+///     Some(block) => match block {
+///         Ok(foobar) => foobar,
+///         Err(err) => panic!("There was a parse error while parsing block 'foobar': {}", err),
+///     },
+///     None => panic!("Missing block 'foobar'"),
+/// };
+/// assert_eq!(foobar.map.len(), 3);
+/// assert_eq!(
+///     foobar.map[&vec![
+///         "1".to_string(),
+///         "2".to_string(),
+///         "3".to_string(),
+///         "4".to_string(),
+///     ]],
+///     0.5
+/// );
+/// assert_eq!(
+///     foobar.map[&vec![
+///         "1".to_string(),
+///         "assdf".to_string(),
+///         "3".to_string(),
+///         "4".to_string(),
+///     ]],
+///     8.
+///     );
+/// assert_eq!(
+///     foobar.map[&vec!["1".to_string(), "2".to_string(), "4".to_string()]],
+///     8.98
+/// );
+/// # }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlockStr<Value> {
+    pub scale: Option<f64>,
+    pub map: HashMap<Vec<String>, Value>,
+}
+impl<Value> SlhaBlock for BlockStr<Value>
+where
+    Value: Parseable,
+{
+    fn parse<'input>(lines: &[Line<'input>], scale: Option<f64>) -> Result<Self> {
+        let map = parse_lines_helper(lines, parse_line_block_str)?;
+        Ok(BlockStr { scale, map })
+    }
+    fn scale(&self) -> Option<f64> {
+        self.scale
+    }
+}
+
+fn parse_line_block_str<'input, Value>(line: &'input str) -> Result<(Vec<String>, Value)>
+where
+    Value: Parseable,
+{
+    // Most attempts to read the value fail, so `complete` is used instead of `end` to avoid
+    // constructing an error for each of them.
+    let mut val = Value::parse(line).complete();
+    let mut keys = Vec::new();
+    let mut rest = line;
+    while val.is_none() {
+        if let Some((key, line)) = next_word(rest) {
+            keys.push(key.to_string());
+            val = Value::parse(line).complete();
+            rest = line;
+        } else {
+            return Err(ErrorKind::InvalidBlockValue.into());
+        }
+    }
+    Ok((keys, val.expect("BUG: This should be impossible.")))
+}
+
+/// A block type that only contains a single value.
+///
+/// This type of block does not represent a map like `Block` but just a single value and an
 /// optional scale.
 /// This is the second type necessary to cover all blocks defined in the SLHA 1 and 2 papers.
 ///
@@ -1294,43 +2643,369 @@ where
         self.scale
     }
 }
+impl<Value> BlockSingle<Value> {
+    /// Creates a block containing `value` with the given scale.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::BlockSingle;
+    ///
+    /// let alpha = BlockSingle::new(-1.13825210e-01, Some(4.67e+02));
+    /// assert_eq!(alpha.value, -1.13825210e-01);
+    /// assert_eq!(alpha.scale, Some(4.67e+02));
+    /// ```
+    pub fn new(value: Value, scale: Option<f64>) -> BlockSingle<Value> {
+        BlockSingle { value, scale }
+    }
 
-/// The decay table of a particle.
-///
-/// The decay table as read from an SLHA file.
-///
-/// # Reading the decay table.
-///
-/// The `DecayTables` can be read from an SLHA file in two different ways, the recommended way using
-/// `slha-derive` or using an `Slha` object.
-///
-/// ## Using derive
-///
-/// The easiest way to read an SLHA file is to automatically derive the `SlhaDerive` trait on  a
-/// struct.
-/// To also include decays in the struct add a field named `decays`.
-/// This field must have a type of `HashMap<i64, DecayTable>`.
-///
-/// ```rust
-/// extern crate slha;
-/// #[macro_use]
-/// extern crate slha_derive;
-/// extern crate error_chain;
-///
-/// use slha::{DecayTable, SlhaDeserialize};
-/// use error_chain::ChainedError;
-/// use std::collections::HashMap;
-///
-/// #[derive(SlhaDeserialize)]
-/// struct Slha {
-///     decays: HashMap<i64, DecayTable>,
-/// }
-///
-/// fn main() {
-///     let input = "\
-/// DECAY 6 1.35
-///     1   2   5   24
-///     ";
+    /// Consumes the block and returns its value.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, BlockSingle};
+    ///
+    /// let input = "\
+    /// BLOCK ALPHA   # Effective Higgs mixing parameter
+    ///      -1.13825210e-01   # alpha
+    /// ";
+    ///
+    /// let slha = Slha::parse(input).unwrap();
+    /// let alpha: BlockSingle<f64> = slha.get_block("alpha").unwrap().unwrap();
+    /// assert_eq!(alpha.into_value(), -1.13825210e-01);
+    /// ```
+    pub fn into_value(self) -> Value {
+        self.value
+    }
+
+    /// Converts the value of the block using `f`, keeping the scale.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, BlockSingle};
+    ///
+    /// let input = "\
+    /// BLOCK ALPHA Q= 4.67e+02   # Effective Higgs mixing parameter
+    ///      -1.13825210e-01   # alpha
+    /// ";
+    ///
+    /// let slha = Slha::parse(input).unwrap();
+    /// let alpha: BlockSingle<f64> = slha.get_block("alpha").unwrap().unwrap();
+    /// let negated = alpha.map(|alpha| -alpha);
+    /// assert_eq!(negated.value, 1.13825210e-01);
+    /// assert_eq!(negated.scale, Some(4.67e+02));
+    /// ```
+    pub fn map<U>(self, f: impl FnOnce(Value) -> U) -> BlockSingle<U> {
+        BlockSingle {
+            value: f(self.value),
+            scale: self.scale,
+        }
+    }
+
+    /// Parses a single valued block that may erroneously contain a key.
+    ///
+    /// Some tools write single valued blocks like `ALPHA` with an integer index in front of the
+    /// value, e.g. `1 -0.11`.
+    /// If the line can not be read as a plain value, this method tries to read it as an integer
+    /// key followed by the value and drops the key.
+    /// The `SlhaBlock` implementation of `BlockSingle` does not accept such lines.
+    ///
+    /// # Errors
+    ///
+    /// A `WrongNumberOfValues` error is returned if there is not exactly one line, and the error
+    /// from reading the line as a plain value if it can not be read with a key either.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, BlockSingle};
+    ///
+    /// let input = "\
+    /// BLOCK ALPHA   # Effective Higgs mixing parameter
+    ///      1   -1.13825210e-01   # alpha
+    /// ";
+    ///
+    /// let slha = Slha::parse(input).unwrap();
+    /// let raw = &slha.get_raw_blocks("alpha")[0];
+    /// let alpha: BlockSingle<f64> = BlockSingle::from_possibly_keyed(&raw.lines, raw.scale).unwrap();
+    /// assert_eq!(alpha.value, -1.13825210e-01);
+    /// ```
+    pub fn from_possibly_keyed(lines: &[Line], scale: Option<f64>) -> Result<Self>
+    where
+        Value: Parseable,
+    {
+        if lines.len() != 1 {
+            bail!(ErrorKind::WrongNumberOfValues(lines.len()));
+        }
+        let err = match Value::parse(lines[0].data).end() {
+            Ok(value) => return Ok(BlockSingle { value, scale }),
+            Err(err) => err,
+        };
+        match parse_line_block::<i64, Value>(lines[0].data) {
+            Ok((_, value)) => Ok(BlockSingle { value, scale }),
+            Err(_) => Err(err),
+        }
+    }
+}
+
+/// A block combined from several sources, e.g. a base card and files overriding some entries.
+///
+/// The sources are merged in order, so that an entry of a later source overrides the same entry
+/// of all earlier ones.
+/// For every key, the index of the source the entry was taken from is recorded in `provenance`,
+/// which makes it possible to find out where an overridden value came from.
+/// The scale of the merged block is the scale of the last source.
+///
+/// # Examples
+///
+/// ```rust
+/// use slha::{Slha, MergedBlock};
+///
+/// let base = Slha::parse("Block MASS\n    6    173.2\n    5    4.25\n").unwrap();
+/// let custom = Slha::parse("Block MASS\n    6    172.5\n").unwrap();
+///
+/// let mass: MergedBlock<i64, f64> = MergedBlock::from_slhas(&[base, custom], "mass").unwrap();
+/// assert_eq!(mass.block.map[&6], 172.5);
+/// assert_eq!(mass.source(&6), Some(1));
+/// assert_eq!(mass.source(&5), Some(0));
+/// ```
+#[derive(Clone, PartialEq)]
+pub struct MergedBlock<Key, Value>
+where
+    Key: Hash + Eq,
+{
+    /// The merged block.
+    pub block: Block<Key, Value>,
+    /// The index of the source that each entry was taken from.
+    pub provenance: HashMap<Key, usize>,
+}
+impl<Key, Value> fmt::Debug for MergedBlock<Key, Value>
+where
+    Key: Hash + Eq + Ord + fmt::Debug,
+    Value: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MergedBlock")
+            .field("block", &self.block)
+            .field("provenance", &SortedMap(&self.provenance))
+            .finish()
+    }
+}
+impl<Key, Value> MergedBlock<Key, Value>
+where
+    Key: Hash + Eq + Clone,
+{
+    /// Merges the blocks in order, with entries of later blocks overriding earlier ones.
+    ///
+    /// The sources are numbered by their position in `blocks`, starting at zero.
+    /// If `blocks` is empty, the merged block is empty and has no scale.
+    pub fn from_blocks<I>(blocks: I) -> MergedBlock<Key, Value>
+    where
+        I: IntoIterator<Item = Block<Key, Value>>,
+    {
+        let mut merged = MergedBlock {
+            block: Block::new(None),
+            provenance: HashMap::new(),
+        };
+        for (source, block) in blocks.into_iter().enumerate() {
+            merged.insert(source, block);
+        }
+        merged
+    }
+
+    /// Returns the index of the source that the entry with the given key was taken from.
+    ///
+    /// If the key is not contained in the merged block, `None` is returned.
+    pub fn source(&self, key: &Key) -> Option<usize> {
+        self.provenance.get(key).cloned()
+    }
+
+    fn insert(&mut self, source: usize, block: Block<Key, Value>) {
+        self.block.scale = block.scale;
+        for (key, value) in block.map {
+            self.provenance.insert(key.clone(), source);
+            self.block.map.insert(key, value);
+        }
+    }
+}
+impl<Key, Value> MergedBlock<Key, Value>
+where
+    Key: Hash + Eq + Clone + Parseable,
+    Value: Parseable,
+{
+    /// Reads the block `name` from every file and merges the blocks in order.
+    ///
+    /// The sources are numbered by the position of the files in `slhas`, starting at zero.
+    /// Files that do not contain the block are skipped, but still count as a source.
+    ///
+    /// # Errors
+    ///
+    /// The same errors as for `Slha::get_block` are returned, if the block can not be read from
+    /// one of the files.
+    pub fn from_slhas(slhas: &[Slha], name: &str) -> Result<MergedBlock<Key, Value>> {
+        let mut merged = MergedBlock {
+            block: Block::new(None),
+            provenance: HashMap::new(),
+        };
+        for (source, slha) in slhas.iter().enumerate() {
+            let block: Block<Key, Value> = match slha.get_block(name) {
+                Some(block) => block?,
+                None => continue,
+            };
+            merged.insert(source, block);
+        }
+        Ok(merged)
+    }
+}
+
+/// All occurrences of a block at different scales, e.g. the grid of a running parameter.
+///
+/// The blocks are sorted by their scale in ascending order.
+/// Blocks without a scale are not part of the grid.
+///
+/// A `ScaleGrid` can be obtained from an `Slha` object using the `get_scale_grid` method, or
+/// constructed directly from the blocks returned by `get_blocks`.
+///
+/// # Examples
+///
+/// ```rust
+/// use slha::{Slha, Block, ScaleGrid};
+///
+/// let input = "\
+/// Block ye Q= 40
+///     3  3 7.0e-02   # Ytau(Q)MSSM DRbar
+/// Block ye Q= 10
+///     3  3 9.0e-02   # Ytau(Q)MSSM DRbar
+/// ";
+///
+/// let slha = Slha::parse(input).unwrap();
+/// let ye: ScaleGrid<Block<(i8, i8), f64>> = slha.get_scale_grid("ye").unwrap();
+/// assert_eq!(ye.scales(), vec![10., 40.]);
+/// assert_eq!(ye.value_at(&(3, 3), 10.), Some(9.0e-02));
+/// assert_eq!(ye.value_at(&(3, 3), 1000.), Some(7.0e-02));
+/// assert_eq!(ye.value_at(&(1, 1), 20.), None);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScaleGrid<B> {
+    blocks: Vec<B>,
+}
+impl<B> ScaleGrid<B>
+where
+    B: SlhaBlock,
+{
+    /// Creates a new grid from the occurrences of a block.
+    ///
+    /// Blocks without a scale are discarded.
+    pub fn new(blocks: Vec<B>) -> ScaleGrid<B> {
+        let mut blocks: Vec<B> = blocks
+            .into_iter()
+            .filter(|block| block.scale().is_some())
+            .collect();
+        blocks.sort_by(|b1, b2| b1.scale().partial_cmp(&b2.scale()).unwrap());
+        ScaleGrid { blocks }
+    }
+
+    /// Returns the blocks of the grid, sorted by their scale in ascending order.
+    pub fn blocks(&self) -> &[B] {
+        &self.blocks
+    }
+
+    /// Returns the scales of the grid in ascending order.
+    pub fn scales(&self) -> Vec<f64> {
+        self.blocks.iter().filter_map(|block| block.scale()).collect()
+    }
+}
+impl<Key> ScaleGrid<Block<Key, f64>>
+where
+    Key: Hash + Eq,
+{
+    /// Returns the value of the entry `key` at the scale `q`.
+    ///
+    /// Between the scales of the grid, the value is interpolated linearly in the logarithm of the
+    /// scale.
+    /// Outside of the range of the grid, the value at the nearest scale is returned.
+    /// Only the blocks that contain `key` are taken into account.
+    /// If none of the blocks contains `key`, `None` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, Block, ScaleGrid};
+    ///
+    /// let input = "\
+    /// Block ye Q= 10
+    ///     3  3 1.0e-01   # Ytau(Q)MSSM DRbar
+    /// Block ye Q= 1000
+    ///     3  3 3.0e-01   # Ytau(Q)MSSM DRbar
+    /// ";
+    ///
+    /// let slha = Slha::parse(input).unwrap();
+    /// let ye: ScaleGrid<Block<(i8, i8), f64>> = slha.get_scale_grid("ye").unwrap();
+    /// let ytau = ye.value_at(&(3, 3), 100.).unwrap();
+    /// assert!((ytau - 2.0e-01).abs() < 1e-12);
+    /// ```
+    pub fn value_at(&self, key: &Key, q: f64) -> Option<f64> {
+        let points: Vec<(f64, f64)> = self.blocks
+            .iter()
+            .filter_map(|block| match (block.scale, block.map.get(key)) {
+                (Some(scale), Some(&value)) => Some((scale, value)),
+                _ => None,
+            })
+            .collect();
+        let &(first_scale, first_value) = points.first()?;
+        if q <= first_scale {
+            return Some(first_value);
+        }
+        for window in points.windows(2) {
+            let (q0, v0) = window[0];
+            let (q1, v1) = window[1];
+            if q <= q1 {
+                let t = (q / q0).ln() / (q1 / q0).ln();
+                return Some(v0 + t * (v1 - v0));
+            }
+        }
+        points.last().map(|&(_, value)| value)
+    }
+}
+
+/// The decay table of a particle.
+///
+/// The decay table as read from an SLHA file.
+///
+/// # Reading the decay table.
+///
+/// The `DecayTables` can be read from an SLHA file in two different ways, the recommended way using
+/// `slha-derive` or using an `Slha` object.
+///
+/// ## Using derive
+///
+/// The easiest way to read an SLHA file is to automatically derive the `SlhaDerive` trait on  a
+/// struct.
+/// To also include decays in the struct add a field named `decays`.
+/// This field must have a type of `HashMap<i64, DecayTable>`.
+///
+/// ```rust
+/// extern crate slha;
+/// #[macro_use]
+/// extern crate slha_derive;
+/// extern crate error_chain;
+///
+/// use slha::{DecayTable, SlhaDeserialize};
+/// use error_chain::ChainedError;
+/// use std::collections::HashMap;
+///
+/// #[derive(SlhaDeserialize)]
+/// struct Slha {
+///     decays: HashMap<i64, DecayTable>,
+/// }
+///
+/// fn main() {
+///     let input = "\
+/// DECAY 6 1.35
+///     1   2   5   24
+///     ";
 ///
 ///     let slha = match Slha::deserialize(input) {
 ///         Ok(slha) => slha,
@@ -1381,1035 +3056,7509 @@ pub struct DecayTable {
     /// All decay modes of the particle.
     pub decays: Vec<Decay>,
 }
-
-/// A single decay mode of a particle.
-#[derive(Clone, Debug, PartialEq)]
-pub struct Decay {
-    /// The branching ratio of this decay mode.
-    pub branching_ratio: f64,
-    /// A vector of all daughter particles.
-    pub daughters: Vec<i64>,
-}
-
-/// A line read from an SLHA file.
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
-pub struct Line<'input> {
-    /// The data contained in the line.
-    pub data: &'input str,
-    /// The comment at the end of the line, if present.
-    pub comment: Option<&'input str>,
-}
-
-/// An unparsed block from an SLHA file.
-///
-/// `RawBlock` contains all the non-comment, non-whitespace lines that belong to a block as well as
-/// the scale that the contents of the block are defined at, if any.
-///
-/// # Examples
-///
-/// ```rust
-/// use slha::Slha;
-///
-/// let input = "\
-/// Block SMINPUTS   # Standard Model inputs
-///      3      0.1172  # alpha_s(MZ) SM MSbar
-///      5      4.25    # Mb(mb) SM MSbar
-///      6    174.3     # Mtop(pole)
-/// DECAY 6 1.35
-///     1   2   5   24
-/// ";
-///
-/// let slha = match Slha::parse(input) {
-///     Ok(slha) => slha,
-///     Err(err) => panic!("Failed to deserialize SLHA file: {}", err),
-/// };
-///
-/// let blocks = slha.get_raw_blocks("sminputs");
-/// assert_eq!(blocks.len(), 1);
-/// let sminputs = &blocks[0];
-/// assert_eq!(sminputs.scale, None);
-/// assert_eq!(sminputs.lines.len(), 3);
-/// assert_eq!(sminputs.lines[1].data, "5      4.25    ");
-/// assert_eq!(sminputs.lines[1].comment, Some("# Mb(mb) SM MSbar"));
-/// ```
-#[derive(Clone, Debug, PartialEq)]
-pub struct RawBlock<'a> {
-    /// The scale contained in the block header.
-    pub scale: Option<f64>,
-    /// The data lines that make up the block, in the order they appear in the SLHA file.
-    pub lines: Vec<Line<'a>>,
-}
-impl<'a> RawBlock<'a> {
-    /// Convert a `RawBlock` into a rust object.
+impl DecayTable {
+    /// Creates a new decay table, checking that the width is valid.
     ///
-    /// # Examples
-    /// ```rust
-    /// use slha::{Slha, Block};
+    /// The decays are not checked, use `Decay::try_new` to create valid decays.
     ///
-    /// let input = "\
-    /// Block SMINPUTS   # Standard Model inputs
-    ///      3      0.1172  # alpha_s(MZ) SM MSbar
-    ///      5      4.25    # Mb(mb) SM MSbar
-    ///      6    174.3     # Mtop(pole)
-    /// DECAY 6 1.35
-    ///     1   2   5   24
-    /// ";
+    /// # Errors
     ///
-    /// let slha = match Slha::parse(input) {
-    ///     Ok(slha) => slha,
-    ///     Err(err) => panic!("Failed to read SLHA file: {}", err),
-    /// };
+    /// An `InvalidWidthValue` error is returned if the width is negative, infinite or not a
+    /// number.
     ///
-    /// let blocks = slha.get_raw_blocks("sminputs");
-    /// assert_eq!(blocks.len(), 1);
-    /// let sminputs_raw = &blocks[0];
+    /// # Examples
     ///
-    /// let sminputs: Block<u8, f64> = match sminputs_raw.to_block("sminputs") {
-    ///     Ok(block) => block,
-    ///     Err(err) => panic!("Failed to parse block 'sminputs': {}", err),
-    /// };
+    /// ```rust
+    /// use slha::{Decay, DecayTable};
     ///
-    /// assert_eq!(sminputs.scale, None);
-    /// assert_eq!(sminputs.map.len(), 3);
-    /// assert_eq!(sminputs.map[&5], 4.25);
+    /// let decays = vec![Decay::try_new(1., vec![5, 24]).unwrap()];
+    /// let table = DecayTable::try_new(1.35, decays).unwrap();
+    /// assert_eq!(table.width, 1.35);
+    /// assert!(DecayTable::try_new(-1.35, Vec::new()).is_err());
     /// ```
-    pub fn to_block<B>(&self, name: &str) -> Result<B>
-    where
-        B: SlhaBlock,
-    {
-        B::parse(&self.lines, self.scale).chain_err(|| ErrorKind::InvalidBlock(name.to_string()))
+    pub fn try_new(width: f64, decays: Vec<Decay>) -> Result<DecayTable> {
+        if !width.is_finite() || width < 0. {
+            bail!(ErrorKind::InvalidWidthValue(width));
+        }
+        Ok(DecayTable { width, decays })
     }
-}
 
-/// A partially parsed SLHA file.
-///
-/// `Slha` objects are another way to parse SLHA files without using the `SlhaDeserialize` trait.
-/// The advantage that using an `SLHA` object has over deriving `SlhaDeserialize` for a struct is
-/// that the names of the blocks and their types do not have to be known at compile time.
-/// This is useful in a few special cases, for exmaple when dealing with the `UFO` format, where every
-/// external parameter comes with the name of an SLHA block and a string containing the keys where
-/// to find the value for this parameter in an SLHA file.
-/// Since the blocks and their types are only known when reading the model file, the
-/// `SlhaDeserialize` approach cannot be used.
-///
-/// The `Slha` object uses a two step parsing approach, because the types of Blocks aren't known
-/// when reading the file and the SLHA format isn't self describing so that the information can not
-/// be extracted from there.
-/// Therefore the `parse` function will extract all blocks (and decays) but only parses the headers
-/// of the blocks and stores their bodies.
-/// The various `get_block` functions then parse the body of the block into the desired type 'on
-/// demand' when accessing a block.
-///
-/// The `get_block` methods can parse a block into any type that implements the `SlhaBlock` trait,
-/// which include `Block` (if both key and value type are known) and `BlockStr` (if only the value
-/// type is known).
-/// If even the value type is unknown at compile time, the `get_raw_block` methods can be used which
-/// return the raw block body read from the file.
-///
-/// # Example
-///
-/// The following example shows all three ways how to access a single block, as well as how to
-/// access the decay table for a given particle.
-///
-/// First, extract all blocks and decays into an `Slha` object:
-///
-/// ```rust
-/// use slha::{Slha, Block, BlockStr, DecayTable};
-///
-/// let input = "\
-/// Block SMINPUTS   # Standard Model inputs
-///      3      0.1172  # alpha_s(MZ) SM MSbar
-///      5      4.25    # Mb(mb) SM MSbar
-///      6    174.3     # Mtop(pole)
-/// DECAY 6 1.35
-///     1   2   5   24
-/// ";
-///
-/// let slha = match Slha::parse(input) {
-///     Ok(slha) => slha,
-///     Err(err) => panic!("Failed to deserialize SLHA file: {}", err),
-/// };
-/// ```
-///
-/// If both the value type and the key type are known, blocks can be extracted into a `Block`
-/// structure:
-///
-/// ```rust
-/// # use slha::{Slha, Block, BlockStr, DecayTable};
-/// #
-/// # let input = "\
-/// # Block SMINPUTS   # Standard Model inputs
-/// #      3      0.1172  # alpha_s(MZ) SM MSbar
-/// #      5      4.25    # Mb(mb) SM MSbar
-/// #      6    174.3     # Mtop(pole)
-/// # DECAY 6 1.35
-/// #     1   2   5   24
-/// # ";
-/// #
-/// # let slha = match Slha::parse(input) {
-/// #     Ok(slha) => slha,
-/// #     Err(err) => panic!("Failed to deserialize SLHA file: {}", err),
-/// # };
-/// #
-/// let sminputs: Block<i8, f64> = match slha.get_block("sminputs") {
-///     Some(block) => match block {
-///         Ok(sminputs) => sminputs,
-///         Err(err) => panic!("Failed to parse block 'smimputs':\n{}", err),
-///     },
-///     None => panic!("Missing block 'sminputs'."),
-/// };
-/// assert_eq!(sminputs.scale, None);
-/// assert_eq!(sminputs.map.len(), 3);
-/// assert_eq!(sminputs.map[&5], 4.25);
-/// ```
-///
-/// If only the value type is known but the key type is not, then it is still possible to use a
-/// `BlockStr` to extract the block and access the values using string keys:
-///
-/// ```rust
-/// # use slha::{Slha, Block, BlockStr, DecayTable};
-/// #
-/// # let input = "\
-/// # Block SMINPUTS   # Standard Model inputs
-/// #      3      0.1172  # alpha_s(MZ) SM MSbar
-/// #      5      4.25    # Mb(mb) SM MSbar
-/// #      6    174.3     # Mtop(pole)
-/// # DECAY 6 1.35
-/// #     1   2   5   24
-/// # ";
-/// #
-/// # let slha = match Slha::parse(input) {
-/// #     Ok(slha) => slha,
-/// #     Err(err) => panic!("Failed to deserialize SLHA file: {}", err),
-/// # };
-/// #
-/// let sminputs: BlockStr<f64> = match slha.get_block("sminputs") {
-///     Some(block) => match block {
-///         Ok(sminputs) => sminputs,
-///         Err(err) => panic!("Failed to parse block 'smimputs':\n{}", err),
-///     },
-///     None => panic!("Missing block 'sminputs'."),
-/// };
-/// assert_eq!(sminputs.scale, None);
-/// assert_eq!(sminputs.map.len(), 3);
-/// assert_eq!(sminputs.map[&vec!["5".to_string()]], 4.25);
-/// ```
-///
-/// If even the value type is not known, it is at least still possible to access the raw data lines
-/// of the block:
-///
-/// ```rust
-/// # use slha::{Slha, Block, BlockStr, DecayTable};
-/// #
-/// # let input = "\
-/// # Block SMINPUTS   # Standard Model inputs
-/// #      3      0.1172  # alpha_s(MZ) SM MSbar
-/// #      5      4.25    # Mb(mb) SM MSbar
-/// #      6    174.3     # Mtop(pole)
-/// # DECAY 6 1.35
-/// #     1   2   5   24
-/// # ";
-/// #
-/// # let slha = match Slha::parse(input) {
-/// #     Ok(slha) => slha,
-/// #     Err(err) => panic!("Failed to deserialize SLHA file: {}", err),
-/// # };
-/// #
-/// let blocks = slha.get_raw_blocks("sminputs");
-/// assert_eq!(blocks.len(), 1);
-/// let sminputs = &blocks[0];
-/// assert_eq!(sminputs.scale, None);
-/// assert_eq!(sminputs.lines.len(), 3);
-/// assert_eq!(sminputs.lines[1].data, "5      4.25    ");
-/// assert_eq!(sminputs.lines[1].comment, Some("# Mb(mb) SM MSbar"));
-/// ```
-///
-/// Access the decays using `get_decay`:
-///
-/// ```rust
-/// # use slha::{Slha, DecayTable};
-/// #
-/// # let input = "\
-/// # Block SMINPUTS   # Standard Model inputs
-/// #      3      0.1172  # alpha_s(MZ) SM MSbar
-/// #      5      4.25    # Mb(mb) SM MSbar
-/// #      6    174.3     # Mtop(pole)
-/// # DECAY 6 1.35
-/// #     1   2   5   24
-/// # ";
-/// #
-/// # let slha = match Slha::parse(input) {
-/// #     Ok(slha) => slha,
-/// #     Err(err) => panic!("Failed to deserialize SLHA file: {}", err),
-/// # };
-/// #
-/// let decay_table = match slha.get_decay(6) {
-///     Some(dec) => dec,
-///     None => panic!("Missing decay table for the top quark."),
-/// };
-/// assert_eq!(decay_table.width, 1.35);
-/// let decay = &decay_table.decays;
-/// assert_eq!(decay.len(), 1);
-/// assert_eq!(decay[0].branching_ratio, 1.);
-/// assert_eq!(decay[0].daughters, vec![5, 24]);
-/// ```
-#[derive(Clone, Debug, PartialEq)]
-pub struct Slha<'a> {
-    blocks: HashMap<String, Vec<RawBlock<'a>>>,
-    decays: HashMap<i64, DecayTable>,
-}
-impl<'a> Slha<'a> {
-    /// Create a new Slha object from the contents of an SLHA file.
+    /// Checks that all decays in the table can be written to an SLHA file.
     ///
-    /// The SLHA file passed to this function is parsed down into its basic building blocks, so
-    /// that decays and blocks can be easily accessed using the various getter functions.
-    /// Decays are parsed completely, for blocks only the raw data is stored and only parsed into
-    /// the desired form by the three `get_block` functions.
+    /// This is useful to validate decay tables that have been built or modified in code, since
+    /// the public fields of `Decay` allow to bypass the checks of `Decay::try_new`.
     ///
     /// # Errors
     ///
-    /// Some errors can already be caught at this stage, even though most checking is only done by
-    /// the accessor functions.
-    /// Errors reported by this function are all errors regarding decays, as well as errors from
-    /// malformed block headers.
+    /// The error of `Decay::try_new` is returned for the first invalid decay, i.e. a
+    /// `NonFiniteBranchingRatio` error if the branching ratio is infinite or not a number, a
+    /// `NoDaughters` error if there are no daughters and a `TooManyDaughters` error if there are
+    /// more than 255 daughters.
     ///
     /// # Examples
     ///
     /// ```rust
-    /// use slha::{Slha, Block, BlockStr, DecayTable};
-    ///
-    /// let input = "\
-    /// Block SMINPUTS   # Standard Model inputs
-    ///      3      0.1172  # alpha_s(MZ) SM MSbar
-    ///      5      4.25    # Mb(mb) SM MSbar
-    ///      6    174.3     # Mtop(pole)
-    /// DECAY 6 1.35
-    ///     1   2   5   24
-    /// ";
-    ///
-    /// let slha = match Slha::parse(input) {
-    ///     Ok(slha) => slha,
-    ///     Err(err) => panic!("Failed to deserialize SLHA file: {}", err),
-    /// };
+    /// use slha::{Decay, DecayTable};
     ///
-    /// let sminputs: Block<i8, f64> = match slha.get_block("sminputs") {
-    ///     Some(block) => match block {
-    ///         Ok(sminputs) => sminputs,
-    ///         Err(err) => panic!("Failed to parse block 'smimputs':\n{}", err),
-    ///     },
-    ///     None => panic!("Missing block 'sminputs'."),
-    /// };
-    /// assert_eq!(sminputs.scale, None);
-    /// assert_eq!(sminputs.map.len(), 3);
-    /// assert_eq!(sminputs.map[&5], 4.25);
+    /// let mut table = DecayTable::try_new(1.35, vec![Decay::try_new(1., vec![5, 24]).unwrap()])
+    ///     .unwrap();
+    /// assert!(table.check_nda().is_ok());
+    /// table.decays[0].daughters.clear();
+    /// assert!(table.check_nda().is_err());
     /// ```
-    pub fn parse(input: &'a str) -> Result<Slha<'a>> {
-        let mut slha = Slha {
-            blocks: HashMap::new(),
-            decays: HashMap::new(),
-        };
-        let mut lines = input.lines().peekable();
-        while let Some(segment) = internal::parse_segment(&mut lines) {
-            match segment? {
-                Segment::Block { name, block } => {
-                    let blocks = slha.blocks.entry(name).or_insert_with(|| Vec::new());
-                    blocks.push(block)
-                }
-                Segment::Decay {
-                    pdg_id,
-                    width,
-                    decays,
-                } => slha.insert_decay(pdg_id, width, decays)?,
-            }
+    pub fn check_nda(&self) -> Result<()> {
+        for decay in &self.decays {
+            decay.check()?;
         }
-        Ok(slha)
+        Ok(())
     }
 
-    /// Lookup a single block by name and parse it into the required rust type.
-    ///
-    /// If there is no block with the given name, None is returned. If there is more than one
-    /// block with the name, an error is returned inside a `Some`. Otherwise the body of the block
-    /// is converted into an object of type `B` and the result is returned.
-    /// If a block may appear more than once in an SLHA file, the methods `get_blocks` and
-    /// `get_blocks_unchecked` may be used to access all of them.
+    /// Returns the width of the particle as a `Width`.
     ///
-    /// # Errors
+    /// Widths in SLHA files are always given in GeV.
+    pub fn width_typed(&self) -> Width {
+        Width::from_gev(self.width)
+    }
+
+    /// Returns the total width of the particle in GeV.
     ///
-    /// It is an error if there is more than one block with name `name` in the file.
-    /// This is independently of the scale.
-    /// Additionally, errors encountered while parsing the raw body of the block into an object of
-    /// type `B` are returned.
+    /// This is the same as the `width` field and is only provided to make code computing e.g.
+    /// partial widths easier to read.
     ///
     /// # Examples
     ///
     /// ```rust
-    /// use slha::{Slha, Block, BlockStr, DecayTable};
+    /// use slha::Slha;
     ///
     /// let input = "\
-    /// Block SMINPUTS   # Standard Model inputs
-    ///      3      0.1172  # alpha_s(MZ) SM MSbar
-    ///      5      4.25    # Mb(mb) SM MSbar
-    ///      6    174.3     # Mtop(pole)
     /// DECAY 6 1.35
-    ///     1   2   5   24
+    ///     1.0   2   5   24
     /// ";
     ///
-    /// let slha = match Slha::parse(input) {
-    ///     Ok(slha) => slha,
-    ///     Err(err) => panic!("Failed to deserialize SLHA file: {}", err),
-    /// };
+    /// let slha = Slha::parse(input).unwrap();
+    /// assert_eq!(slha.get_decay(6).unwrap().total_width(), 1.35);
+    /// ```
+    pub fn total_width(&self) -> f64 {
+        self.width
+    }
+
+    /// Computes the width accounted for by the decay modes listed in the table.
     ///
-    /// let sminputs: Block<i8, f64> = match slha.get_block("sminputs") {
-    ///     Some(block) => match block {
-    ///         Ok(sminputs) => sminputs,
-    ///         Err(err) => panic!("Failed to parse block 'smimputs':\n{}", err),
-    ///     },
-    ///     None => panic!("Missing block 'sminputs'."),
+    /// This is the sum of the partial widths, i.e. the total width times the branching ratio, of
+    /// all decay modes.
+    /// It only agrees with `total_width` if the branching ratios add up to one, so it can be used
+    /// to check how much of the width is missing from a truncated decay table.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::Slha;
+    ///
+    /// let input = "\
+    /// DECAY 25 4.0e-03
+    ///     0.5     2   5   -5
+    ///     0.25    2   24  -24
+    /// ";
+    ///
+    /// let slha = Slha::parse(input).unwrap();
+    /// let higgs = slha.get_decay(25).unwrap();
+    /// assert_eq!(higgs.computed_width_from_brs(), 3.0e-03);
+    /// ```
+    pub fn computed_width_from_brs(&self) -> f64 {
+        self.decays
+            .iter()
+            .map(|decay| self.width * decay.branching_ratio)
+            .sum()
+    }
+
+    /// Returns the decay modes of the particle as a slice.
+    ///
+    /// This gives the same view as the public `decays` field, but as a method that is not
+    /// affected by changes to the internal representation of the decay table.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::Slha;
+    ///
+    /// let input = "\
+    /// DECAY 6 1.35
+    ///     0.9   2   5   24
+    ///     0.1   3   5   24   22
+    /// ";
+    ///
+    /// let slha = Slha::parse(input).unwrap();
+    /// let decays = slha.get_decay(6).unwrap().decays();
+    /// assert_eq!(decays.len(), 2);
+    /// assert_eq!(decays[0].daughters(), &[5, 24]);
+    /// assert_eq!(decays[1].daughters(), &[5, 24, 22]);
+    /// ```
+    pub fn decays(&self) -> &[Decay] {
+        &self.decays
+    }
+
+    /// Returns an iterator over the decay modes of the particle.
+    ///
+    /// This is the same as iterating over `&table`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::Slha;
+    ///
+    /// let input = "\
+    /// DECAY 6 1.35
+    ///     0.9   2   5   24
+    ///     0.1   3   5   24   22
+    /// ";
+    ///
+    /// let slha = Slha::parse(input).unwrap();
+    /// let table = slha.get_decay(6).unwrap();
+    /// let mut total = 0.;
+    /// for decay in table {
+    ///     total += decay.branching_ratio;
+    /// }
+    /// assert_eq!(total, 1.);
+    /// assert_eq!(table.iter().filter(|d| d.daughters.len() == 2).count(), 1);
+    /// ```
+    pub fn iter(&self) -> ::std::slice::Iter<'_, Decay> {
+        self.decays.iter()
+    }
+
+    /// Returns the decay modes with exactly `n` daughter particles.
+    ///
+    /// The decays are returned in the order in which they appear in the table.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::Slha;
+    ///
+    /// let input = "\
+    /// DECAY 6 1.35
+    ///     0.9   2   5   24
+    ///     0.1   3   5   24   22
+    /// ";
+    ///
+    /// let slha = Slha::parse(input).unwrap();
+    /// let top = slha.get_decay(6).unwrap();
+    /// assert_eq!(top.n_body(2).len(), 1);
+    /// assert_eq!(top.n_body(3)[0].daughters, vec![5, 24, 22]);
+    /// assert!(top.n_body(4).is_empty());
+    /// ```
+    pub fn n_body(&self, n: usize) -> Vec<&Decay> {
+        self.decays.iter().filter(|decay| decay.daughters.len() == n).collect()
+    }
+
+    /// Groups the decay modes by their number of daughter particles.
+    ///
+    /// Within each group the decays are in the order in which they appear in the table.
+    /// Only numbers of daughters for which there is at least one decay mode are included.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::Slha;
+    ///
+    /// let input = "\
+    /// DECAY 6 1.35
+    ///     0.9   2   5   24
+    ///     0.1   3   5   24   22
+    /// ";
+    ///
+    /// let slha = Slha::parse(input).unwrap();
+    /// let groups = slha.get_decay(6).unwrap().group_by_multiplicity();
+    /// assert_eq!(groups.keys().collect::<Vec<_>>(), vec![&2, &3]);
+    /// assert_eq!(groups[&2][0].branching_ratio, 0.9);
+    /// ```
+    pub fn group_by_multiplicity(&self) -> BTreeMap<usize, Vec<&Decay>> {
+        let mut groups = BTreeMap::new();
+        for decay in &self.decays {
+            groups.entry(decay.daughters.len()).or_insert_with(Vec::new).push(decay);
+        }
+        groups
+    }
+
+    /// Looks up the branching ratio of the decay into the given daughters.
+    ///
+    /// The daughters are compared regardless of their order.
+    /// If there is no such decay mode, `None` is returned.
+    /// If the table contains the same final state more than once, the branching ratio of the
+    /// first occurrence is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::Slha;
+    ///
+    /// let input = "\
+    /// DECAY 6 1.35
+    ///     0.9   2   5   24
+    ///     0.1   2   3   24
+    /// ";
+    ///
+    /// let slha = Slha::parse(input).unwrap();
+    /// let top = slha.get_decay(6).unwrap();
+    /// assert_eq!(top.branching_ratio_for(&[24, 3]), Some(0.1));
+    /// assert_eq!(top.branching_ratio_for(&[1, 24]), None);
+    /// ```
+    pub fn branching_ratio_for(&self, daughters: &[i64]) -> Option<f64> {
+        self.decays
+            .iter()
+            .find(|decay| same_daughters(&decay.daughters, daughters))
+            .map(|decay| decay.branching_ratio)
+    }
+
+    /// Adds the decays of another, partial decay table of the same particle.
+    ///
+    /// Both tables are treated as lists of partial decay modes, where the width of each table is
+    /// the sum of the partial widths of its decays.
+    /// The width of the merged table is therefore the sum of both widths, and the branching
+    /// ratios of both tables are rescaled by their share of the total width.
+    /// If both widths are zero, the branching ratios are kept as they are.
+    ///
+    /// The `policy` decides what happens if a decay channel, i.e. the same daughters in any order,
+    /// appears in both tables.
+    ///
+    /// # Errors
+    ///
+    /// If `policy` is `ChannelPolicy::Error` and a decay channel appears in both tables, a
+    /// `DuplicateChannel` error is returned.
+    /// In this case the table is not changed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{ChannelPolicy, Decay, DecayTable};
+    ///
+    /// let mut top = DecayTable::try_new(1.0, vec![Decay::try_new(1.0, vec![5, 24]).unwrap()])
+    ///     .unwrap();
+    /// let other = DecayTable::try_new(0.25, vec![
+    ///     Decay::try_new(0.8, vec![24, 5]).unwrap(),
+    ///     Decay::try_new(0.2, vec![3, 24]).unwrap(),
+    /// ]).unwrap();
+    ///
+    /// assert!(top.clone().merge(other.clone(), ChannelPolicy::Error).is_err());
+    /// top.merge(other, ChannelPolicy::Sum).unwrap();
+    /// assert_eq!(top.width, 1.25);
+    /// assert!((top.branching_ratio_for(&[5, 24]).unwrap() - 0.96).abs() < 1e-12);
+    /// assert!((top.branching_ratio_for(&[3, 24]).unwrap() - 0.04).abs() < 1e-12);
+    /// ```
+    pub fn merge(&mut self, other: DecayTable, policy: ChannelPolicy) -> Result<()> {
+        let width = self.width + other.width;
+        let (own_share, other_share) = if width == 0. {
+            (1., 1.)
+        } else {
+            (self.width / width, other.width / width)
+        };
+        let mut decays: Vec<Decay> = self.decays
+            .iter()
+            .map(|decay| Decay {
+                branching_ratio: decay.branching_ratio * own_share,
+                daughters: decay.daughters.clone(),
+            })
+            .collect();
+        for decay in other.decays {
+            let branching_ratio = decay.branching_ratio * other_share;
+            let existing = decays
+                .iter_mut()
+                .find(|existing| same_daughters(&existing.daughters, &decay.daughters));
+            match (existing, policy) {
+                (Some(_), ChannelPolicy::Error) => {
+                    bail!(ErrorKind::DuplicateChannel(decay.daughters))
+                }
+                (Some(existing), ChannelPolicy::Sum) => existing.branching_ratio += branching_ratio,
+                (None, _) => decays.push(Decay {
+                    branching_ratio,
+                    daughters: decay.daughters,
+                }),
+            }
+        }
+        self.width = width;
+        self.decays = decays;
+        Ok(())
+    }
+
+    /// Consumes the decay table and returns the decay modes of the particle.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::Slha;
+    ///
+    /// let input = "\
+    /// DECAY 6 1.35
+    ///     1   2   5   24
+    /// ";
+    ///
+    /// let slha = Slha::parse(input).unwrap();
+    /// let decays = slha.get_decay(6).unwrap().clone().into_decays();
+    /// assert_eq!(decays.len(), 1);
+    /// assert_eq!(decays[0].daughters, vec![5, 24]);
+    /// ```
+    pub fn into_decays(self) -> Vec<Decay> {
+        self.decays
+    }
+
+    /// Computes a fingerprint of the width and the decay modes of the particle.
+    ///
+    /// Since `f64` does not implement `Hash`, the width and branching ratios are hashed using
+    /// their bit patterns.
+    /// The decay modes are hashed in the order in which they appear in the table.
+    /// Like `Block::content_hash`, the hash is computed with the 64 bit FNV-1a algorithm, so it is
+    /// the same on every platform and can be stored.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::Slha;
+    ///
+    /// let input = "\
+    /// DECAY 6 1.35
+    ///     1   2   5   24
+    /// DECAY -6 1.35
+    ///     1   2   -5   -24
+    /// ";
+    ///
+    /// let slha = Slha::parse(input).unwrap();
+    /// let top = slha.get_decay(6).unwrap();
+    /// assert_eq!(top.content_hash(), top.clone().content_hash());
+    /// assert!(top.content_hash() != slha.get_decay(-6).unwrap().content_hash());
+    /// ```
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = FnvHasher::new();
+        hasher.write_u64(self.width.to_bits());
+        for decay in &self.decays {
+            hasher.write_u64(decay.branching_ratio.to_bits());
+            hasher.write_u64(decay.daughters.len() as u64);
+            for &daughter in &decay.daughters {
+                hasher.write_i64(daughter);
+            }
+        }
+        hasher.finish()
+    }
+
+    /// Format the decay table of the particle `pdg_id` as it appears in an SLHA file.
+    ///
+    /// The table starts with a `DECAY` line, containing the pdg id right-aligned in a column of
+    /// width `daughter_width` and the width in the same notation as the branching ratios,
+    /// right-aligned such that it ends in the same column as the branching ratios.
+    /// It is followed by one line for each decay mode as written by `Decay::format`.
+    /// Every line, including the last one, ends with a line break.
+    /// Reading the result with `Slha::parse` gives back the same table, as long as the width and
+    /// the branching ratios can be represented with `precision` digits.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, DecayTable, Decay, DecayFormat};
+    ///
+    /// let table = DecayTable {
+    ///     width: 1.35,
+    ///     decays: vec![Decay { branching_ratio: 1., daughters: vec![5, 24] }],
     /// };
-    /// assert_eq!(sminputs.scale, None);
-    /// assert_eq!(sminputs.map.len(), 3);
-    /// assert_eq!(sminputs.map[&5], 4.25);
+    /// let formatted = table.format(6, &DecayFormat::default());
+    /// assert_eq!(
+    ///     formatted,
+    ///     "DECAY         6     1.35000000E+00\n     1.00000000E+00    2           5        24\n"
+    /// );
+    /// let slha = Slha::parse(&formatted).unwrap();
+    /// assert_eq!(slha.get_decay(6), Some(&table));
     /// ```
-    pub fn get_block<B: SlhaBlock>(&self, name: &str) -> Option<Result<B>> {
-        let name = name.to_lowercase();
-        let blocks = match self.blocks.get(&name) {
-            Some(blocks) => blocks,
-            None => return None,
+    pub fn format(&self, pdg_id: i64, format: &DecayFormat) -> String {
+        let mut table = format!(
+            "DECAY{:>pdg_width$}{:>width_width$}\n",
+            pdg_id,
+            format_fortran_float(self.width, format.precision),
+            pdg_width = format.daughter_width,
+            width_width = format.indent + format.branching_ratio_width,
+        );
+        for decay in &self.decays {
+            table.push_str(&decay.format(format));
+            table.push('\n');
+        }
+        table
+    }
+}
+impl IntoIterator for DecayTable {
+    type Item = Decay;
+    type IntoIter = ::std::vec::IntoIter<Decay>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.decays.into_iter()
+    }
+}
+impl<'t> IntoIterator for &'t DecayTable {
+    type Item = &'t Decay;
+    type IntoIter = ::std::slice::Iter<'t, Decay>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.decays.iter()
+    }
+}
+
+/// The width of a particle with an explicit unit.
+///
+/// Internally the width is stored in GeV, which is the unit used in SLHA files.
+/// Using `Width` instead of a plain `f64` makes it explicit in which unit a width is given and
+/// prevents accidentally mixing widths in different units.
+///
+/// # Examples
+///
+/// ```rust
+/// use slha::{Slha, Width};
+///
+/// let input = "\
+/// DECAY 25 4.07e-03
+///     1   2   5   -5
+/// ";
+///
+/// let slha = Slha::parse(input).unwrap();
+/// let width = slha.get_decay(25).unwrap().width_typed();
+/// assert_eq!(width.in_gev(), 4.07e-03);
+/// assert!((width.in_mev() - 4.07).abs() < 1e-12);
+/// assert_eq!(Width::from_mev(1350.), Width::from_gev(1.35));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct Width(f64);
+impl Width {
+    /// Creates a width from a value in GeV.
+    pub fn from_gev(width: f64) -> Width {
+        Width(width)
+    }
+    /// Creates a width from a value in MeV.
+    pub fn from_mev(width: f64) -> Width {
+        Width(width / 1000.)
+    }
+    /// Returns the width in GeV.
+    pub fn in_gev(&self) -> f64 {
+        self.0
+    }
+    /// Returns the width in MeV.
+    pub fn in_mev(&self) -> f64 {
+        self.0 * 1000.
+    }
+}
+
+/// A single decay mode of a particle.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Decay {
+    /// The branching ratio of this decay mode.
+    pub branching_ratio: f64,
+    /// A vector of all daughter particles.
+    pub daughters: Vec<i64>,
+}
+impl Decay {
+    /// Creates a new decay, checking that it can be written to an SLHA file.
+    ///
+    /// # Errors
+    ///
+    /// A `NonFiniteBranchingRatio` error is returned if the branching ratio is infinite or not a
+    /// number.
+    /// A `NoDaughters` error is returned if `daughters` is empty, and a `TooManyDaughters` error
+    /// if there are more than 255 daughters.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::Decay;
+    ///
+    /// let decay = Decay::try_new(0.5, vec![5, 24]).unwrap();
+    /// assert_eq!(decay.branching_ratio, 0.5);
+    /// assert_eq!(decay.daughters, vec![5, 24]);
+    /// assert!(Decay::try_new(0.5, Vec::new()).is_err());
+    /// ```
+    pub fn try_new(branching_ratio: f64, daughters: Vec<i64>) -> Result<Decay> {
+        let decay = Decay {
+            branching_ratio,
+            daughters,
         };
-        if blocks.len() > 1 {
-            return Some(Err(ErrorKind::DuplicateBlock(name).into()));
+        decay.check()?;
+        Ok(decay)
+    }
+
+    /// Checks that the decay can be written to an SLHA file.
+    ///
+    /// This performs the same checks as `try_new`.
+    fn check(&self) -> Result<()> {
+        if !self.branching_ratio.is_finite() {
+            bail!(ErrorKind::NonFiniteBranchingRatio(self.branching_ratio));
+        }
+        if self.daughters.is_empty() {
+            bail!(ErrorKind::NoDaughters);
+        }
+        if self.daughters.len() > u8::MAX as usize {
+            bail!(ErrorKind::TooManyDaughters(
+                self.daughters.len(),
+                u8::MAX as usize,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns the pdg ids of the daughter particles as a slice.
+    ///
+    /// Like `DecayTable::decays`, this is a stable alternative to the public `daughters` field.
+    pub fn daughters(&self) -> &[i64] {
+        &self.daughters
+    }
+
+    /// Format the decay mode as a line of a decay table, using the column widths given in `format`.
+    ///
+    /// The branching ratio is written in Fortran style scientific notation, i.e. with a sign and at
+    /// least two digits in the exponent, followed by the number of daughters and the pdg ids of the
+    /// daughters, each right-aligned in their column.
+    /// The first daughter is preceded by `daughters_indent` additional spaces.
+    /// No trailing comment and no line break are added.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Decay, DecayFormat};
+    ///
+    /// let decay = Decay {
+    ///     branching_ratio: 0.25,
+    ///     daughters: vec![1000022, 11],
+    /// };
+    /// let format = DecayFormat {
+    ///     daughters_indent: 0,
+    ///     daughter_width: 9,
+    ///     ..DecayFormat::default()
+    /// };
+    /// assert_eq!(decay.format(&format), "     2.50000000E-01    2  1000022       11");
+    /// ```
+    pub fn format(&self, format: &DecayFormat) -> String {
+        let mut line = format!(
+            "{:indent$}{:>br_width$}{:>nda_width$}{:daughters_indent$}",
+            "",
+            format_fortran_float(self.branching_ratio, format.precision),
+            self.daughters.len(),
+            "",
+            indent = format.indent,
+            br_width = format.branching_ratio_width,
+            nda_width = format.nda_width,
+            daughters_indent = format.daughters_indent,
+        );
+        for daughter in &self.daughters {
+            line.push_str(&format!(
+                "{:>width$}",
+                daughter,
+                width = format.daughter_width
+            ));
+        }
+        line
+    }
+
+    /// Checks whether two decays describe the same decay channel.
+    ///
+    /// The daughters are compared regardless of their order, so `t -> b W+` and `t -> W+ b` are
+    /// the same channel.
+    /// The branching ratios must differ by at most `rel_tol` times the larger of the two.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::Decay;
+    ///
+    /// let decay = Decay {
+    ///     branching_ratio: 0.5,
+    ///     daughters: vec![5, 24],
+    /// };
+    /// let other = Decay {
+    ///     branching_ratio: 0.50000001,
+    ///     daughters: vec![24, 5],
+    /// };
+    /// assert!(decay.same_channel(&other, 1e-6));
+    /// assert!(!decay.same_channel(&other, 1e-9));
+    /// ```
+    pub fn same_channel(&self, other: &Decay, rel_tol: f64) -> bool {
+        let tol = rel_tol * self.branching_ratio.abs().max(other.branching_ratio.abs());
+        if (self.branching_ratio - other.branching_ratio).abs() > tol {
+            return false;
+        }
+        same_daughters(&self.daughters, &other.daughters)
+    }
+}
+
+/// Checks whether two lists of daughters contain the same particles, regardless of their order.
+fn same_daughters(a: &[i64], b: &[i64]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut a = a.to_vec();
+    let mut b = b.to_vec();
+    a.sort_unstable();
+    b.sort_unstable();
+    a == b
+}
+impl fmt::Display for Decay {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.format(&DecayFormat::default()))
+    }
+}
+
+/// The column layout used to write a `Decay` as a line of a decay table.
+///
+/// The default layout matches the one used by SDECAY and SUSY-HIT:
+/// three spaces of indentation, the branching ratio with eight digits after the decimal point in a
+/// 16 column field, the number of daughters in a 5 column field, two additional spaces and each
+/// daughter in a 10 column field, e.g. `     2.08271916E-02    2     1000001        -1`.
+/// When writing a whole table with `DecayTable::format`, the header uses the same layout:
+/// `DECAY`, the pdg id in a 10 column field and the width ending in the same column as the
+/// branching ratios, e.g. `DECAY   1000021     1.14378450E+00`.
+/// All numbers are written such that they are read back unchanged, provided that the width and
+/// the branching ratios have at most nine significant digits.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecayFormat {
+    /// The number of spaces at the beginning of the line.
+    pub indent: usize,
+    /// The width of the column containing the branching ratio.
+    pub branching_ratio_width: usize,
+    /// The number of digits after the decimal point of the branching ratio.
+    pub precision: usize,
+    /// The width of the column containing the number of daughters.
+    pub nda_width: usize,
+    /// The number of additional spaces in front of the first daughter.
+    pub daughters_indent: usize,
+    /// The width of the column of each daughter.
+    pub daughter_width: usize,
+}
+impl Default for DecayFormat {
+    fn default() -> DecayFormat {
+        DecayFormat {
+            indent: 3,
+            branching_ratio_width: 16,
+            precision: 8,
+            nda_width: 5,
+            daughters_indent: 2,
+            daughter_width: 10,
+        }
+    }
+}
+
+/// Format a float in scientific notation with a signed exponent of at least two digits.
+fn format_fortran_float(value: f64, precision: usize) -> String {
+    let formatted = format!("{:.*E}", precision, value);
+    let (mantissa, exponent) = match formatted.find('E') {
+        Some(pos) => (&formatted[..pos], &formatted[pos + 1..]),
+        None => return formatted,
+    };
+    let exponent: i32 = match exponent.parse() {
+        Ok(exp) => exp,
+        Err(_) => return formatted,
+    };
+    let sign = if exponent < 0 { '-' } else { '+' };
+    format!("{}E{}{:02}", mantissa, sign, exponent.abs())
+}
+
+/// A line read from an SLHA file.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Line<'input> {
+    /// The data contained in the line.
+    pub data: &'input str,
+    /// The comment at the end of the line, if present.
+    pub comment: Option<&'input str>,
+}
+impl<'input> Line<'input> {
+    /// Returns the data contained in the line without trailing whitespace.
+    ///
+    /// The `data` field contains everything up to the start of the comment, so it usually ends
+    /// with the whitespace separating the data from the comment.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::Slha;
+    ///
+    /// let input = "\
+    /// Block SMINPUTS   # Standard Model inputs
+    ///      5      4.25    # Mb(mb) SM MSbar
+    /// ";
+    ///
+    /// let slha = Slha::parse(input).unwrap();
+    /// let line = &slha.get_raw_blocks("sminputs")[0].lines[0];
+    /// assert_eq!(line.data, "5      4.25    ");
+    /// assert_eq!(line.data_trimmed(), "5      4.25");
+    /// ```
+    pub fn data_trimmed(&self) -> &'input str {
+        self.data.trim_end()
+    }
+
+    /// Compares two lines, ignoring trailing whitespace in their data.
+    ///
+    /// The comments still have to be equal.
+    pub fn eq_ignoring_trailing_ws(&self, other: &Line) -> bool {
+        self.data_trimmed() == other.data_trimmed() && self.comment == other.comment
+    }
+}
+
+/// An unparsed block from an SLHA file.
+///
+/// `RawBlock` contains all the non-comment, non-whitespace lines that belong to a block as well as
+/// the scale that the contents of the block are defined at, if any.
+///
+/// # Examples
+///
+/// ```rust
+/// use slha::Slha;
+///
+/// let input = "\
+/// Block SMINPUTS   # Standard Model inputs
+///      3      0.1172  # alpha_s(MZ) SM MSbar
+///      5      4.25    # Mb(mb) SM MSbar
+///      6    174.3     # Mtop(pole)
+/// DECAY 6 1.35
+///     1   2   5   24
+/// ";
+///
+/// let slha = match Slha::parse(input) {
+///     Ok(slha) => slha,
+///     Err(err) => panic!("Failed to deserialize SLHA file: {}", err),
+/// };
+///
+/// let blocks = slha.get_raw_blocks("sminputs");
+/// assert_eq!(blocks.len(), 1);
+/// let sminputs = &blocks[0];
+/// assert_eq!(sminputs.scale, None);
+/// assert_eq!(sminputs.lines.len(), 3);
+/// assert_eq!(sminputs.lines[1].data, "5      4.25    ");
+/// assert_eq!(sminputs.lines[1].comment, Some("# Mb(mb) SM MSbar"));
+/// ```
+#[derive(Clone, Debug)]
+pub struct RawBlock<'a> {
+    /// The scale contained in the block header.
+    pub scale: Option<f64>,
+    /// The data lines that make up the block, in the order they appear in the SLHA file.
+    pub lines: Vec<Line<'a>>,
+    /// How each of the `lines` is read, if the file was read with `line_continuation` or
+    /// `decimal_comma` set and one of the lines is affected.
+    /// Otherwise this is empty and all lines are read as they are.
+    logical: Vec<LogicalData>,
+}
+impl<'a> RawBlock<'a> {
+    /// Creates a `RawBlock` from its scale and data lines.
+    ///
+    /// The lines are read into a rust type as they are, i.e. `line_continuation` and
+    /// `decimal_comma` only apply to blocks read from an SLHA file.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Block, Line, RawBlock};
+    ///
+    /// let raw = RawBlock::new(None, vec![Line { data: "6    173.2", comment: None }]);
+    /// let mass: Block<i64, f64> = raw.to_block("mass").unwrap();
+    /// assert_eq!(mass.map[&6], 173.2);
+    /// ```
+    pub fn new(scale: Option<f64>, lines: Vec<Line<'a>>) -> RawBlock<'a> {
+        RawBlock {
+            scale,
+            lines,
+            logical: Vec::new(),
+        }
+    }
+
+    /// Returns the data lines of the block as they are read into a rust type.
+    ///
+    /// If the file was read with `line_continuation` set, lines ending with a backslash are
+    /// joined with the following lines, and the comment of a joined line is the first comment
+    /// found on any of its lines.
+    /// If it was read with `decimal_comma` set, decimal commas are replaced.
+    /// Otherwise these are the same as `lines`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, ParseOptions};
+    ///
+    /// let input = "\
+    /// Block MASS
+    ///      6    173,2 \\   # Mtop(pole)
+    ///           pole
+    ///      5    4,25
+    /// ";
+    ///
+    /// let options = ParseOptions {
+    ///     line_continuation: true,
+    ///     decimal_comma: true,
+    ///     ..ParseOptions::default()
+    /// };
+    /// let slha = Slha::parse_with_options(input, &options).unwrap();
+    /// let mass = slha.get_raw_block("mass").unwrap();
+    /// assert_eq!(mass.lines.len(), 3);
+    /// let lines = mass.logical_lines();
+    /// assert_eq!(lines.len(), 2);
+    /// assert_eq!(lines[0].data, "6    173.2  pole");
+    /// assert_eq!(lines[0].comment, Some("# Mtop(pole)"));
+    /// assert_eq!(lines[1].data, "5    4.25");
+    /// ```
+    pub fn logical_lines(&self) -> Vec<Line<'_>> {
+        self.numbered_logical_lines().into_iter().map(|(_, line)| line).collect()
+    }
+
+    /// Returns the data lines as they are read, each together with the number of the line in
+    /// `lines` it starts at.
+    fn numbered_logical_lines(&self) -> Vec<(usize, Line<'_>)> {
+        if self.logical.is_empty() {
+            return self.lines.iter().cloned().enumerate().map(|(i, line)| (i + 1, line)).collect();
+        }
+        let mut lines: Vec<(usize, Line)> = Vec::with_capacity(self.lines.len());
+        for (i, (line, logical)) in self.lines.iter().zip(&self.logical).enumerate() {
+            if *logical != LogicalData::Joined {
+                let data = logical.data(line);
+                lines.push((i + 1, Line { data, comment: line.comment }));
+                continue;
+            }
+            if let Some(&mut (_, ref mut joined)) = lines.last_mut() {
+                joined.comment = joined.comment.or(line.comment);
+            }
+        }
+        lines
+    }
+
+    /// Calls `parse` on the data lines as they are read, see `logical_lines`.
+    ///
+    /// Line numbers in the returned error count the lines in `lines`, i.e. a joined line is
+    /// reported at the line it starts at.
+    fn parse_logical<T>(&self, parse: impl FnOnce(&[Line]) -> Result<T>) -> Result<T> {
+        if self.logical.is_empty() {
+            return parse(&self.lines);
+        }
+        let (numbers, lines): (Vec<_>, Vec<_>) = self.numbered_logical_lines().into_iter().unzip();
+        parse(&lines).map_err(|err| renumber_lines(err, &numbers))
+    }
+
+    /// Convert a `RawBlock` into a rust object.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use slha::{Slha, Block};
+    ///
+    /// let input = "\
+    /// Block SMINPUTS   # Standard Model inputs
+    ///      3      0.1172  # alpha_s(MZ) SM MSbar
+    ///      5      4.25    # Mb(mb) SM MSbar
+    ///      6    174.3     # Mtop(pole)
+    /// DECAY 6 1.35
+    ///     1   2   5   24
+    /// ";
+    ///
+    /// let slha = match Slha::parse(input) {
+    ///     Ok(slha) => slha,
+    ///     Err(err) => panic!("Failed to read SLHA file: {}", err),
+    /// };
+    ///
+    /// let blocks = slha.get_raw_blocks("sminputs");
+    /// assert_eq!(blocks.len(), 1);
+    /// let sminputs_raw = &blocks[0];
+    ///
+    /// let sminputs: Block<u8, f64> = match sminputs_raw.to_block("sminputs") {
+    ///     Ok(block) => block,
+    ///     Err(err) => panic!("Failed to parse block 'sminputs': {}", err),
+    /// };
+    ///
+    /// assert_eq!(sminputs.scale, None);
+    /// assert_eq!(sminputs.map.len(), 3);
+    /// assert_eq!(sminputs.map[&5], 4.25);
+    /// ```
+    pub fn to_block<B>(&self, name: &str) -> Result<B>
+    where
+        B: SlhaBlock,
+    {
+        self.parse_logical(|lines| B::parse(lines, self.scale))
+            .chain_err(|| ErrorKind::InvalidBlock(name.to_string()))
+    }
+
+    /// Convert a `RawBlock` into a rust object, handling input left over at the end of a line
+    /// according to `policy`.
+    ///
+    /// With `TrailingPolicy::Error` this is the same as `to_block`.
+    /// Otherwise, every line is first read on its own, and if it can not be read because of an
+    /// `IncompleteParse` error, the left over input is removed from the line.
+    /// The block is then read once from the remaining lines.
+    /// With `TrailingPolicy::Capture` the removed input is returned, while with
+    /// `TrailingPolicy::Ignore` the returned vector is always empty.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, Block, TrailingInput, TrailingPolicy};
+    ///
+    /// let input = "\
+    /// Block MASS
+    ///      6    173.2    pole
+    ///      5    4.25
+    /// ";
+    ///
+    /// let slha = Slha::parse(input).unwrap();
+    /// let raw = &slha.get_raw_blocks("mass")[0];
+    /// assert!(raw.to_block::<Block<i64, f64>>("mass").is_err());
+    /// let (mass, trailing) = raw
+    ///     .to_block_with_policy::<Block<i64, f64>>("mass", TrailingPolicy::Capture)
+    ///     .unwrap();
+    /// assert_eq!(mass.map[&6], 173.2);
+    /// assert_eq!(trailing, vec![TrailingInput { line: 1, input: "pole".to_string() }]);
+    /// ```
+    pub fn to_block_with_policy<B>(
+        &self,
+        name: &str,
+        policy: TrailingPolicy,
+    ) -> Result<(B, Vec<TrailingInput>)>
+    where
+        B: SlhaBlock,
+    {
+        if policy == TrailingPolicy::Error {
+            return self.to_block(name).map(|block| (block, Vec::new()));
+        }
+        let (numbers, mut lines): (Vec<_>, Vec<_>) =
+            self.numbered_logical_lines().into_iter().unzip();
+        let mut trailing = Vec::new();
+        for (&number, line) in numbers.iter().zip(lines.iter_mut()) {
+            let err = match B::parse(slice::from_ref(line), self.scale) {
+                Ok(_) => continue,
+                Err(err) => err,
+            };
+            // The left over input is the end of the line, so only its length is needed.
+            let data = line.data.trim_end();
+            let end = match incomplete_parse(&err) {
+                Some(rest) if rest.trim_end().len() <= data.len() => {
+                    data.len() - rest.trim_end().len()
+                }
+                _ => continue,
+            };
+            if !data.is_char_boundary(end) {
+                continue;
+            }
+            let (data, rest) = data.split_at(end);
+            line.data = data;
+            if policy == TrailingPolicy::Capture {
+                trailing.push(TrailingInput {
+                    line: number,
+                    input: rest.trim().to_string(),
+                });
+            }
+        }
+        B::parse(&lines, self.scale)
+            .map(|block| (block, trailing))
+            .map_err(|err| renumber_lines(err, &numbers))
+            .chain_err(|| ErrorKind::InvalidBlock(name.to_string()))
+    }
+
+    /// Convert a `RawBlock` into a `BlockStr`.
+    ///
+    /// This is a shorthand for `to_block` for the common case where the keys are only needed as
+    /// strings.
+    /// Since the `RawBlock` does not know its own name, errors are not wrapped in an
+    /// `InvalidBlock` error.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::Slha;
+    ///
+    /// let input = "\
+    /// Block SMINPUTS   # Standard Model inputs
+    ///      3      0.1172  # alpha_s(MZ) SM MSbar
+    ///      5      4.25    # Mb(mb) SM MSbar
+    /// ";
+    ///
+    /// let slha = Slha::parse(input).unwrap();
+    /// let sminputs = slha.get_raw_blocks("sminputs")[0].to_block_str::<f64>().unwrap();
+    /// assert_eq!(sminputs.map.len(), 2);
+    /// assert_eq!(sminputs.map[&vec!["5".to_string()]], 4.25);
+    /// ```
+    pub fn to_block_str<V>(&self) -> Result<BlockStr<V>>
+    where
+        V: Parseable,
+    {
+        self.parse_logical(|lines| BlockStr::parse(lines, self.scale))
+    }
+
+    /// Removes all lines for which the predicate `f` returns `false`.
+    ///
+    /// The remaining lines keep their order and their comments.
+    /// A removed line is dropped together with its trailing comment.
+    /// Lines that have been joined with the line before them because of `line_continuation` are
+    /// kept or removed together with it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::Slha;
+    ///
+    /// let input = "\
+    /// Block SMINPUTS   # Standard Model inputs
+    ///      3      0.1172  # alpha_s(MZ) SM MSbar
+    ///      5      4.25    # Mb(mb) SM MSbar
+    ///      6    174.3     # Mtop(pole)
+    /// ";
+    ///
+    /// let slha = Slha::parse(input).unwrap();
+    /// let mut sminputs = slha.get_raw_blocks("sminputs")[0].clone();
+    /// sminputs.retain_lines(|line| !line.data.starts_with("5 "));
+    /// assert_eq!(sminputs.lines.len(), 2);
+    /// assert_eq!(sminputs.lines[1].comment, Some("# Mtop(pole)"));
+    /// ```
+    pub fn retain_lines(&mut self, mut f: impl FnMut(&Line) -> bool) {
+        let mut keep = true;
+        let lines = self.take_lines()
+            .into_iter()
+            .filter(|(line, logical)| {
+                if *logical != LogicalData::Joined {
+                    keep = f(line);
+                }
+                keep
+            })
+            .collect();
+        self.set_lines(lines);
+    }
+
+    /// Removes all lines from the block, each together with how it is read.
+    fn take_lines(&mut self) -> Vec<(Line<'a>, LogicalData)> {
+        let mut logical = mem::take(&mut self.logical);
+        logical.resize(self.lines.len(), LogicalData::Unchanged);
+        self.lines.drain(..).zip(logical).collect()
+    }
+
+    /// Replaces the lines of the block, where `lines` is returned by `take_lines`.
+    fn set_lines(&mut self, lines: Vec<(Line<'a>, LogicalData)>) {
+        let (lines, logical): (Vec<_>, Vec<_>) = lines.into_iter().unzip();
+        self.lines = lines;
+        if logical.iter().any(|logical| *logical != LogicalData::Unchanged) {
+            self.logical = logical;
+        }
+    }
+}
+
+/// Two blocks are equal if they have the same scale and their lines are read the same, see
+/// `logical_lines`.
+impl<'a, 'b> PartialEq<RawBlock<'b>> for RawBlock<'a> {
+    fn eq(&self, other: &RawBlock<'b>) -> bool {
+        self.scale == other.scale && self.logical_lines() == other.logical_lines()
+    }
+}
+
+/// Replaces the line number in an `InvalidBlockLine` or `DuplicateKey` error by the number at
+/// this position in `numbers`.
+fn renumber_lines(mut err: Error, numbers: &[usize]) -> Error {
+    let number = |n: usize| n.checked_sub(1).and_then(|i| numbers.get(i)).cloned().unwrap_or(n);
+    match err.0 {
+        ErrorKind::InvalidBlockLine(n) => err.0 = ErrorKind::InvalidBlockLine(number(n)),
+        ErrorKind::DuplicateKey(n) => err.0 = ErrorKind::DuplicateKey(number(n)),
+        _ => (),
+    }
+    err
+}
+
+/// Options controlling how an SLHA file is read by `Slha::parse_with_options`.
+///
+/// The default options follow the SLHA standard, i.e. `Slha::parse(input)` is the same as
+/// `Slha::parse_with_options(input, &ParseOptions::default())`.
+/// The non-default settings exist to read files written by tools that deviate from the standard.
+///
+/// # Examples
+///
+/// ```rust
+/// use slha::{Slha, ParseOptions};
+///
+/// let input = "\
+/// BLOCK MASS
+///    6    173.2
+///
+///    5    4.25
+/// ";
+///
+/// let options = ParseOptions {
+///     blank_terminates: true,
+///     ..ParseOptions::default()
+/// };
+/// let slha = Slha::parse_with_options(input, &options).unwrap();
+/// assert_eq!(slha.get_raw_blocks("mass")[0].lines.len(), 1);
+/// assert_eq!(slha.get_raw_blocks("")[0].lines.len(), 1);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ParseOptions {
+    /// End a block at the first blank line.
+    ///
+    /// According to the SLHA standard a block extends up to the next `BLOCK` or `DECAY` line, no
+    /// matter how many blank lines there are in between, which is also the default behaviour.
+    /// Some tools however use a blank line to terminate a block and consider any data lines
+    /// following it to be a new block without a name.
+    /// If this option is set, these lines are collected into a block with an empty name, which can
+    /// be accessed using e.g. `get_raw_blocks("")`.
+    pub blank_terminates: bool,
+    /// Match block names case sensitively.
+    ///
+    /// By default block names are case insensitive, i.e. `Block MASS` and `Block mass` refer to
+    /// the same block.
+    /// If this option is set, the names of the blocks are kept as they are and the names passed to
+    /// e.g. `get_block` have to match them exactly.
+    pub case_sensitive: bool,
+    /// Read the branching ratios of decays as percentages.
+    ///
+    /// Some tools write branching ratios in percent, i.e. `41.83` instead of `0.4183`.
+    /// If this option is set, all branching ratios are divided by 100 when they are read.
+    /// The widths of the decaying particles are not affected.
+    pub br_as_percent: bool,
+    /// Join data lines ending with a backslash with the following line.
+    ///
+    /// Some hand-edited files wrap long entries of a block across several lines by ending all but
+    /// the last line with a `\` (before the comment, if any).
+    /// If this option is set, such lines are joined with the backslashes replaced by a space when
+    /// the block is read.
+    /// The `lines` of a `RawBlock` are kept as they are found in the file, while the joined lines
+    /// are returned by `RawBlock::logical_lines` and are used whenever the block is read into a
+    /// rust type.
+    /// Errors still give the number of the line in the file, counting from the first data line
+    /// of the block, at which the joined line starts.
+    /// A line is only continued by the data line directly following it in the same block, so
+    /// the backslash is removed from a line that is followed by a comment, an empty line or the
+    /// end of the block.
+    pub line_continuation: bool,
+    /// Accept a comma as the decimal separator of floating point numbers.
+    ///
+    /// Some tools write numbers using the decimal separator of the current locale, e.g. `1,35`
+    /// instead of `1.35`.
+    /// If this option is set, a single comma in a word is replaced by a point, if the result can
+    /// be read as a floating point number.
+    /// Words that do not contain a comma, like integer keys, are not affected.
+    /// Like `line_continuation`, this is applied when the block is read and the converted lines
+    /// are returned by `RawBlock::logical_lines`.
+    pub decimal_comma: bool,
+    /// Decide what happens if a block appears more than once.
+    ///
+    /// By default all occurrences of a block are stored and duplicates are only reported when
+    /// the block is accessed, e.g. by `get_block`.
+    /// With `DuplicatePolicy::Error`, `parse_with_options` instead returns a `DuplicateBlock`
+    /// error as soon as a block name is repeated.
+    /// Note that this also rejects blocks that appear several times with different scales.
+    pub on_duplicate_block: DuplicatePolicy,
+    /// Accept `DECAY` headers without a width.
+    ///
+    /// Some tools store the widths of the particles in a separate `WIDTH` block keyed by pdg id
+    /// and omit them from the headers of the decay tables.
+    /// If this option is set, a missing width is read as `0`.
+    /// The widths can then be filled in from the `WIDTH` block using `Slha::resolve_widths`.
+    pub optional_width: bool,
+    /// Keep blocks that have been commented out.
+    ///
+    /// Blocks are sometimes disabled by commenting out their header, e.g. `# BLOCK MASS`, while
+    /// leaving the (indented) data lines as they are, so that the block can be enabled again later.
+    /// By default such a header is an ordinary comment and the data lines are added to the
+    /// preceding block.
+    /// If this option is set, a comment whose first word is `BLOCK`, followed by a valid block
+    /// header, starts a disabled block instead, which is not visible to `get_block` and friends,
+    /// but can be accessed using `Slha::get_disabled_blocks` or turned back into a normal block
+    /// using `Slha::enable_block`.
+    /// Such a header always ends the preceding block or decay table.
+    /// If it is followed by commented out data lines, e.g. `#   6   173.2`, the whole block has
+    /// been commented out and these lines with the `#` removed are the body of the disabled
+    /// block.
+    /// Data lines that are not commented out are never added to such a block, so they have to be
+    /// preceded by the header of another block.
+    /// Otherwise, the data lines following the header make up the body, as for an active block.
+    pub recognize_commented_blocks: bool,
+    /// Keep segments that are neither blocks nor decay tables.
+    ///
+    /// Some files contain additional top level segments, e.g. a free form `INFO` header.
+    /// By default these are rejected with an `UnknownSegment` error.
+    /// If this option is set, the lines of such a segment are stored instead and can be accessed
+    /// using `Slha::extra_segments`.
+    /// An unknown segment extends up to the next `BLOCK` or `DECAY` line.
+    pub keep_unknown_segments: bool,
+    /// Reject decays with more daughter particles than this.
+    ///
+    /// This guards against untrusted input declaring an absurd number of daughters.
+    /// If a decay mode declares more daughters than allowed, a `TooManyDaughters` error is
+    /// returned before any of the daughters are read.
+    /// By default, any number of daughters that fits into the `NDA` column, i.e. up to 255, is
+    /// accepted.
+    pub max_daughters: Option<u8>,
+    /// Read a lone number following the block name as the scale of the block.
+    ///
+    /// Some tools write the scale without the `Q=` marker, i.e. `BLOCK YE 464.6`.
+    /// By default this is rejected with a `MalformedBlockHeader` error, since it is not clear
+    /// whether the number is meant as the scale.
+    /// If this option is set, a block header containing exactly one word after the block name,
+    /// which can be read as a floating point number, gets this number as its scale.
+    pub bare_scale: bool,
+    /// How input left over at the end of a data line of a block is handled.
+    ///
+    /// Some files contain additional, undocumented columns that are not part of the type a block
+    /// is read into.
+    /// By default this is an `IncompleteParse` error.
+    /// The policy is used by `get_block`, `get_blocks` and the other accessors of the `Slha`
+    /// object that read a block into a rust type, while `RawBlock::to_block` and the derived
+    /// deserialization always return an error.
+    pub on_trailing: TrailingPolicy,
+    /// Skip a header row naming the columns at the start of a block.
+    ///
+    /// Some hand-edited files contain a line like `i   j   value` as the first line of a block,
+    /// without marking it as a comment.
+    /// By default this line is part of the block and makes reading the block fail.
+    /// If this option is set, the first data line of a block is dropped if its first word is
+    /// not a number.
+    /// Since any such line is dropped, this should not be used for blocks whose keys are not
+    /// numbers.
+    pub skip_block_header_row: bool,
+    /// Store blocks whose name is a known alias under the standard name.
+    ///
+    /// Different tools use different names for some blocks, e.g. FeynRules writes the effective
+    /// Higgs mixing angle to a block `FRALPHA` instead of `ALPHA`.
+    /// If this option is set, every block listed in `KNOWN_BLOCK_ALIASES` is stored under the
+    /// corresponding standard name, so that it can be read e.g. using `get_block("alpha")`.
+    /// If both names appear in a file, the block is found twice.
+    /// Blocks with other names can be renamed after reading using `Slha::rename_block`.
+    pub known_aliases: bool,
+    /// The largest difference between two scales at which they are considered to be equal.
+    ///
+    /// Different tools write the same scale with different precision, e.g. `464.649125` and
+    /// `464.6491250001`.
+    /// `Slha::get_blocks` reports blocks whose scales differ by at most this amount as a
+    /// `DuplicateBlockScale` error.
+    /// The default of `0` only treats exactly equal scales as duplicates.
+    pub scale_eps: f64,
+}
+
+/// Block names used by some tools instead of the name of a standard block.
+///
+/// The first name of each pair is the alias, the second one the standard name.
+/// The aliases are applied when reading a file with the `known_aliases` option.
+pub const KNOWN_BLOCK_ALIASES: &[(&str, &str)] = &[("fralpha", "alpha")];
+
+/// Decides how `Slha::parse_with_options` handles blocks that appear more than once.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    /// Store all occurrences of the block and leave the checks to the accessor functions.
+    #[default]
+    Allow,
+    /// Return an error when a block appears more than once.
+    Error,
+}
+
+/// Decides how the accessor functions of `Slha` handle input left over at the end of a line.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TrailingPolicy {
+    /// Return an `IncompleteParse` error.
+    #[default]
+    Error,
+    /// Drop the left over input.
+    Ignore,
+    /// Drop the left over input, but make it available via `Slha::get_block_with_trailing`.
+    Capture,
+}
+
+/// Input left over at the end of a line, as captured with `TrailingPolicy::Capture`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TrailingInput {
+    /// The number of the data line in the block, starting at one.
+    pub line: usize,
+    /// The left over input, without surrounding whitespace.
+    pub input: String,
+}
+
+/// Decides how `DecayTable::merge` handles decay channels that are present in both tables.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChannelPolicy {
+    /// Return an error.
+    Error,
+    /// Add up the (rescaled) branching ratios of both channels.
+    Sum,
+}
+
+/// Decides what happens when merging data that is present on both sides.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// Return an error on conflicting entries.
+    Error,
+    /// Keep the existing entry and discard the new one.
+    KeepFirst,
+    /// Replace the existing entry with the new one.
+    Override,
+}
+
+/// Statistics about an SLHA file collected by `Slha::parse_with_stats`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ParseStats {
+    /// The total number of lines in the input.
+    pub lines: usize,
+    /// The number of blocks found, counting every occurence of a repeated block.
+    pub blocks: usize,
+    /// The number of decay tables found.
+    pub decays: usize,
+    /// The number of lines that only contain a comment.
+    pub comments: usize,
+}
+
+/// Statistics about all occurrences of a block, as returned by `Slha::block_report`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlockReport {
+    /// The name of the block.
+    pub name: String,
+    /// How often the block appears in the file.
+    pub occurrences: usize,
+    /// The number of data lines in all occurrences of the block.
+    pub total_entries: usize,
+    /// The scale of every occurrence of the block, in the order they appear in the file.
+    pub scales: Vec<Option<f64>>,
+}
+
+/// A partially parsed SLHA file.
+///
+/// `Slha` objects are another way to parse SLHA files without using the `SlhaDeserialize` trait.
+/// The advantage that using an `SLHA` object has over deriving `SlhaDeserialize` for a struct is
+/// that the names of the blocks and their types do not have to be known at compile time.
+/// This is useful in a few special cases, for exmaple when dealing with the `UFO` format, where every
+/// external parameter comes with the name of an SLHA block and a string containing the keys where
+/// to find the value for this parameter in an SLHA file.
+/// Since the blocks and their types are only known when reading the model file, the
+/// `SlhaDeserialize` approach cannot be used.
+///
+/// The `Slha` object uses a two step parsing approach, because the types of Blocks aren't known
+/// when reading the file and the SLHA format isn't self describing so that the information can not
+/// be extracted from there.
+/// Therefore the `parse` function will extract all blocks (and decays) but only parses the headers
+/// of the blocks and stores their bodies.
+/// The various `get_block` functions then parse the body of the block into the desired type 'on
+/// demand' when accessing a block.
+///
+/// The `get_block` methods can parse a block into any type that implements the `SlhaBlock` trait,
+/// which include `Block` (if both key and value type are known) and `BlockStr` (if only the value
+/// type is known).
+/// If even the value type is unknown at compile time, the `get_raw_block` methods can be used which
+/// return the raw block body read from the file.
+///
+/// # Example
+///
+/// The following example shows all three ways how to access a single block, as well as how to
+/// access the decay table for a given particle.
+///
+/// First, extract all blocks and decays into an `Slha` object:
+///
+/// ```rust
+/// use slha::{Slha, Block, BlockStr, DecayTable};
+///
+/// let input = "\
+/// Block SMINPUTS   # Standard Model inputs
+///      3      0.1172  # alpha_s(MZ) SM MSbar
+///      5      4.25    # Mb(mb) SM MSbar
+///      6    174.3     # Mtop(pole)
+/// DECAY 6 1.35
+///     1   2   5   24
+/// ";
+///
+/// let slha = match Slha::parse(input) {
+///     Ok(slha) => slha,
+///     Err(err) => panic!("Failed to deserialize SLHA file: {}", err),
+/// };
+/// ```
+///
+/// If both the value type and the key type are known, blocks can be extracted into a `Block`
+/// structure:
+///
+/// ```rust
+/// # use slha::{Slha, Block, BlockStr, DecayTable};
+/// #
+/// # let input = "\
+/// # Block SMINPUTS   # Standard Model inputs
+/// #      3      0.1172  # alpha_s(MZ) SM MSbar
+/// #      5      4.25    # Mb(mb) SM MSbar
+/// #      6    174.3     # Mtop(pole)
+/// # DECAY 6 1.35
+/// #     1   2   5   24
+/// # ";
+/// #
+/// # let slha = match Slha::parse(input) {
+/// #     Ok(slha) => slha,
+/// #     Err(err) => panic!("Failed to deserialize SLHA file: {}", err),
+/// # };
+/// #
+/// let sminputs: Block<i8, f64> = match slha.get_block("sminputs") {
+///     Some(block) => match block {
+///         Ok(sminputs) => sminputs,
+///         Err(err) => panic!("Failed to parse block 'smimputs':\n{}", err),
+///     },
+///     None => panic!("Missing block 'sminputs'."),
+/// };
+/// assert_eq!(sminputs.scale, None);
+/// assert_eq!(sminputs.map.len(), 3);
+/// assert_eq!(sminputs.map[&5], 4.25);
+/// ```
+///
+/// If only the value type is known but the key type is not, then it is still possible to use a
+/// `BlockStr` to extract the block and access the values using string keys:
+///
+/// ```rust
+/// # use slha::{Slha, Block, BlockStr, DecayTable};
+/// #
+/// # let input = "\
+/// # Block SMINPUTS   # Standard Model inputs
+/// #      3      0.1172  # alpha_s(MZ) SM MSbar
+/// #      5      4.25    # Mb(mb) SM MSbar
+/// #      6    174.3     # Mtop(pole)
+/// # DECAY 6 1.35
+/// #     1   2   5   24
+/// # ";
+/// #
+/// # let slha = match Slha::parse(input) {
+/// #     Ok(slha) => slha,
+/// #     Err(err) => panic!("Failed to deserialize SLHA file: {}", err),
+/// # };
+/// #
+/// let sminputs: BlockStr<f64> = match slha.get_block("sminputs") {
+///     Some(block) => match block {
+///         Ok(sminputs) => sminputs,
+///         Err(err) => panic!("Failed to parse block 'smimputs':\n{}", err),
+///     },
+///     None => panic!("Missing block 'sminputs'."),
+/// };
+/// assert_eq!(sminputs.scale, None);
+/// assert_eq!(sminputs.map.len(), 3);
+/// assert_eq!(sminputs.map[&vec!["5".to_string()]], 4.25);
+/// ```
+///
+/// If even the value type is not known, it is at least still possible to access the raw data lines
+/// of the block:
+///
+/// ```rust
+/// # use slha::{Slha, Block, BlockStr, DecayTable};
+/// #
+/// # let input = "\
+/// # Block SMINPUTS   # Standard Model inputs
+/// #      3      0.1172  # alpha_s(MZ) SM MSbar
+/// #      5      4.25    # Mb(mb) SM MSbar
+/// #      6    174.3     # Mtop(pole)
+/// # DECAY 6 1.35
+/// #     1   2   5   24
+/// # ";
+/// #
+/// # let slha = match Slha::parse(input) {
+/// #     Ok(slha) => slha,
+/// #     Err(err) => panic!("Failed to deserialize SLHA file: {}", err),
+/// # };
+/// #
+/// let blocks = slha.get_raw_blocks("sminputs");
+/// assert_eq!(blocks.len(), 1);
+/// let sminputs = &blocks[0];
+/// assert_eq!(sminputs.scale, None);
+/// assert_eq!(sminputs.lines.len(), 3);
+/// assert_eq!(sminputs.lines[1].data, "5      4.25    ");
+/// assert_eq!(sminputs.lines[1].comment, Some("# Mb(mb) SM MSbar"));
+/// ```
+///
+/// Access the decays using `get_decay`:
+///
+/// ```rust
+/// # use slha::{Slha, DecayTable};
+/// #
+/// # let input = "\
+/// # Block SMINPUTS   # Standard Model inputs
+/// #      3      0.1172  # alpha_s(MZ) SM MSbar
+/// #      5      4.25    # Mb(mb) SM MSbar
+/// #      6    174.3     # Mtop(pole)
+/// # DECAY 6 1.35
+/// #     1   2   5   24
+/// # ";
+/// #
+/// # let slha = match Slha::parse(input) {
+/// #     Ok(slha) => slha,
+/// #     Err(err) => panic!("Failed to deserialize SLHA file: {}", err),
+/// # };
+/// #
+/// let decay_table = match slha.get_decay(6) {
+///     Some(dec) => dec,
+///     None => panic!("Missing decay table for the top quark."),
+/// };
+/// assert_eq!(decay_table.width, 1.35);
+/// let decay = &decay_table.decays;
+/// assert_eq!(decay.len(), 1);
+/// assert_eq!(decay[0].branching_ratio, 1.);
+/// assert_eq!(decay[0].daughters, vec![5, 24]);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Slha<'a> {
+    blocks: HashMap<String, Vec<RawBlock<'a>>>,
+    // The name and the index of the occurrence of every block in the order of the file.
+    block_order: Vec<(String, usize)>,
+    decays: HashMap<i64, DecayTable>,
+    lazy_decays: HashMap<i64, LazyDecayTable<'a>>,
+    disabled_blocks: HashMap<String, Vec<RawBlock<'a>>>,
+    extra_segments: Vec<(String, Vec<Line<'a>>)>,
+    // The options the file was read with, which are also needed to read the blocks later on.
+    options: ParseOptions,
+}
+impl<'a> Slha<'a> {
+    /// Create a new Slha object from the contents of an SLHA file.
+    ///
+    /// The SLHA file passed to this function is parsed down into its basic building blocks, so
+    /// that decays and blocks can be easily accessed using the various getter functions.
+    /// Decays are parsed completely, for blocks only the raw data is stored and only parsed into
+    /// the desired form by the three `get_block` functions.
+    ///
+    /// A block extends up to the next `BLOCK` or `DECAY` line, blank lines and comments in its body
+    /// are skipped.
+    /// Use `parse_with_options` to read files that deviate from this.
+    ///
+    /// # Errors
+    ///
+    /// Some errors can already be caught at this stage, even though most checking is only done by
+    /// the accessor functions.
+    /// Errors reported by this function are all errors regarding decays, as well as errors from
+    /// malformed block headers.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, Block, BlockStr, DecayTable};
+    ///
+    /// let input = "\
+    /// Block SMINPUTS   # Standard Model inputs
+    ///      3      0.1172  # alpha_s(MZ) SM MSbar
+    ///      5      4.25    # Mb(mb) SM MSbar
+    ///      6    174.3     # Mtop(pole)
+    /// DECAY 6 1.35
+    ///     1   2   5   24
+    /// ";
+    ///
+    /// let slha = match Slha::parse(input) {
+    ///     Ok(slha) => slha,
+    ///     Err(err) => panic!("Failed to deserialize SLHA file: {}", err),
+    /// };
+    ///
+    /// let sminputs: Block<i8, f64> = match slha.get_block("sminputs") {
+    ///     Some(block) => match block {
+    ///         Ok(sminputs) => sminputs,
+    ///         Err(err) => panic!("Failed to parse block 'smimputs':\n{}", err),
+    ///     },
+    ///     None => panic!("Missing block 'sminputs'."),
+    /// };
+    /// assert_eq!(sminputs.scale, None);
+    /// assert_eq!(sminputs.map.len(), 3);
+    /// assert_eq!(sminputs.map[&5], 4.25);
+    /// ```
+    pub fn parse(input: &'a str) -> Result<Slha<'a>> {
+        Slha::parse_with_options(input, &ParseOptions::default())
+    }
+
+    /// Create a new Slha object from the contents of an SLHA file using non-default options.
+    ///
+    /// This works like `parse`, except that the behaviour of the parser can be adjusted to read
+    /// files that do not strictly follow the SLHA standard.
+    /// See `ParseOptions` for the available settings.
+    ///
+    /// # Errors
+    ///
+    /// The same errors as for `parse` are returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, ParseOptions};
+    ///
+    /// let input = "\
+    /// BLOCK MASS
+    ///    6    173.2
+    ///
+    ///    5    4.25
+    /// ";
+    ///
+    /// let slha = Slha::parse_with_options(input, &ParseOptions::default()).unwrap();
+    /// assert_eq!(slha.get_raw_blocks("mass")[0].lines.len(), 2);
+    /// ```
+    pub fn parse_with_options(input: &'a str, options: &ParseOptions) -> Result<Slha<'a>> {
+        let mut slha = Slha::empty(options);
+        let mut lines = input.lines().peekable();
+        while let Some(segment) = internal::parse_segment_with_options(&mut lines, options) {
+            slha.insert_segment(segment?, options)?;
+        }
+        Ok(slha)
+    }
+
+    /// Create a new Slha object containing only the blocks with the given names.
+    ///
+    /// This works like `parse`, except that all blocks not listed in `names` are skipped while
+    /// reading the input, without storing their lines.
+    /// Decay tables are kept if `keep_decays` is set and skipped otherwise, so that a block
+    /// called e.g. `DECAYS` can be selected like any other block.
+    /// This saves memory if only a few blocks are needed from a large file.
+    /// The names are case insensitive.
+    ///
+    /// # Errors
+    ///
+    /// The same errors as for `parse` are returned for the blocks and decay tables that are kept.
+    /// Skipped segments are not checked, except that they must start with `BLOCK` or `DECAY`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, Block};
+    ///
+    /// let input = "\
+    /// BLOCK SMINPUTS
+    ///    3    0.1172
+    /// BLOCK MASS
+    ///    6    173.2
+    /// DECAY 6 1.35
+    ///    1.0    2    5    24
+    /// ";
+    ///
+    /// let slha = Slha::parse_selective(input, &["mass"], false).unwrap();
+    /// let mass: Block<i64, f64> = slha.get_block("mass").unwrap().unwrap();
+    /// assert_eq!(mass.map[&6], 173.2);
+    /// assert!(slha.get_raw_block("sminputs").is_none());
+    /// assert!(slha.get_decay(6).is_none());
+    ///
+    /// let slha = Slha::parse_selective(input, &["mass"], true).unwrap();
+    /// assert_eq!(slha.get_decay(6).unwrap().width, 1.35);
+    /// ```
+    pub fn parse_selective(
+        input: &'a str,
+        names: &[&str],
+        keep_decays: bool,
+    ) -> Result<Slha<'a>> {
+        Slha::parse_selective_with_options(input, names, keep_decays, &ParseOptions::default())
+    }
+
+    /// Create a new Slha object containing only the blocks with the given names, using
+    /// non-default options.
+    ///
+    /// This works like `parse_selective`, except that the segments that are kept are read with
+    /// `parse_with_options`.
+    /// The names are only case sensitive if `case_sensitive` is set.
+    /// If `recognize_commented_blocks` is set, disabled blocks are selected by their names in the
+    /// same way as active blocks.
+    ///
+    /// # Errors
+    ///
+    /// The same errors as for `parse_with_options` are returned for the blocks and decay tables
+    /// that are kept.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, ParseOptions};
+    ///
+    /// let input = "\
+    /// BLOCK Mass
+    ///    6    173.2
+    /// BLOCK MASS
+    ///    5    4.25
+    /// ";
+    ///
+    /// let options = ParseOptions { case_sensitive: true, ..ParseOptions::default() };
+    /// let slha = Slha::parse_selective_with_options(input, &["Mass"], false, &options).unwrap();
+    /// assert!(slha.get_raw_block("Mass").is_some());
+    /// assert!(slha.get_raw_block("MASS").is_none());
+    /// ```
+    pub fn parse_selective_with_options(
+        input: &'a str,
+        names: &[&str],
+        keep_decays: bool,
+        options: &ParseOptions,
+    ) -> Result<Slha<'a>> {
+        let mut slha = Slha::empty(options);
+        let names: Vec<_> = names.iter().map(|name| slha.block_name(name)).collect();
+        let mut lines = input.lines().peekable();
+        loop {
+            internal::skip_empty_lines_with_options(&mut lines, options);
+            let skip = match lines.peek() {
+                Some(line) => {
+                    match internal::segment_header(line) {
+                        Some((kw, _)) if kw.eq_ignore_ascii_case("decay") => !keep_decays,
+                        Some((kw, name)) if kw.eq_ignore_ascii_case("block") => {
+                            !names.contains(&slha.block_name(name))
+                        }
+                        _ => false,
+                    }
+                }
+                None => break,
+            };
+            if skip {
+                lines.next();
+                internal::skip_segment_body_with_options(&mut lines, options);
+                continue;
+            }
+            match internal::parse_segment_with_options(&mut lines, options) {
+                Some(segment) => match segment? {
+                    Segment::DisabledBlock { ref name, .. } if !names.contains(name) => (),
+                    segment => slha.insert_segment(segment, options)?,
+                },
+                None => unreachable!("BUG: the input cannot be empty after peeking a line."),
+            }
+        }
+        Ok(slha)
+    }
+
+    /// Create a new Slha object from the contents of an SLHA file, skipping invalid segments.
+    ///
+    /// This works like `parse`, except that a block or decay table that could not be read does
+    /// not abort parsing.
+    /// Instead, the segment is skipped up to the next line that is neither indented, empty nor a
+    /// comment, and the error is collected together with the number of the line, starting at one,
+    /// on which it occurs.
+    /// This is the data line that could not be read if the error is caused by an
+    /// `InvalidBlockLine` or `InvalidDecayLine` error, and the first line of the segment
+    /// otherwise.
+    /// The errors are returned in the order in which they occur in the input, alongside the
+    /// segments that could be read.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, Block};
+    ///
+    /// let input = "\
+    /// BLOCK MASS
+    ///    6    173.2
+    /// DECAY 6 foo
+    ///    1.0    2    5    24
+    /// BLOCK SMINPUTS
+    ///    3    0.1172
+    /// ";
+    ///
+    /// let (slha, errors) = Slha::parse_lenient(input);
+    /// assert_eq!(errors.len(), 1);
+    /// assert_eq!(errors[0].1, 3);
+    /// assert!(slha.get_decay(6).is_none());
+    /// let sminputs: Block<i8, f64> = slha.get_block("sminputs").unwrap().unwrap();
+    /// assert_eq!(sminputs.map[&3], 0.1172);
+    /// ```
+    pub fn parse_lenient(input: &'a str) -> (Slha<'a>, Vec<(Error, usize)>) {
+        Slha::parse_lenient_with_options(input, &ParseOptions::default())
+    }
+
+    /// Create a new Slha object from the contents of an SLHA file using non-default options,
+    /// skipping invalid segments.
+    ///
+    /// This works like `parse_lenient`, except that the segments are read like by
+    /// `parse_with_options`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, ParseOptions};
+    ///
+    /// let input = "\
+    /// DECAY 6 1,35
+    ///    1,0    2    5    24
+    /// DECAY 25 4,07e-03
+    ///    0,5    2    5    -5
+    ///    0,5    2    x    -4
+    /// ";
+    ///
+    /// let options = ParseOptions { decimal_comma: true, ..ParseOptions::default() };
+    /// let (slha, errors) = Slha::parse_lenient_with_options(input, &options);
+    /// assert_eq!(errors.len(), 1);
+    /// assert_eq!(errors[0].1, 5);
+    /// assert_eq!(slha.get_decay(6).unwrap().width, 1.35);
+    /// ```
+    pub fn parse_lenient_with_options(
+        input: &'a str,
+        options: &ParseOptions,
+    ) -> (Slha<'a>, Vec<(Error, usize)>) {
+        let mut slha = Slha::empty(options);
+        let mut errors = Vec::new();
+        // The line numbers are computed from the position of the lines in the input, counting
+        // the newlines since the start of the previous segment.
+        let mut offset = 0;
+        let mut line_number = 1;
+        let mut lines = input.lines().peekable();
+        loop {
+            internal::skip_empty_lines_with_options(&mut lines, options);
+            let start = match lines.peek() {
+                Some(line) => line.as_ptr() as usize - input.as_ptr() as usize,
+                None => break,
+            };
+            line_number += input[offset..start].matches('\n').count();
+            offset = start;
+            let result = match internal::parse_segment_with_options(&mut lines, options) {
+                Some(segment) => segment.and_then(|segment| slha.insert_segment(segment, options)),
+                None => unreachable!("BUG: the input cannot be empty after peeking a line."),
+            };
+            if let Err(err) = result {
+                let line = failed_data_line(&err)
+                    .and_then(|n| data_line_offset(&input[start..], n))
+                    .map_or(line_number, |data_line| line_number + data_line);
+                errors.push((err, line));
+                internal::skip_segment_body_with_options(&mut lines, options);
+            }
+        }
+        (slha, errors)
+    }
+
+    /// Create a new Slha object from the bytes of an SLHA file that may not be valid UTF-8.
+    ///
+    /// Files written by legacy tools sometimes contain bytes in an 8-bit encoding like
+    /// Windows-1252 or Latin-1, e.g. in comments.
+    /// Such files can not be read with `parse`, since they can not be converted to a `str`.
+    /// This function replaces all invalid byte sequences by `U+FFFD REPLACEMENT CHARACTER` first,
+    /// so that they only affect the text they appear in.
+    ///
+    /// Since an `Slha` object borrows from its input, the converted input is returned in an
+    /// `SlhaOwned`, which gives access to the parsed file via its `slha` method.
+    ///
+    /// # Errors
+    ///
+    /// The same errors as for `parse` are returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, Block};
+    ///
+    /// let input = b"\
+    /// BLOCK MASS   # M\xe4ssen
+    ///    6    173.2
+    /// ";
+    ///
+    /// let owned = Slha::from_bytes_lossy(input).unwrap();
+    /// let slha = owned.slha();
+    /// let mass: Block<i64, f64> = slha.get_block("mass").unwrap().unwrap();
+    /// assert_eq!(mass.map[&6], 173.2);
+    /// ```
+    pub fn from_bytes_lossy(input: &[u8]) -> Result<SlhaOwned> {
+        Slha::from_bytes_lossy_with_options(input, &ParseOptions::default())
+    }
+
+    /// Create a new Slha object from the bytes of an SLHA file that may not be valid UTF-8, using
+    /// the given options.
+    ///
+    /// This works like `from_bytes_lossy`, except that the converted input is read with
+    /// `parse_with_options`.
+    ///
+    /// # Errors
+    ///
+    /// The same errors as for `parse_with_options` are returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, ParseOptions};
+    ///
+    /// let input = b"BLOCK Mass   # M\xe4ssen\n   6    173.2\n";
+    ///
+    /// let options = ParseOptions { case_sensitive: true, ..ParseOptions::default() };
+    /// let owned = Slha::from_bytes_lossy_with_options(input, &options).unwrap();
+    /// let slha = owned.slha();
+    /// assert!(slha.get_raw_block("Mass").is_some());
+    /// assert!(slha.get_raw_block("MASS").is_none());
+    /// ```
+    pub fn from_bytes_lossy_with_options(
+        input: &[u8],
+        options: &ParseOptions,
+    ) -> Result<SlhaOwned> {
+        SlhaOwned::new(String::from_utf8_lossy(input).into_owned(), options)
+    }
+
+    /// Create a new Slha object without parsing the bodies of the decay tables.
+    ///
+    /// This works like `parse`, except that only the headers of the decay tables are read
+    /// immediately.
+    /// The decays are parsed when the decay table is first accessed, e.g. by `get_decay`.
+    /// This saves a lot of work for files with many decay tables if only the blocks or a few of
+    /// the decay tables are needed.
+    ///
+    /// Since the decays are only parsed on access, malformed decay lines are not reported by this
+    /// function.
+    /// Instead, `get_decay` and `get_decay_abs` treat a decay table that can not be parsed as
+    /// missing, while `try_get_decay` and `to_typed` return the error.
+    /// The decay tables are parsed using the same `options` as the rest of the file.
+    ///
+    /// # Errors
+    ///
+    /// The same errors as for `parse_with_options` are returned, except for errors in the bodies
+    /// of decay tables.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, ParseOptions};
+    ///
+    /// let input = "\
+    /// BLOCK MASS
+    ///    6    173.2
+    /// DECAY 6 1.35
+    ///     1   2   5   24
+    /// DECAY 25 4.07e-03
+    ///     1   2   5   foo
+    /// ";
+    ///
+    /// assert!(Slha::parse(input).is_err());
+    /// let slha = Slha::parse_blocks_only(input, &ParseOptions::default()).unwrap();
+    /// assert_eq!(slha.get_decay(6).unwrap().decays[0].daughters, vec![5, 24]);
+    /// assert!(slha.get_decay(25).is_none());
+    /// assert!(slha.try_get_decay(25).unwrap().is_err());
+    /// ```
+    pub fn parse_blocks_only(input: &'a str, options: &ParseOptions) -> Result<Slha<'a>> {
+        let mut slha = Slha::empty(options);
+        let mut lines = input.lines().peekable();
+        while let Some(segment) = internal::parse_segment_lazy(&mut lines, options) {
+            match segment? {
+                LazySegment::Segment(segment) => slha.insert_segment(segment, options)?,
+                LazySegment::Decay {
+                    pdg_id,
+                    width,
+                    lines,
+                } => {
+                    if slha.lazy_decays.contains_key(&pdg_id) {
+                        bail!(ErrorKind::DuplicateDecay(pdg_id));
+                    }
+                    let table = LazyDecayTable {
+                        width,
+                        lines,
+                        table: OnceLock::new(),
+                    };
+                    slha.lazy_decays.insert(pdg_id, table);
+                }
+            }
+        }
+        Ok(slha)
+    }
+
+    /// Create a new Slha object and collect some statistics about the input.
+    ///
+    /// This works exactly like `parse`, but additionally returns a `ParseStats` object with the
+    /// number of lines, blocks, decay tables and comment lines found in the input.
+    /// This can be used e.g. as a sanity check when importing large files.
+    ///
+    /// The statistics are collected while reading the segments, so only the lines that are
+    /// actually treated as comments are counted as such.
+    ///
+    /// # Errors
+    ///
+    /// The same errors as for `parse` are returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::Slha;
+    ///
+    /// let input = "\
+    /// BLOCK MASS  # Masses
+    ///    6    173.2
+    ///    #Top quark decays
+    /// DECAY 6 1.35
+    ///    1   2   5   24
+    /// ";
+    ///
+    /// let (slha, stats) = Slha::parse_with_stats(input).unwrap();
+    /// assert_eq!(stats.lines, 5);
+    /// assert_eq!(stats.blocks, 1);
+    /// assert_eq!(stats.decays, 1);
+    /// assert_eq!(stats.comments, 1);
+    /// assert!(slha.get_decay(6).is_some());
+    /// ```
+    pub fn parse_with_stats(input: &'a str) -> Result<(Slha<'a>, ParseStats)> {
+        Slha::parse_with_stats_with_options(input, &ParseOptions::default())
+    }
+
+    /// Create a new Slha object using non-default options and collect some statistics about the
+    /// input.
+    ///
+    /// This works like `parse_with_stats`, except that the input is read like by
+    /// `parse_with_options`.
+    /// If `recognize_commented_blocks` is set, the headers and the commented out data lines of
+    /// disabled blocks are not counted as comments, and disabled blocks are not counted as blocks.
+    ///
+    /// # Errors
+    ///
+    /// The same errors as for `parse_with_options` are returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, ParseOptions};
+    ///
+    /// let input = "\
+    /// BLOCK MASS
+    ///    6    173.2
+    /// ## BLOCK SMINPUTS
+    /// ##   3    0.1172
+    /// ## Disabled for now
+    /// ";
+    ///
+    /// let options = ParseOptions { recognize_commented_blocks: true, ..ParseOptions::default() };
+    /// let (_, stats) = Slha::parse_with_stats_with_options(input, &options).unwrap();
+    /// assert_eq!(stats.blocks, 1);
+    /// assert_eq!(stats.comments, 1);
+    /// ```
+    pub fn parse_with_stats_with_options(
+        input: &'a str,
+        options: &ParseOptions,
+    ) -> Result<(Slha<'a>, ParseStats)> {
+        let mut slha = Slha::empty(options);
+        let mut stats = ParseStats {
+            lines: input.lines().count(),
+            ..ParseStats::default()
+        };
+        let mut lines = input.lines().peekable();
+        let mut begin = 0;
+        loop {
+            internal::skip_empty_lines_with_options(&mut lines, options);
+            let header = line_offset(input, &mut lines);
+            let segment = match internal::parse_segment_with_options(&mut lines, options) {
+                Some(segment) => Some(segment?),
+                None => None,
+            };
+            let end = line_offset(input, &mut lines);
+            stats.comments += comment_lines(input, begin, end, header, segment.as_ref());
+            begin = end;
+            match segment {
+                Some(segment) => {
+                    match segment {
+                        Segment::Block { .. } => stats.blocks += 1,
+                        Segment::Decay { .. } => stats.decays += 1,
+                        _ => (),
+                    }
+                    slha.insert_segment(segment, options)?;
+                }
+                None => break,
+            }
+        }
+        Ok((slha, stats))
+    }
+
+    /// Read several concatenated SLHA documents.
+    ///
+    /// Outputs of parameter scans often contain the SLHA files for many parameter points in a
+    /// single file, separated by a marker line like `ENDSLHA` or `# === point N ===`.
+    /// This function splits the input at every line that starts with `separator` (ignoring
+    /// leading whitespace) and parses each of the pieces into its own `Slha` object.
+    /// The separator lines themselves are not part of any document and pieces that contain
+    /// nothing but whitespace, e.g. before a leading separator, are skipped.
+    ///
+    /// The returned documents are in the same order as they appear in the input.
+    ///
+    /// # Errors
+    ///
+    /// If one of the documents could not be parsed, the error is returned wrapped in an
+    /// `InvalidDocument` error giving the position of the document in the input, starting at one.
+    /// The skipped pieces are counted as well, so the position is one more than the number of
+    /// separator lines before the document.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, Block};
+    ///
+    /// let input = "\
+    /// BLOCK MASS
+    ///    6    173.2
+    /// ENDSLHA
+    /// BLOCK MASS
+    ///    6    175.0
+    /// ENDSLHA
+    /// ";
+    ///
+    /// let points = Slha::parse_multi(input, "ENDSLHA").unwrap();
+    /// assert_eq!(points.len(), 2);
+    /// let mass: Block<i64, f64> = points[1].get_block("mass").unwrap().unwrap();
+    /// assert_eq!(mass.map[&6], 175.0);
+    /// ```
+    pub fn parse_multi(input: &'a str, separator: &str) -> Result<Vec<Slha<'a>>> {
+        Slha::parse_multi_with_options(input, separator, &ParseOptions::default())
+    }
+
+    /// Read several concatenated SLHA documents using non-default options.
+    ///
+    /// This works like `parse_multi`, except that every document is read with
+    /// `parse_with_options`.
+    ///
+    /// # Errors
+    ///
+    /// The same errors as for `parse_multi` are returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, Block, ParseOptions};
+    ///
+    /// let input = "\
+    /// BLOCK MASS
+    ///    6    173,2
+    /// ENDSLHA
+    /// BLOCK MASS
+    ///    6    175,0
+    /// ";
+    ///
+    /// let options = ParseOptions { decimal_comma: true, ..ParseOptions::default() };
+    /// let points = Slha::parse_multi_with_options(input, "ENDSLHA", &options).unwrap();
+    /// let mass: Block<i64, f64> = points[0].get_block("mass").unwrap().unwrap();
+    /// assert_eq!(mass.map[&6], 173.2);
+    /// ```
+    pub fn parse_multi_with_options(
+        input: &'a str,
+        separator: &str,
+        options: &ParseOptions,
+    ) -> Result<Vec<Slha<'a>>> {
+        let mut documents = Vec::new();
+        let mut start = 0;
+        for line in input.lines() {
+            if !line.trim_start().starts_with(separator) {
+                continue;
+            }
+            let offset = line.as_ptr() as usize - input.as_ptr() as usize;
+            documents.push(&input[start..offset]);
+            start = offset + line.len();
+        }
+        documents.push(&input[start..]);
+        documents
+            .into_iter()
+            .enumerate()
+            .filter(|&(_, document)| !document.trim().is_empty())
+            .map(|(i, document)| {
+                Slha::parse_with_options(document, options)
+                    .chain_err(|| ErrorKind::InvalidDocument(i + 1))
+            })
+            .collect()
+    }
+
+    /// Lookup a single block by name and parse it into the required rust type.
+    ///
+    /// If there is no block with the given name, None is returned. If there is more than one
+    /// block with the name, an error is returned inside a `Some`. Otherwise the body of the block
+    /// is converted into an object of type `B` and the result is returned.
+    /// If a block may appear more than once in an SLHA file, the methods `get_blocks` and
+    /// `get_blocks_unchecked` may be used to access all of them.
+    ///
+    /// # Errors
+    ///
+    /// It is an error if there is more than one block with name `name` in the file.
+    /// This is independently of the scale.
+    /// Additionally, errors encountered while parsing the raw body of the block into an object of
+    /// type `B` are returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, Block, BlockStr, DecayTable};
+    ///
+    /// let input = "\
+    /// Block SMINPUTS   # Standard Model inputs
+    ///      3      0.1172  # alpha_s(MZ) SM MSbar
+    ///      5      4.25    # Mb(mb) SM MSbar
+    ///      6    174.3     # Mtop(pole)
+    /// DECAY 6 1.35
+    ///     1   2   5   24
+    /// ";
+    ///
+    /// let slha = match Slha::parse(input) {
+    ///     Ok(slha) => slha,
+    ///     Err(err) => panic!("Failed to deserialize SLHA file: {}", err),
+    /// };
+    ///
+    /// let sminputs: Block<i8, f64> = match slha.get_block("sminputs") {
+    ///     Some(block) => match block {
+    ///         Ok(sminputs) => sminputs,
+    ///         Err(err) => panic!("Failed to parse block 'smimputs':\n{}", err),
+    ///     },
+    ///     None => panic!("Missing block 'sminputs'."),
+    /// };
+    /// assert_eq!(sminputs.scale, None);
+    /// assert_eq!(sminputs.map.len(), 3);
+    /// assert_eq!(sminputs.map[&5], 4.25);
+    /// ```
+    pub fn get_block<B: SlhaBlock>(&self, name: &str) -> Option<Result<B>> {
+        let name = self.block_name(name);
+        let blocks = match self.blocks.get(&name) {
+            Some(blocks) => blocks,
+            None => return None,
+        };
+        if blocks.len() > 1 {
+            return Some(Err(ErrorKind::DuplicateBlock(name).into()));
+        }
+        Some(self.convert_block(&blocks[0], &name))
+    }
+
+    /// Lookup a block by name and convert it to a rust type, returning the input that was left
+    /// over at the end of its lines.
+    ///
+    /// This works like `get_block`, but if the file was read with `on_trailing` set to
+    /// `TrailingPolicy::Capture`, the input dropped from the end of the lines is returned as
+    /// well.
+    /// Otherwise the returned vector is always empty.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, Block, ParseOptions, TrailingPolicy};
+    ///
+    /// let input = "\
+    /// Block MASS
+    ///      6    173.2    pole
+    ///      5    4.25
+    ///     24    80.4     pole   PDG
+    /// ";
+    ///
+    /// let options = ParseOptions {
+    ///     on_trailing: TrailingPolicy::Capture,
+    ///     ..ParseOptions::default()
+    /// };
+    /// let slha = Slha::parse_with_options(input, &options).unwrap();
+    /// let (mass, trailing) = slha.get_block_with_trailing::<Block<i64, f64>>("mass")
+    ///     .unwrap()
+    ///     .unwrap();
+    /// assert_eq!(mass.map[&24], 80.4);
+    /// assert_eq!(trailing.len(), 2);
+    /// assert_eq!(trailing[1].line, 3);
+    /// assert_eq!(trailing[1].input, "pole   PDG");
+    /// ```
+    pub fn get_block_with_trailing<B: SlhaBlock>(
+        &self,
+        name: &str,
+    ) -> Option<Result<(B, Vec<TrailingInput>)>> {
+        let name = self.block_name(name);
+        let blocks = self.blocks.get(&name)?;
+        if blocks.len() > 1 {
+            return Some(Err(ErrorKind::DuplicateBlock(name).into()));
+        }
+        Some(blocks[0].to_block_with_policy(&name, self.options.on_trailing))
+    }
+
+    /// Lookup a block by name and read it as a `BlockStr`.
+    ///
+    /// This is a shorthand for `get_block::<BlockStr<V>>(name)`, see `get_block` for details.
+    ///
+    /// # Errors
+    ///
+    /// The same errors as for `get_block` are returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::Slha;
+    ///
+    /// let input = "\
+    /// Block SMINPUTS   # Standard Model inputs
+    ///      3      0.1172  # alpha_s(MZ) SM MSbar
+    ///      5      4.25    # Mb(mb) SM MSbar
+    ///      6    174.3     # Mtop(pole)
+    /// ";
+    ///
+    /// let slha = Slha::parse(input).unwrap();
+    /// let sminputs = slha.get_block_str::<f64>("sminputs").unwrap().unwrap();
+    /// assert_eq!(sminputs.scale, None);
+    /// assert_eq!(sminputs.map.len(), 3);
+    /// assert_eq!(sminputs.map[&vec!["5".to_string()]], 4.25);
+    /// ```
+    pub fn get_block_str<V: Parseable>(&self, name: &str) -> Option<Result<BlockStr<V>>> {
+        self.get_block(name)
+    }
+
+    /// Lookup a block by name, parse it into an object of type `B` and apply `f` to it.
+    ///
+    /// This is a shorthand for calling `get_block` and transforming the successfully parsed block.
+    /// If the block is not present, `None` is returned and `f` is not called.
+    ///
+    /// # Errors
+    ///
+    /// The same errors as for `get_block` are returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, Block};
+    ///
+    /// let input = "\
+    /// Block SMINPUTS   # Standard Model inputs
+    ///      1   1.27934000e+02   # alpha_em^-1(M_Z)^MSbar
+    ///      3      0.1172  # alpha_s(MZ) SM MSbar
+    /// ";
+    ///
+    /// let slha = Slha::parse(input).unwrap();
+    /// let alpha_em = slha.map_block("sminputs", |sminputs: Block<u8, f64>| 1. / sminputs.map[&1])
+    ///     .unwrap()
+    ///     .unwrap();
+    /// assert_eq!(alpha_em, 1. / 127.934);
+    /// assert!(slha.map_block("mass", |mass: Block<i64, f64>| mass.map.len()).is_none());
+    /// ```
+    pub fn map_block<B: SlhaBlock, T>(
+        &self,
+        name: &str,
+        f: impl FnOnce(B) -> T,
+    ) -> Option<Result<T>> {
+        self.get_block(name).map(|block| block.map(f))
+    }
+
+    /// Lookup the occurrence of a block that is defined at (approximately) the scale `q`.
+    ///
+    /// A block matches if its scale differs from `q` by at most `rel_tol * |q|`, see
+    /// `Block::scale_approx`.
+    /// Blocks without a scale never match.
+    /// If no occurrence of the block matches, `None` is returned.
+    ///
+    /// # Errors
+    ///
+    /// It is an error if more than one occurrence of the block matches the scale.
+    /// Additionally, errors encountered while parsing the raw body of the block into an object of
+    /// type `B` are returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, Block};
+    ///
+    /// let input = "\
+    /// Block ye Q= 4.64649125e+02
+    ///     3  3 9.97405356e-02   # Ytau(Q)MSSM DRbar
+    /// Block ye Q= 4.64649125e+03
+    ///     3  3 9.97405356e-03   # Ytau(Q)MSSM DRbar
+    /// ";
+    ///
+    /// let slha = Slha::parse(input).unwrap();
+    /// let ye: Block<(u8, u8), f64> = slha.get_block_at_scale("ye", 464.649, 1e-5).unwrap().unwrap();
+    /// assert_eq!(ye.map[&(3, 3)], 9.97405356e-02);
+    /// assert!(slha.get_block_at_scale::<Block<(u8, u8), f64>>("ye", 500., 1e-5).is_none());
+    /// ```
+    pub fn get_block_at_scale<B: SlhaBlock>(
+        &self,
+        name: &str,
+        q: f64,
+        rel_tol: f64,
+    ) -> Option<Result<B>> {
+        let name = self.block_name(name);
+        let mut matching = self.get_raw_blocks(&name).iter().filter(|block| {
+            scale_approx(block.scale, q, rel_tol)
+        });
+        let block = matching.next()?;
+        if matching.next().is_some() {
+            return Some(Err(ErrorKind::DuplicateBlockScale(name, q).into()));
+        }
+        Some(self.convert_block(block, &name))
+    }
+
+    /// Lookup the occurrence of a block with the given scale.
+    ///
+    /// With `Some(q)`, this works like `get_block_at_scale` with a relative tolerance of `1e-6`,
+    /// which is enough to absorb differences in the printed precision of the scale.
+    /// With `None`, the occurrence of the block without a scale is selected.
+    /// If no occurrence of the block matches, `None` is returned.
+    ///
+    /// # Errors
+    ///
+    /// It is an error if more than one occurrence of the block matches, which is reported as a
+    /// `DuplicateBlockScale` error for `Some(q)` and as a `DuplicateBlock` error for `None`.
+    /// Additionally, errors encountered while parsing the raw body of the block into an object of
+    /// type `B` are returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, Block};
+    ///
+    /// let input = "\
+    /// Block ye
+    ///     3  3 1.0e-01
+    /// Block ye Q= 4.64649125e+02
+    ///     3  3 9.97405356e-02   # Ytau(Q)MSSM DRbar
+    /// ";
+    ///
+    /// let slha = Slha::parse(input).unwrap();
+    /// let ye: Block<(u8, u8), f64> = slha.get_block_by_scale("ye", Some(464.649125)).unwrap().unwrap();
+    /// assert_eq!(ye.map[&(3, 3)], 9.97405356e-02);
+    /// let ye: Block<(u8, u8), f64> = slha.get_block_by_scale("ye", None).unwrap().unwrap();
+    /// assert_eq!(ye.map[&(3, 3)], 1.0e-01);
+    /// ```
+    pub fn get_block_by_scale<B: SlhaBlock>(
+        &self,
+        name: &str,
+        scale: Option<f64>,
+    ) -> Option<Result<B>> {
+        if let Some(q) = scale {
+            return self.get_block_at_scale(name, q, SCALE_REL_TOL);
+        }
+        let name = self.block_name(name);
+        let mut matching = self.get_raw_blocks(&name).iter().filter(|block| {
+            block.scale.is_none()
+        });
+        let block = matching.next()?;
+        if matching.next().is_some() {
+            return Some(Err(ErrorKind::DuplicateBlock(name).into()));
+        }
+        Some(self.convert_block(block, &name))
+    }
+
+    /// Lookup the occurrence of a block whose scale satisfies the predicate `pred`.
+    ///
+    /// The predicate is called with the scale of every occurrence of the block, or `None` for an
+    /// occurrence without a scale.
+    /// If no occurrence matches, `None` is returned.
+    ///
+    /// # Errors
+    ///
+    /// A `DuplicateBlock` error is returned if more than one occurrence matches.
+    /// Otherwise, the same errors as for `get_block` are returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, Block};
+    ///
+    /// let input = "\
+    /// Block ye Q= 40
+    ///     3  3 7.0e-02
+    /// Block ye Q= 200
+    ///     3  3 6.0e-02
+    /// Block ye
+    ///     3  3 9.0e-02
+    /// ";
+    ///
+    /// let slha = Slha::parse(input).unwrap();
+    /// let ye: Block<(u8, u8), f64> = slha
+    ///     .get_block_where("ye", |scale| scale.map_or(false, |q| q > 100.))
+    ///     .unwrap()
+    ///     .unwrap();
+    /// assert_eq!(ye.scale, Some(200.));
+    /// assert!(slha.get_block_where::<Block<(u8, u8), f64>>("ye", |q| q == Some(10.)).is_none());
+    /// assert!(slha.get_block_where::<Block<(u8, u8), f64>>("ye", |q| q.is_some())
+    ///     .unwrap()
+    ///     .is_err());
+    /// ```
+    pub fn get_block_where<B: SlhaBlock>(
+        &self,
+        name: &str,
+        pred: impl Fn(Option<f64>) -> bool,
+    ) -> Option<Result<B>> {
+        let name = self.block_name(name);
+        let mut matching = self.get_raw_blocks(&name).iter().filter(|block| pred(block.scale));
+        let block = matching.next()?;
+        if matching.next().is_some() {
+            return Some(Err(ErrorKind::DuplicateBlock(name).into()));
+        }
+        Some(self.convert_block(block, &name))
+    }
+
+    /// Lookup all blocks with a given name but different scale and parse them into a vector of
+    /// rust objects.
+    ///
+    /// This function checks that all blocks have different scales.
+    /// If one block does have a scale, then all of them must have one.
+    /// Only one block without a scale is allowed.
+    /// As an alternative the method `get_blocks_unchecked` exists, which does not perform these
+    /// sanity checks.
+    /// If there is no block with the given name, the returned vector is empty.
+    ///
+    /// The returned blocks are in the same order as they appear in the SLHA file.
+    ///
+    /// # Errors
+    ///
+    /// If (at least) one of the blocks could not be parsed into an object of type `B`.
+    /// If the blocks do not have unique scales as described above.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, Block};
+    ///
+    /// let input = "\
+    /// BLOCK Mass
+    ///     6    173.2
+    /// Block ye Q= 20
+    ///     3  3 9.0e-02   # Ytau(Q)MSSM DRbar
+    /// Block yu Q= 10
+    ///     3  3 8.88194465e-01   # Yt(Q)MSSM DRbar
+    /// Block ye Q= 40
+    ///     3  3 7.0e-03   # Ytau(Q)MSSM DRbar
+    /// ";
+    ///
+    /// let slha = Slha::parse(input).unwrap();
+    /// let mass: Vec<Block<i64, f64>> = match slha.get_blocks("mass") {
+    ///     Ok(mass) => mass,
+    ///     Err(err) => panic!("Error while parsing block mass: {}", err),
+    /// };
+    /// assert_eq!(mass.len(), 1);
+    /// assert_eq!(mass[0].map[&6], 173.2);
+    ///
+    /// let ye: Vec<Block<(i8,i8), f64>> = match slha.get_blocks("ye") {
+    ///     Ok(ye) => ye,
+    ///     Err(err) => panic!("Error while parsing block ye: {}", err),
+    /// };
+    /// assert_eq!(ye.len(), 2);
+    /// assert_eq!(ye[0].scale, Some(20.));
+    /// assert_eq!(ye[0].map[&(3,3) ], 9.0e-02);
+    /// assert_eq!(ye[1].scale, Some(40.));
+    /// assert_eq!(ye[1].map[&(3,3) ], 7.0e-03);
+    /// ```
+    pub fn get_blocks<B: SlhaBlock>(&self, name: &str) -> Result<Vec<B>> {
+        let blocks: Vec<B> = self.get_blocks_unchecked(name)?;
+        let mut no_scale = false;
+        let mut seen_scales = Vec::new();
+        for block in &blocks {
+            match block.scale() {
+                Some(scale) => seen_scales.push(scale),
+                None if no_scale => bail!(ErrorKind::DuplicateBlock(self.block_name(name))),
+                None => no_scale = true,
+            }
+        }
+        if no_scale && !seen_scales.is_empty() {
+            bail!(ErrorKind::RedefinedBlockWithQ(self.block_name(name)));
+        }
+        if let Some(scale) = find_duplicates(seen_scales, self.options.scale_eps) {
+            bail!(ErrorKind::DuplicateBlockScale(self.block_name(name), scale));
+        }
+        Ok(blocks)
+    }
+
+    /// Lookup all blocks with a given name and parse them into a vector of rust objects.
+    ///
+    /// Unlike `get_blocks` this function does not check if the scales of the blocks are consistent
+    /// with each other.
+    /// If there is no block with the given name, the returned vector is empty.
+    ///
+    /// The returned blocks are in the same order as they appear in the SLHA file.
+    ///
+    /// # Errors
+    ///
+    /// If (at least) one of the blocks could not be parsed into an object of type `B`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, Block};
+    ///
+    /// let input = "\
+    /// BLOCK Mass
+    ///     6    173.2
+    /// Block ye Q= 20
+    ///     3  3 9.0e-02   # Ytau(Q)MSSM DRbar
+    /// Block ye Q= 30
+    ///     3  3 8.0e-01   # Yt(Q)MSSM DRbar
+    /// BLOCK Mass
+    ///     5    5.2
+    /// Block ye Q= 20
+    ///     3  3 7.0e-03   # Ytau(Q)MSSM DRbar
+    /// ";
+    ///
+    /// let slha = Slha::parse(input).unwrap();
+    /// let mass: Vec<Block<i64, f64>> = match slha.get_blocks_unchecked("mass") {
+    ///     Ok(mass) => mass,
+    ///     Err(err) => panic!("Error while parsing block mass: {}", err),
+    /// };
+    /// assert_eq!(mass.len(), 2);
+    /// assert_eq!(mass[0].map[&6], 173.2);
+    /// assert_eq!(mass[1].map[&5], 5.2);
+    ///
+    /// let ye: Vec<Block<(i8,i8), f64>> = match slha.get_blocks_unchecked("ye") {
+    ///     Ok(ye) => ye,
+    ///     Err(err) => panic!("Error while parsing block ye: {}", err),
+    /// };
+    /// assert_eq!(ye.len(), 3);
+    /// assert_eq!(ye[0].scale, Some(20.));
+    /// assert_eq!(ye[0].map[&(3,3) ], 9.0e-02);
+    /// assert_eq!(ye[1].scale, Some(30.));
+    /// assert_eq!(ye[1].map[&(3,3) ], 8.0e-01);
+    /// assert_eq!(ye[2].scale, Some(20.));
+    /// assert_eq!(ye[2].map[&(3,3) ], 7.0e-03);
+    /// ```
+    pub fn get_blocks_unchecked<B: SlhaBlock>(&self, name: &str) -> Result<Vec<B>> {
+        let name = self.block_name(name);
+        let blocks = match self.blocks.get(&name) {
+            Some(blocks) => blocks,
+            None => return Ok(Vec::new()),
+        };
+        blocks.iter().map(|block| self.convert_block(block, &name)).collect()
+    }
+
+    /// Lookup all blocks with a given name and collect them into a `ScaleGrid`.
+    ///
+    /// The blocks are read using `get_blocks`, so the same checks on the scales of the blocks are
+    /// performed.
+    /// If there is no block with the given name, the returned grid is empty.
+    ///
+    /// # Errors
+    ///
+    /// The same errors as for `get_blocks` are returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, Block, ScaleGrid};
+    ///
+    /// let input = "\
+    /// Block yu Q= 20
+    ///     3  3 8.9e-01   # Yt(Q)MSSM DRbar
+    /// Block yu Q= 10
+    ///     3  3 9.0e-01   # Yt(Q)MSSM DRbar
+    /// ";
+    ///
+    /// let slha = Slha::parse(input).unwrap();
+    /// let yu: ScaleGrid<Block<(i8, i8), f64>> = slha.get_scale_grid("yu").unwrap();
+    /// assert_eq!(yu.blocks().len(), 2);
+    /// assert_eq!(yu.blocks()[0].scale, Some(10.));
+    /// assert_eq!(yu.blocks()[1].scale, Some(20.));
+    /// ```
+    pub fn get_scale_grid<B: SlhaBlock>(&self, name: &str) -> Result<ScaleGrid<B>> {
+        Ok(ScaleGrid::new(self.get_blocks(name)?))
+    }
+
+    /// Returns the raw bodies of all blocks with the given names.
+    ///
+    /// The returned `RawBlock` objects contain all non-whitespace, non-comment lines that belong
+    /// to the block.
+    /// While leading whitespace is not included and neither is the newline at the end of the line,
+    /// all other whitespace is still present.
+    ///
+    /// The returned blocks are in the same order as they appear in the SLHA file.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, Line};
+    ///
+    /// let input = "\
+    /// BLOCK Mass
+    ///     6    173.2
+    /// Block ye Q= 20
+    ///     3  3 9.0e-02   # First line
+    /// Block ye Q= 30
+    ///     3  3 8.0e-01   #    Second line
+    /// BLOCK Mass
+    ///     5    5.2
+    /// Block ye Q= 20
+    ///     3  3 7.0e-03   # Third
+    /// ";
+    ///
+    /// let slha = Slha::parse(input).unwrap();
+    /// let mass = slha.get_raw_blocks("mass");
+    /// assert_eq!(mass.len(), 2);
+    /// assert_eq!(mass[0].lines[0], Line { data: "6    173.2", comment: None });
+    /// assert_eq!(mass[1].lines[0], Line { data: "5    5.2", comment: None });
+    ///
+    /// let ye = slha.get_raw_blocks("ye");
+    /// assert_eq!(ye.len(), 3);
+    /// assert_eq!(ye[0].scale, Some(20.));
+    /// assert_eq!(ye[0].lines[0], Line { data: "3  3 9.0e-02   ", comment: Some("# First line") });
+    /// assert_eq!(ye[1].scale, Some(30.));
+    /// assert_eq!(ye[1].lines[0], Line { data: "3  3 8.0e-01   ", comment: Some("#    Second line") });
+    /// assert_eq!(ye[2].scale, Some(20.));
+    /// assert_eq!(ye[2].lines[0], Line { data: "3  3 7.0e-03   ", comment: Some("# Third") });
+    /// ```
+    pub fn get_raw_blocks<'s>(&'s self, name: &str) -> &'s [RawBlock<'a>] {
+        let name = self.block_name(name);
+        match self.blocks.get(&name) {
+            Some(blocks) => &blocks,
+            None => &[],
+        }
+    }
+
+    /// Returns the raw bodies of all commented out blocks with the given name.
+    ///
+    /// Disabled blocks are only recognized if the file was read using `parse_with_options` with
+    /// `recognize_commented_blocks` set, otherwise this always returns an empty slice.
+    /// The blocks are in the same order as they appear in the SLHA file.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, ParseOptions};
+    ///
+    /// let input = "\
+    /// BLOCK MASS
+    ///    6    173.2
+    ///    5    4.25
+    /// ## BLOCK MASS Q= 100
+    /// ##   6    175.0
+    /// ";
+    ///
+    /// let options = ParseOptions {
+    ///     recognize_commented_blocks: true,
+    ///     ..ParseOptions::default()
+    /// };
+    /// let slha = Slha::parse_with_options(input, &options).unwrap();
+    /// assert_eq!(slha.get_raw_blocks("mass")[0].lines.len(), 2);
+    /// let disabled = &slha.get_disabled_blocks("mass")[0];
+    /// assert_eq!(disabled.scale, Some(100.));
+    /// assert_eq!(disabled.lines[0].data, "6    175.0");
+    /// ```
+    pub fn get_disabled_blocks<'s>(&'s self, name: &str) -> &'s [RawBlock<'a>] {
+        let name = self.block_name(name);
+        match self.disabled_blocks.get(&name) {
+            Some(blocks) => blocks,
+            None => &[],
+        }
+    }
+
+    /// Returns the segments that are neither blocks nor decay tables.
+    ///
+    /// Each segment is given by its name, as it appears in the file, and its data lines.
+    /// The rest of the header line, if any, is the first line of the segment.
+    /// Unknown segments are only kept if the file was read using `parse_with_options` with
+    /// `keep_unknown_segments` set, otherwise this is always empty.
+    /// The segments are in the same order as they appear in the SLHA file.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, ParseOptions};
+    ///
+    /// let input = "\
+    /// INFO   # Generated by hand
+    /// Some free form text
+    ///     with several lines
+    /// BLOCK MASS
+    ///    6    173.2
+    /// ";
+    ///
+    /// let options = ParseOptions {
+    ///     keep_unknown_segments: true,
+    ///     ..ParseOptions::default()
+    /// };
+    /// let slha = Slha::parse_with_options(input, &options).unwrap();
+    /// let (ref name, ref lines) = slha.extra_segments()[0];
+    /// assert_eq!(name, "INFO");
+    /// assert_eq!(lines.len(), 2);
+    /// assert_eq!(lines[0].data, "Some free form text");
+    /// assert_eq!(slha.get_raw_blocks("mass").len(), 1);
+    /// ```
+    pub fn extra_segments(&self) -> &[(String, Vec<Line<'a>>)] {
+        &self.extra_segments
+    }
+
+    /// Turns all commented out blocks with the given name back into normal blocks.
+    ///
+    /// The disabled blocks are appended to the occurrences of the block that are already present.
+    /// Returns `true` if there were any disabled blocks with this name.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, ParseOptions, Block};
+    ///
+    /// let input = "\
+    /// ## BLOCK MASS
+    ///    6    173.2
+    /// ";
+    ///
+    /// let options = ParseOptions {
+    ///     recognize_commented_blocks: true,
+    ///     ..ParseOptions::default()
+    /// };
+    /// let mut slha = Slha::parse_with_options(input, &options).unwrap();
+    /// assert!(slha.get_raw_block("mass").is_none());
+    /// assert!(slha.enable_block("mass"));
+    /// let mass: Block<i64, f64> = slha.get_block("mass").unwrap().unwrap();
+    /// assert_eq!(mass.map[&6], 173.2);
+    /// assert!(!slha.enable_block("mass"));
+    /// ```
+    pub fn enable_block(&mut self, name: &str) -> bool {
+        let name = self.block_name(name);
+        match self.disabled_blocks.remove(&name) {
+            Some(blocks) => {
+                let occurrences = self.blocks.entry(name.clone()).or_default();
+                for (i, block) in blocks.into_iter().enumerate() {
+                    self.block_order.push((name.clone(), occurrences.len() + i));
+                    occurrences.push(block);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Lookup the first occurrence of a block by name and return it unparsed.
+    ///
+    /// If there is no block with the given name, `None` is returned.
+    /// Any further occurrences of the block are ignored, use `get_raw_blocks` to access all of
+    /// them.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::Slha;
+    ///
+    /// let input = "\
+    /// BLOCK Mass
+    ///     6    173.2
+    /// BLOCK Mass
+    ///     5    5.2
+    /// ";
+    ///
+    /// let slha = Slha::parse(input).unwrap();
+    /// let mass = slha.get_raw_block("mass").unwrap();
+    /// assert_eq!(mass.scale, None);
+    /// assert_eq!(mass.lines.len(), 1);
+    /// assert_eq!(mass.lines[0].data, "6    173.2");
+    /// assert!(slha.get_raw_block("ye").is_none());
+    /// ```
+    pub fn get_raw_block<'s>(&'s self, name: &str) -> Option<&'s RawBlock<'a>> {
+        self.get_raw_blocks(name).first()
+    }
+
+    /// Returns all blocks whose name matches `pattern`, together with their names.
+    ///
+    /// A `*` at the end of the pattern matches any suffix, so e.g. `y*` matches the Yukawa blocks
+    /// `yu`, `yd` and `ye`.
+    /// Without a `*` the name has to match the pattern exactly.
+    /// Like block names, the pattern is case insensitive unless the file was read with
+    /// `case_sensitive` set.
+    ///
+    /// The blocks are sorted by name, and all occurences of a block are returned in the order in
+    /// which they appear in the SLHA file.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::Slha;
+    ///
+    /// let input = "\
+    /// Block yu Q= 4.64649125e+02
+    ///     3  3 8.88193465e-01
+    /// Block ye Q= 4.64649125e+02
+    ///     3  3 9.97405356e-02
+    /// Block mass
+    ///     6    173.2
+    /// ";
+    ///
+    /// let slha = Slha::parse(input).unwrap();
+    /// let names: Vec<_> = slha.blocks_matching("Y*").into_iter().map(|(name, _)| name).collect();
+    /// assert_eq!(names, vec!["ye", "yu"]);
+    /// ```
+    pub fn blocks_matching<'s>(&'s self, pattern: &str) -> Vec<(&'s str, &'s RawBlock<'a>)> {
+        let pattern = self.block_name(pattern);
+        let mut names: Vec<&String> = if pattern.ends_with('*') {
+            let prefix = &pattern[..pattern.len() - 1];
+            self.blocks.keys().filter(|name| name.starts_with(prefix)).collect()
+        } else {
+            self.blocks.keys().filter(|&name| *name == pattern).collect()
+        };
+        names.sort();
+        names
+            .into_iter()
+            .flat_map(|name| self.blocks[name].iter().map(move |block| (name.as_str(), block)))
+            .collect()
+    }
+
+    /// Returns all data lines of all blocks, together with the name and scale of their block.
+    ///
+    /// The blocks are visited in the order in which they appear in the SLHA file and the lines of
+    /// every block in their original order.
+    /// After `canonicalize`, the blocks are instead visited ordered by name and scale.
+    /// Disabled blocks, see `ParseOptions::recognize_commented_blocks`, are not included.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::Slha;
+    ///
+    /// let input = "\
+    /// Block ye Q= 20
+    ///     3  3 9.0e-02
+    /// BLOCK Mass
+    ///     6    173.2
+    ///     5    4.25
+    /// Block ye Q= 30
+    ///     3  3 8.0e-01
+    /// ";
+    ///
+    /// let slha = Slha::parse(input).unwrap();
+    /// let entries: Vec<_> = slha.entries()
+    ///     .map(|(name, scale, line)| (name, scale, line.data.as_ref()))
+    ///     .collect();
+    /// assert_eq!(entries, vec![
+    ///     ("ye", Some(20.), "3  3 9.0e-02"),
+    ///     ("mass", None, "6    173.2"),
+    ///     ("mass", None, "5    4.25"),
+    ///     ("ye", Some(30.), "3  3 8.0e-01"),
+    /// ]);
+    /// ```
+    pub fn entries<'s>(
+        &'s self,
+    ) -> impl Iterator<Item = (&'s str, Option<f64>, &'s Line<'a>)> + 's {
+        self.block_order.iter().flat_map(move |&(ref name, i)| {
+            let block = &self.blocks[name][i];
+            block.lines.iter().map(move |line| (name.as_str(), block.scale, line))
+        })
+    }
+
+    /// Returns the total number of data lines in all occurrences of a block.
+    ///
+    /// The lines are only counted, not parsed.
+    /// If there is no block with the given name, `0` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::Slha;
+    ///
+    /// let input = "\
+    /// BLOCK Mass
+    ///     6    173.2
+    ///     5    4.25
+    /// BLOCK Mass
+    ///     4    1.27
+    /// ";
+    ///
+    /// let slha = Slha::parse(input).unwrap();
+    /// assert_eq!(slha.block_entry_count("mass"), 3);
+    /// assert_eq!(slha.block_entry_count("ye"), 0);
+    /// ```
+    pub fn block_entry_count(&self, name: &str) -> usize {
+        self.get_raw_blocks(name).iter().map(|block| block.lines.len()).sum()
+    }
+
+    /// Returns statistics about every block in the file.
+    ///
+    /// The blocks are sorted by name.
+    /// The data lines are only counted, not parsed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::Slha;
+    ///
+    /// let input = "\
+    /// BLOCK MASS
+    ///     6    173.2
+    ///     5    4.25
+    /// BLOCK YE Q= 20
+    ///     3  3 9.0e-01
+    /// BLOCK YE Q= 30
+    ///     3  3 8.0e-01
+    /// ";
+    ///
+    /// let slha = Slha::parse(input).unwrap();
+    /// let report = slha.block_report();
+    /// assert_eq!(report.len(), 2);
+    /// assert_eq!(report[0].name, "mass");
+    /// assert_eq!(report[0].total_entries, 2);
+    /// assert_eq!(report[1].name, "ye");
+    /// assert_eq!(report[1].occurrences, 2);
+    /// assert_eq!(report[1].scales, vec![Some(20.), Some(30.)]);
+    /// ```
+    pub fn block_report(&self) -> Vec<BlockReport> {
+        let mut report: Vec<_> = self.blocks
+            .iter()
+            .map(|(name, blocks)| {
+                BlockReport {
+                    name: name.clone(),
+                    occurrences: blocks.len(),
+                    total_entries: blocks.iter().map(|block| block.lines.len()).sum(),
+                    scales: blocks.iter().map(|block| block.scale).collect(),
+                }
+            })
+            .collect();
+        report.sort_by(|a, b| a.name.cmp(&b.name));
+        report
+    }
+
+    /// Checks that none of the given blocks has a scale.
+    ///
+    /// Some blocks, like `MODSEL`, are not running parameters and therefore must not be defined
+    /// at a scale.
+    /// Since the parser accepts a scale on any block, this method can be used to validate that
+    /// the blocks in `forbidden` do not carry one.
+    /// The names are case-insensitive and blocks that are not present in the file are ignored.
+    ///
+    /// # Errors
+    ///
+    /// A `ForbiddenScale` error naming the block is returned for the first occurence of one of the
+    /// listed blocks that has a scale.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::Slha;
+    ///
+    /// let input = "\
+    /// Block MODSEL  # Select model
+    ///      1    1   # sugra
+    /// Block ye Q= 4.64649125e+02
+    ///   3  3     9.97405356e-02   # Ytau(Q)MSSM DRbar
+    /// ";
+    ///
+    /// let slha = Slha::parse(input).unwrap();
+    /// assert!(slha.validate_scales(&["modsel", "mass"]).is_ok());
+    /// assert!(slha.validate_scales(&["modsel", "ye"]).is_err());
+    /// ```
+    pub fn validate_scales(&self, forbidden: &[&str]) -> Result<()> {
+        for name in forbidden {
+            let name = self.block_name(name);
+            if self.get_raw_blocks(&name).iter().any(|block| block.scale.is_some()) {
+                bail!(ErrorKind::ForbiddenScale(name));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the decay table of the particle with the given pdg id.
+    ///
+    /// If there is no decay table for the given particle in the SLHA file, then `None` is
+    /// returned.
+    /// An error is only returned for decay tables that are parsed on first access, see
+    /// `parse_blocks_only`, if the table can not be parsed.
+    ///
+    /// ```rust
+    /// use slha::{Slha, DecayTable};
+    ///
+    /// let input = "\
+    /// Block SMINPUTS   # Standard Model inputs
+    ///      3      0.1172  # alpha_s(MZ) SM MSbar
+    ///      5      4.25    # Mb(mb) SM MSbar
+    ///      6    174.3     # Mtop(pole)
+    /// DECAY 6 1.35
+    ///     1   2   5   24
+    /// ";
+    ///
+    /// let slha = match Slha::parse(input) {
+    ///     Ok(slha) => slha,
+    ///     Err(err) => panic!("Failed to deserialize SLHA file: {}", err),
+    /// };
+    ///
+    /// let decay_table = match slha.get_decay(6) {
+    ///     Some(dec) => dec,
+    ///     None => panic!("Missing decay table for the top quark."),
+    /// };
+    /// assert_eq!(decay_table.width, 1.35);
+    /// let decay = &decay_table.decays;
+    /// assert_eq!(decay.len(), 1);
+    /// assert_eq!(decay[0].branching_ratio, 1.);
+    /// assert_eq!(decay[0].daughters, vec![5, 24]);
+    /// ```
+    pub fn get_decay(&self, pdg_id: i64) -> Option<&DecayTable> {
+        self.try_get_decay(pdg_id).and_then(|table| table.ok())
+    }
+
+    /// Returns the decay table of the particle with the given pdg id, or the error if it could not
+    /// be parsed.
+    ///
+    /// This works like `get_decay`, except that the error is returned if the decay table was read
+    /// lazily by `parse_blocks_only` and its decays can not be parsed, while `get_decay` returns
+    /// `None` in this case.
+    /// The table is only parsed on the first access, and the result is cached.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, ParseOptions};
+    /// use slha::errors::{Error, ErrorKind};
+    ///
+    /// let input = "\
+    /// DECAY 6 1.35
+    ///     1   2   5   24
+    /// DECAY 25 4.07e-03
+    ///     1   2   5   foo
+    /// ";
+    ///
+    /// let slha = Slha::parse_blocks_only(input, &ParseOptions::default()).unwrap();
+    /// assert_eq!(slha.try_get_decay(6).unwrap().unwrap().width, 1.35);
+    /// match slha.try_get_decay(25) {
+    ///     Some(Err(Error(ErrorKind::InvalidDecay(25), _))) => (),
+    ///     other => panic!("Unexpected result {:?}", other),
+    /// }
+    /// assert!(slha.get_decay(25).is_none());
+    /// assert!(slha.try_get_decay(24).is_none());
+    /// ```
+    pub fn try_get_decay(&self, pdg_id: i64) -> Option<Result<&DecayTable>> {
+        match self.decays.get(&pdg_id) {
+            Some(table) => Some(Ok(table)),
+            None => {
+                self.lazy_decays.get(&pdg_id).map(
+                    |table| table.get(pdg_id, &self.options),
+                )
+            }
+        }
+    }
+
+    /// Lookup the decay table of a particle, falling back to the table of its antiparticle.
+    ///
+    /// The decay table for `pdg_id` is returned if it is present in the SLHA file.
+    /// Otherwise the decay table for `-pdg_id` is returned, if present.
+    /// This is useful since usually only the decays of either the particle or the antiparticle
+    /// are given.
+    ///
+    /// Note that the table of the antiparticle is returned as it is, i.e. the daughters are *not*
+    /// replaced by their antiparticles.
+    ///
+    /// ```rust
+    /// use slha::Slha;
+    ///
+    /// let input = "\
+    /// DECAY 6 1.35
+    ///     1   2   5   24
+    /// ";
+    ///
+    /// let slha = Slha::parse(input).unwrap();
+    /// assert!(slha.get_decay(-6).is_none());
+    /// let decay_table = slha.get_decay_abs(-6).unwrap();
+    /// assert_eq!(decay_table.width, 1.35);
+    /// assert_eq!(decay_table.decays[0].daughters, vec![5, 24]);
+    /// ```
+    pub fn get_decay_abs(&self, pdg_id: i64) -> Option<&DecayTable> {
+        self.get_decay(pdg_id).or_else(|| self.get_decay(-pdg_id))
+    }
+
+    /// Returns an iterator over the PDG ids of all particles that have a decay table.
+    ///
+    /// The ids are returned in arbitrary order.
+    pub fn decay_pdg_ids(&self) -> impl Iterator<Item = i64> + '_ {
+        self.decays.keys().chain(self.lazy_decays.keys()).cloned()
+    }
+
+    /// Converts the `Slha` object into a struct that `SlhaDeserialize` is implemented for.
+    ///
+    /// This gives the same result as calling `T::deserialize` on the SLHA file that the `Slha`
+    /// object has been read from, but does not require to read the file again.
+    /// In particular, an `Slha` object that has been modified after reading can be converted
+    /// without writing it out first.
+    ///
+    /// # Errors
+    ///
+    /// The same errors as for `T::deserialize` are returned, except for the ones that are already
+    /// detected when reading the `Slha` object.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// extern crate slha;
+    /// #[macro_use]
+    /// extern crate slha_derive;
+    ///
+    /// use slha::{Slha, SlhaDeserialize, Block, DecayTable};
+    /// use std::collections::HashMap;
+    ///
+    /// #[derive(Debug, SlhaDeserialize)]
+    /// struct MySlha {
+    ///     mass: Block<i64, f64>,
+    ///     decays: HashMap<i64, DecayTable>,
+    /// }
+    ///
+    /// fn main() {
+    ///     let input = "\
+    /// Block MASS
+    ///    6    173.2
+    /// DECAY 6 1.35
+    ///     1   2   5   24
+    /// ";
+    ///
+    ///     let slha = Slha::parse(input).unwrap();
+    ///     let typed: MySlha = slha.to_typed().unwrap();
+    ///     assert_eq!(typed.mass.map[&6], 173.2);
+    ///     assert_eq!(typed.decays[&6].width, 1.35);
+    /// }
+    /// ```
+    pub fn to_typed<T: SlhaDeserialize>(&self) -> Result<T> {
+        T::deserialize_segments(self.segments())
+    }
+
+    /// Converts the `Slha` object into the struct of results of a type that
+    /// `SlhaDeserializeLenient` is implemented for.
+    ///
+    /// This works like `to_typed`, except that every field is read on its own, such that an
+    /// error in one block only affects the fields reading from this block.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// extern crate slha;
+    /// #[macro_use]
+    /// extern crate slha_derive;
+    ///
+    /// use slha::{Slha, SlhaDeserializeLenient, Block};
+    ///
+    /// #[derive(Debug, SlhaDeserializeLenient)]
+    /// struct MySlha {
+    ///     mass: Block<i64, f64>,
+    ///     nmix: Block<(u8, u8), f64>,
+    /// }
+    ///
+    /// fn main() {
+    ///     let input = "\
+    /// Block MASS
+    ///    6    173.2
+    /// ";
+    ///
+    ///     let slha = Slha::parse(input).unwrap();
+    ///     let typed: MySlhaLenient = slha.to_typed_lenient::<MySlha>();
+    ///     assert_eq!(typed.mass.unwrap().map[&6], 173.2);
+    ///     assert!(typed.nmix.is_err());
+    /// }
+    /// ```
+    pub fn to_typed_lenient<T: SlhaDeserializeLenient>(&self) -> T::Lenient {
+        T::deserialize_segments_lenient(self.segments())
+    }
+
+    /// Returns all blocks and decay tables as segments, as used by the derived deserialization.
+    fn segments<'b>(&'b self) -> impl Iterator<Item = Result<Segment<'a>>> + 'b {
+        let blocks = self.blocks.iter().flat_map(|(name, blocks)| {
+            blocks.iter().map(move |block| {
+                Ok(Segment::Block {
+                    name: name.clone(),
+                    block: block.clone(),
+                })
+            })
+        });
+        let decays = self.decays.iter().map(|(&pdg_id, table)| {
+            Ok(Segment::Decay {
+                pdg_id,
+                width: table.width,
+                decays: table.decays.clone(),
+            })
+        });
+        let options = &self.options;
+        let lazy_decays = self.lazy_decays.iter().map(move |(&pdg_id, table)| {
+            Ok(Segment::Decay {
+                pdg_id,
+                width: table.width,
+                decays: table.get(pdg_id, options)?.decays.clone(),
+            })
+        });
+        blocks.chain(decays).chain(lazy_decays)
+    }
+
+    /// Renames all occurrences of the block `from` to `to`.
+    ///
+    /// This can be used e.g. to migrate SLHA files that use outdated block names.
+    /// Unless the `Slha` object was read with `case_sensitive` set, both names are case
+    /// insensitive, i.e. the block is stored under the lowercased name `to`.
+    ///
+    /// # Errors
+    ///
+    /// A `MissingBlock` error is returned if there is no block `from`, and a `DuplicateBlock` error
+    /// if there already is a block `to`.
+    /// In both cases the `Slha` object is not modified.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, Block};
+    ///
+    /// let input = "\
+    /// Block SMINPUT
+    ///      3      0.1172  # alpha_s(MZ) SM MSbar
+    /// ";
+    ///
+    /// let mut slha = Slha::parse(input).unwrap();
+    /// slha.rename_block("sminput", "SMINPUTS").unwrap();
+    /// assert!(slha.get_raw_block("sminput").is_none());
+    /// let sminputs: Block<u8, f64> = slha.get_block("sminputs").unwrap().unwrap();
+    /// assert_eq!(sminputs.map[&3], 0.1172);
+    /// ```
+    pub fn rename_block(&mut self, from: &str, to: &str) -> Result<()> {
+        let from = self.block_name(from);
+        let to = self.block_name(to);
+        if !self.blocks.contains_key(&from) {
+            bail!(ErrorKind::MissingBlock(from));
+        }
+        if from == to {
+            return Ok(());
+        }
+        if self.blocks.contains_key(&to) {
+            bail!(ErrorKind::DuplicateBlock(to));
+        }
+        let blocks = self.blocks.remove(&from).expect("BUG: Block was checked to exist.");
+        for entry in &mut self.block_order {
+            if entry.0 == from {
+                entry.0 = to.clone();
+            }
+        }
+        self.blocks.insert(to, blocks);
+        Ok(())
+    }
+
+    /// Fills in the widths of decay tables from a `WIDTH` block.
+    ///
+    /// Some tools store the widths of the particles in a separate `WIDTH` block keyed by pdg id
+    /// instead of in the headers of the decay tables.
+    /// Every decay table with a width of zero, which is also used for missing widths if the
+    /// `optional_width` option is set, gets the width given for its particle in the `WIDTH`
+    /// block.
+    /// Decay tables with a non-zero width and particles without an entry in the `WIDTH` block
+    /// are not changed.
+    /// If there is no `WIDTH` block, this does nothing.
+    ///
+    /// # Errors
+    ///
+    /// An error is returned if the `WIDTH` block can not be read as a `Block<i64, f64>`.
+    /// In this case no width is changed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, ParseOptions};
+    ///
+    /// let input = "\
+    /// BLOCK WIDTH
+    ///     6    1.35
+    ///    25    4.07e-03
+    /// DECAY 6
+    ///     1   2   5   24
+    /// DECAY 25
+    ///     1   2   5   -5
+    /// ";
+    ///
+    /// let options = ParseOptions {
+    ///     optional_width: true,
+    ///     ..ParseOptions::default()
+    /// };
+    /// let mut slha = Slha::parse_with_options(input, &options).unwrap();
+    /// assert_eq!(slha.get_decay(6).unwrap().width, 0.);
+    /// slha.resolve_widths().unwrap();
+    /// assert_eq!(slha.get_decay(6).unwrap().width, 1.35);
+    /// assert_eq!(slha.get_decay(25).unwrap().width, 4.07e-03);
+    /// ```
+    pub fn resolve_widths(&mut self) -> Result<()> {
+        let widths: Block<i64, f64> = match self.get_block("width") {
+            Some(widths) => widths?,
+            None => return Ok(()),
+        };
+        for (pdg_id, table) in &mut self.decays {
+            match widths.map.get(pdg_id) {
+                Some(&width) if table.width == 0. => table.width = width,
+                _ => (),
+            }
+        }
+        for (pdg_id, table) in &mut self.lazy_decays {
+            match widths.map.get(pdg_id) {
+                Some(&width) if table.width == 0. => table.set_width(width),
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+
+    /// Brings the blocks and decay tables into a canonical form.
+    ///
+    /// Two `Slha` objects read from files that only differ in the order of the blocks, of the
+    /// lines in the blocks, of the decays in the decay tables or in the whitespace within the
+    /// lines compare equal after being canonicalized.
+    /// To this end
+    ///
+    /// * the occurences of each block are sorted by their scale, with blocks without a scale
+    ///   first,
+    /// * the data of each line is trimmed and its values are separated by a single space,
+    /// * the lines of each block are sorted word by word, comparing words as numbers where
+    ///   possible,
+    /// * the decays of each decay table are sorted by descending branching ratio and then by
+    ///   their daughters.
+    ///
+    /// Decay tables of an `Slha` object read with `parse_blocks_only` are parsed, unless they are
+    /// malformed.
+    /// Comments are kept together with their line.
+    /// Lines that have been joined because of `line_continuation` are replaced by the first of
+    /// them, which is read as the joined line.
+    /// Since the data of the lines is borrowed from the input, the `lines` of the blocks keep the
+    /// whitespace between the values, while the normalized lines are returned by
+    /// `RawBlock::logical_lines` and are used when the blocks are compared or read into a rust
+    /// type.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::Slha;
+    ///
+    /// let first = "\
+    /// BLOCK MASS
+    ///     6  173.2
+    ///     5  4.25
+    /// ";
+    /// let second = "\
+    /// BLOCK MASS
+    ///   5    4.25
+    ///   6 173.2
+    /// ";
+    ///
+    /// let mut first = Slha::parse(first).unwrap();
+    /// let mut second = Slha::parse(second).unwrap();
+    /// assert!(first != second);
+    /// first.canonicalize();
+    /// second.canonicalize();
+    /// assert_eq!(first, second);
+    /// let mass = first.get_raw_block("mass").unwrap();
+    /// assert_eq!(mass.lines[0].data, "5  4.25");
+    /// assert_eq!(mass.logical_lines()[0].data, "5 4.25");
+    /// ```
+    pub fn canonicalize(&mut self) {
+        for blocks in self.blocks.values_mut() {
+            blocks.sort_by(|a, b| a.scale.partial_cmp(&b.scale).unwrap_or(Ordering::Equal));
+            for block in blocks {
+                let mut lines: Vec<(Line, LogicalData)> = Vec::new();
+                for (mut line, logical) in block.take_lines() {
+                    let logical = match logical {
+                        LogicalData::Joined => {
+                            if let Some(&mut (ref mut joined, _)) = lines.last_mut() {
+                                joined.comment = joined.comment.or(line.comment);
+                            }
+                            continue;
+                        }
+                        logical => logical,
+                    };
+                    line.data = line.data.trim();
+                    let words: Vec<_> = logical.data(&line).split_whitespace().collect();
+                    let data = words.join(" ");
+                    let logical = if data == line.data {
+                        LogicalData::Unchanged
+                    } else {
+                        LogicalData::Replaced(data)
+                    };
+                    lines.push((line, logical));
+                }
+                lines.sort_by(|a, b| compare_words(a.1.data(&a.0), b.1.data(&b.0)));
+                block.set_lines(lines);
+            }
+        }
+        let mut block_order: Vec<_> = self.blocks
+            .iter()
+            .flat_map(|(name, blocks)| (0..blocks.len()).map(move |i| (name.clone(), i)))
+            .collect();
+        block_order.sort();
+        self.block_order = block_order;
+        let lazy_decays = self.lazy_decays.drain().collect::<Vec<_>>();
+        for (pdg_id, table) in lazy_decays {
+            if table.get(pdg_id, &self.options).is_err() {
+                self.lazy_decays.insert(pdg_id, table);
+                continue;
+            }
+            let table = table.into_table(pdg_id, &self.options).expect(
+                "BUG: The table was checked to be valid.",
+            );
+            self.decays.insert(pdg_id, table);
+        }
+        for table in self.decays.values_mut() {
+            table.decays.sort_by(|a, b| {
+                b.branching_ratio
+                    .partial_cmp(&a.branching_ratio)
+                    .unwrap_or(Ordering::Equal)
+                    .then_with(|| a.daughters.cmp(&b.daughters))
+            });
+        }
+    }
+
+    /// Adds the decay tables in `other` to the decay tables of this `Slha` object.
+    ///
+    /// The `policy` decides what happens if there already is a decay table for one of the
+    /// particles in `other`.
+    /// This can be used to combine the decays read from several SLHA files.
+    ///
+    /// # Errors
+    ///
+    /// If `policy` is `MergePolicy::Error` and there is a decay table in `other` for a particle
+    /// that already has a decay table, a `DuplicateDecay` error is returned.
+    /// In this case no decay table is added.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::{Slha, MergePolicy};
+    ///
+    /// let input = "\
+    /// DECAY 6 1.35
+    ///     1   2   5   24
+    /// ";
+    /// let other = "\
+    /// DECAY 6 1.5
+    ///     1   2   5   24
+    /// DECAY 25 4.07e-03
+    ///     1   2   5   -5
+    /// ";
+    ///
+    /// let mut slha = Slha::parse(input).unwrap();
+    /// let other = Slha::parse(other).unwrap();
+    /// slha.merge_decays(other.into_decays(), MergePolicy::KeepFirst).unwrap();
+    /// assert_eq!(slha.get_decay(6).unwrap().width, 1.35);
+    /// assert_eq!(slha.get_decay(25).unwrap().width, 4.07e-03);
+    /// ```
+    pub fn merge_decays(
+        &mut self,
+        other: HashMap<i64, DecayTable>,
+        policy: MergePolicy,
+    ) -> Result<()> {
+        if policy == MergePolicy::Error {
+            if let Some(&pdg_id) = other.keys().find(|id| {
+                self.decays.contains_key(id) || self.lazy_decays.contains_key(id)
+            })
+            {
+                bail!(ErrorKind::DuplicateDecay(pdg_id));
+            }
+        }
+        for (pdg_id, table) in other {
+            match policy {
+                MergePolicy::KeepFirst if self.lazy_decays.contains_key(&pdg_id) => (),
+                MergePolicy::KeepFirst => {
+                    self.decays.entry(pdg_id).or_insert(table);
+                }
+                MergePolicy::Error | MergePolicy::Override => {
+                    self.lazy_decays.remove(&pdg_id);
+                    self.decays.insert(pdg_id, table);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Consumes the `Slha` object and returns the decay tables of all particles.
+    ///
+    /// Decay tables that have not been parsed yet and can not be parsed are skipped, see
+    /// `parse_blocks_only`.
+    pub fn into_decays(self) -> HashMap<i64, DecayTable> {
+        let mut decays = self.decays;
+        for (pdg_id, table) in self.lazy_decays {
+            if let Ok(table) = table.into_table(pdg_id, &self.options) {
+                decays.insert(pdg_id, table);
+            }
+        }
+        decays
+    }
+
+    /// Returns a copy of the decay tables of all particles.
+    ///
+    /// Unlike the `Slha` object itself, the returned tables do not borrow from the input, so they
+    /// can be kept after the input has been dropped.
+    /// Decay tables that have not been parsed yet and can not be parsed are skipped, see
+    /// `parse_blocks_only`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::Slha;
+    ///
+    /// let decays = {
+    ///     let input = String::from("DECAY 6 1.35\n    1.0   2   5   24\n");
+    ///     let slha = Slha::parse(&input).unwrap();
+    ///     slha.clone_decays()
+    /// };
+    /// assert_eq!(decays[&6].width, 1.35);
+    /// ```
+    pub fn clone_decays(&self) -> HashMap<i64, DecayTable> {
+        let mut decays = self.decays.clone();
+        for (&pdg_id, table) in &self.lazy_decays {
+            if let Ok(table) = table.get(pdg_id, &self.options) {
+                decays.insert(pdg_id, table.clone());
+            }
+        }
+        decays
+    }
+
+    /// Converts a block according to the `TrailingPolicy` the file was read with.
+    fn convert_block<B: SlhaBlock>(&self, block: &RawBlock<'a>, name: &str) -> Result<B> {
+        block.to_block_with_policy(name, self.options.on_trailing).map(|(block, _)| block)
+    }
+
+    /// Creates an `Slha` object without any blocks or decay tables.
+    fn empty(options: &ParseOptions) -> Slha<'a> {
+        Slha {
+            blocks: HashMap::new(),
+            block_order: Vec::new(),
+            decays: HashMap::new(),
+            lazy_decays: HashMap::new(),
+            disabled_blocks: HashMap::new(),
+            extra_segments: Vec::new(),
+            options: options.clone(),
+        }
+    }
+
+    /// Returns the name under which a block is stored, depending on the case sensitivity.
+    fn block_name(&self, name: &str) -> String {
+        if self.options.case_sensitive {
+            name.to_string()
+        } else {
+            name.to_lowercase()
+        }
+    }
+
+    fn insert_segment(&mut self, segment: Segment<'a>, options: &ParseOptions) -> Result<()> {
+        let segment = match segment {
+            Segment::Block { name, block } if options.known_aliases => {
+                Segment::Block {
+                    name: standard_block_name(name),
+                    block,
+                }
+            }
+            segment => segment,
+        };
+        match segment {
+            Segment::Block { name, .. }
+                if options.on_duplicate_block == DuplicatePolicy::Error &&
+                       self.blocks.contains_key(&name) => {
+                bail!(ErrorKind::DuplicateBlock(name))
+            }
+            Segment::Block { name, block } => self.insert_block(name, block),
+            Segment::Decay {
+                pdg_id,
+                width,
+                decays,
+            } => self.insert_decay(pdg_id, width, decays)?,
+            Segment::DisabledBlock { name, block } => {
+                self.disabled_blocks.entry(name).or_default().push(block)
+            }
+            Segment::Unknown { name, lines } => self.extra_segments.push((name, lines)),
+        }
+        Ok(())
+    }
+
+    fn insert_block(&mut self, name: String, block: RawBlock<'a>) {
+        let occurrences = self.blocks.entry(name.clone()).or_default();
+        self.block_order.push((name, occurrences.len()));
+        occurrences.push(block)
+    }
+
+    fn insert_decay(&mut self, pdg_id: i64, width: f64, decays: Vec<Decay>) -> Result<()> {
+        if self.decays.contains_key(&pdg_id) {
+            bail!(ErrorKind::DuplicateDecay(pdg_id));
+        }
+        self.decays.insert(pdg_id, DecayTable { width, decays });
+        Ok(())
+    }
+}
+
+/// The 64 bit FNV-1a hash, used by `content_hash`.
+///
+/// Unlike the `DefaultHasher` of the standard library its algorithm is fixed, and integers are
+/// hashed from their little endian bytes, with `usize` extended to 64 bits, so the hash of a value
+/// is the same on every platform.
+struct FnvHasher(u64);
+impl FnvHasher {
+    fn new() -> FnvHasher {
+        FnvHasher(0xcbf2_9ce4_8422_2325)
+    }
+}
+impl Hasher for FnvHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes())
+    }
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes())
+    }
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes())
+    }
+    fn write_u128(&mut self, i: u128) {
+        self.write(&i.to_le_bytes())
+    }
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64)
+    }
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Compares two lines word by word.
+///
+/// Words that can both be read as numbers are compared numerically, all others as strings.
+fn compare_words(a: &str, b: &str) -> Ordering {
+    let mut a_words = a.split_whitespace();
+    let mut b_words = b.split_whitespace();
+    loop {
+        let (a, b) = match (a_words.next(), b_words.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(a), Some(b)) => (a, b),
+        };
+        let ordering = match (a.parse::<f64>(), b.parse::<f64>()) {
+            (Ok(x), Ok(y)) => x.partial_cmp(&y).unwrap_or(Ordering::Equal).then_with(|| a.cmp(b)),
+            _ => a.cmp(b),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+}
+
+/// An `Slha` object together with the input it has been read from.
+///
+/// This is returned by `Slha::from_bytes_lossy`, which has to convert its input and therefore
+/// can not return an `Slha` object borrowing from the input passed to it.
+/// The input is only read once, when the `SlhaOwned` is created.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SlhaOwned {
+    // `slha` borrows from `input`, so it is declared first to be dropped first.
+    slha: Slha<'static>,
+    input: Arc<str>,
+}
+impl SlhaOwned {
+    /// Reads `input` with the given options and keeps it together with the `Slha` object.
+    fn new(input: String, options: &ParseOptions) -> Result<SlhaOwned> {
+        let input: Arc<str> = Arc::from(input);
+        // SAFETY: The text of `input` is never modified and stays at the same place on the heap
+        // until the last clone of the `Arc` is dropped.
+        // Every `Slha` object borrowing from it, including clones of `slha`, is stored next to a
+        // clone of the `Arc`, is dropped before it, and is only handed out with the lifetime of
+        // the `SlhaOwned` holding it.
+        let text: &'static str = unsafe { &*(&*input as *const str) };
+        let slha = Slha::parse_with_options(text, options)?;
+        Ok(SlhaOwned { slha, input })
+    }
+
+    /// Returns the converted input.
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+
+    /// Returns the options used to read the input.
+    pub fn options(&self) -> &ParseOptions {
+        &self.slha.options
+    }
+
+    /// Returns the `Slha` object read from the input.
+    pub fn slha(&self) -> &Slha<'_> {
+        &self.slha
+    }
+}
+
+/// A decay table whose decays are only parsed on first access.
+///
+/// The result of parsing the table is cached, including a failure.
+/// Since `Error` is not `Sync`, a failure is cached as the messages of the error chain, from
+/// which an `InvalidDecay` error is rebuilt on every access.
+#[derive(Clone, Debug)]
+struct LazyDecayTable<'a> {
+    width: f64,
+    lines: Vec<&'a str>,
+    table: OnceLock<::std::result::Result<DecayTable, CachedError>>,
+}
+impl<'a> LazyDecayTable<'a> {
+    /// Returns the parsed decay table, parsing it on the first call.
+    fn get(&self, pdg_id: i64, options: &ParseOptions) -> Result<&DecayTable> {
+        let table = self.table.get_or_init(|| {
+            match internal::parse_decay_lines(pdg_id, &self.lines, options) {
+                Ok(decays) => Ok(DecayTable {
+                    width: self.width,
+                    decays,
+                }),
+                // The outermost error is always `InvalidDecay`, which is rebuilt below.
+                Err(err) => Err(CachedError::new(
+                    err.source().expect("BUG: InvalidDecay always has a cause."),
+                )),
+            }
+        });
+        match *table {
+            Ok(ref table) => Ok(table),
+            Err(ref err) => Err(Error::with_chain(err.clone(), ErrorKind::InvalidDecay(pdg_id))),
+        }
+    }
+
+    /// Sets the width, both of the unparsed and of an already parsed table.
+    fn set_width(&mut self, width: f64) {
+        self.width = width;
+        if let Some(Ok(table)) = self.table.get_mut() {
+            table.width = width;
+        }
+    }
+
+    fn into_table(self, pdg_id: i64, options: &ParseOptions) -> Result<DecayTable> {
+        self.get(pdg_id, options)?;
+        Ok(self.table.into_inner().and_then(|table| table.ok()).expect(
+            "BUG: The table was parsed successfully.",
+        ))
+    }
+}
+
+/// The messages of an error chain, which unlike `Error` can be shared between threads.
+#[derive(Clone, Debug)]
+struct CachedError {
+    message: String,
+    cause: Option<Box<CachedError>>,
+}
+impl CachedError {
+    fn new(err: &(dyn StdError + 'static)) -> CachedError {
+        CachedError {
+            message: err.to_string(),
+            cause: err.source().map(|cause| Box::new(CachedError::new(cause))),
+        }
+    }
+}
+impl fmt::Display for CachedError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+impl StdError for CachedError {
+    fn description(&self) -> &str {
+        &self.message
+    }
+
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        self.cause.as_ref().map(|cause| &**cause as &(dyn StdError + 'static))
+    }
+}
+impl<'a> PartialEq for LazyDecayTable<'a> {
+    fn eq(&self, other: &LazyDecayTable<'a>) -> bool {
+        self.width == other.width && self.lines == other.lines
+    }
+}
+
+/// Asserts that two SLHA files contain the same data, up to a relative tolerance `tol`.
+///
+/// This is meant to be used in the tests of programs writing SLHA files, e.g. spectrum
+/// generators, where the output should be compared to a reference file without depending on the
+/// exact formatting.
+/// Both files are read with `Slha::parse`, and the following is ignored in the comparison:
+///
+/// * Comments, whitespace and the order of the lines.
+/// * The formatting of numbers, e.g. `100` and `1.0E+02` are the same.
+/// * The order of the occurrences of a block, which are matched by their scale.
+/// * The order of the daughter particles of a decay.
+///
+/// The entries of a block are matched by their keys, which are all integers at the beginning of
+/// a line except for the last word, in any formatting, e.g. `100` or `1.0E+02`.
+/// The remaining words are compared as numbers if they can be read as one and as text otherwise.
+/// Entries with the same key, like the messages in `SPINFO`, are compared in sorted order, and a
+/// key that appears a different number of times in the two files is a difference as well.
+/// The same holds for decays with the same daughters.
+/// Two numbers `x` and `y` are considered equal if `|x - y| <= tol * max(|x|, |y|)`.
+///
+/// # Panics
+///
+/// If one of the files can not be read, or if the files are not equivalent.
+/// In the latter case the message lists all differences that were found.
+///
+/// # Examples
+///
+/// ```rust
+/// let a = "\
+/// BLOCK MASS   # Mass spectrum
+///     6    1.73200000E+02   # M_t
+///    25    1.25000000E+02   # M_h
+/// DECAY   6   1.35
+///     1.0   2   5   24
+/// ";
+/// let b = "\
+/// Block mass
+///   25   125.0
+///    6   173.2
+/// DECAY 6 1.3500001
+///   1.0   2   24   5
+/// ";
+///
+/// slha::assert_equivalent(a, b, 1e-6);
+/// ```
+pub fn assert_equivalent(a: &str, b: &str, tol: f64) {
+    let a = Slha::parse(a).unwrap_or_else(|err| panic!("Failed to read the first file: {}", err));
+    let b = Slha::parse(b).unwrap_or_else(|err| panic!("Failed to read the second file: {}", err));
+    let differences = differences(&a, &b, tol);
+    if !differences.is_empty() {
+        panic!("The SLHA files are not equivalent:\n  {}", differences.join("\n  "));
+    }
+}
+
+/// Lists all differences between two SLHA files, as described for `assert_equivalent`.
+fn differences(a: &Slha, b: &Slha, tol: f64) -> Vec<String> {
+    let mut differences = Vec::new();
+    let mut names: Vec<_> = a.blocks.keys().chain(b.blocks.keys()).collect();
+    names.sort();
+    names.dedup();
+    for name in names {
+        let (blocks_a, blocks_b) = match (a.blocks.get(name), b.blocks.get(name)) {
+            (Some(blocks_a), Some(blocks_b)) => (blocks_a, blocks_b),
+            (Some(_), None) => {
+                differences.push(format!("Block '{}' is only in the first file", name));
+                continue;
+            }
+            (None, _) => {
+                differences.push(format!("Block '{}' is only in the second file", name));
+                continue;
+            }
+        };
+        if blocks_a.len() != blocks_b.len() {
+            differences.push(format!(
+                "Block '{}' appears {} times in the first file, but {} times in the second",
+                name,
+                blocks_a.len(),
+                blocks_b.len()
+            ));
+            continue;
+        }
+        for (block_a, block_b) in sorted_by_scale(blocks_a).iter().zip(&sorted_by_scale(blocks_b)) {
+            block_differences(name, block_a, block_b, tol, &mut differences);
+        }
+    }
+
+    let decays_a = a.clone_decays();
+    let decays_b = b.clone_decays();
+    let mut pdg_ids: Vec<_> = decays_a.keys().chain(decays_b.keys()).collect();
+    pdg_ids.sort();
+    pdg_ids.dedup();
+    for pdg_id in pdg_ids {
+        let (table_a, table_b) = match (decays_a.get(pdg_id), decays_b.get(pdg_id)) {
+            (Some(table_a), Some(table_b)) => (table_a, table_b),
+            (Some(_), None) => {
+                differences.push(format!("Decay table {} is only in the first file", pdg_id));
+                continue;
+            }
+            (None, _) => {
+                differences.push(format!("Decay table {} is only in the second file", pdg_id));
+                continue;
+            }
+        };
+        if !approx_eq(table_a.width, table_b.width, tol) {
+            differences.push(format!(
+                "Decay table {} has the width {} in the first file, but {} in the second",
+                pdg_id,
+                table_a.width,
+                table_b.width
+            ));
+        }
+        let channels = |table: &DecayTable| {
+            let mut channels: BTreeMap<Vec<i64>, Vec<f64>> = BTreeMap::new();
+            for decay in &table.decays {
+                let mut daughters = decay.daughters.clone();
+                daughters.sort();
+                channels.entry(daughters).or_default().push(decay.branching_ratio);
+            }
+            for brs in channels.values_mut() {
+                brs.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+            }
+            channels
+        };
+        for (key, brs_a, brs_b) in pair_entries(&channels(table_a), &channels(table_b)) {
+            match (brs_a, brs_b) {
+                (Some(brs_a), Some(brs_b)) if brs_a.len() != brs_b.len() => {
+                    differences.push(format!(
+                        "Decay {} -> {:?} appears {} times in the first file, but {} times in \
+                         the second",
+                        pdg_id,
+                        key,
+                        brs_a.len(),
+                        brs_b.len()
+                    ))
+                }
+                (Some(brs_a), Some(brs_b)) => {
+                    for (&br_a, &br_b) in brs_a.iter().zip(brs_b) {
+                        if !approx_eq(br_a, br_b, tol) {
+                            differences.push(format!(
+                                "Decay {} -> {:?} has the branching ratio {} in the first file, \
+                                 but {} in the second",
+                                pdg_id,
+                                key,
+                                br_a,
+                                br_b
+                            ))
+                        }
+                    }
+                }
+                (_, None) => {
+                    differences.push(format!(
+                        "Decay {} -> {:?} is only in the first file",
+                        pdg_id,
+                        key
+                    ))
+                }
+                (None, _) => {
+                    differences.push(format!(
+                        "Decay {} -> {:?} is only in the second file",
+                        pdg_id,
+                        key
+                    ))
+                }
+            }
+        }
+    }
+    differences
+}
+
+fn sorted_by_scale<'s, 'a>(blocks: &'s [RawBlock<'a>]) -> Vec<&'s RawBlock<'a>> {
+    let mut blocks: Vec<_> = blocks.iter().collect();
+    blocks.sort_by(|a, b| a.scale.partial_cmp(&b.scale).unwrap_or(Ordering::Equal));
+    blocks
+}
+
+fn block_differences(
+    name: &str,
+    a: &RawBlock,
+    b: &RawBlock,
+    tol: f64,
+    differences: &mut Vec<String>,
+) {
+    let same_scale = match (a.scale, b.scale) {
+        (Some(q_a), Some(q_b)) => approx_eq(q_a, q_b, tol),
+        (q_a, q_b) => q_a == q_b,
+    };
+    if !same_scale {
+        differences.push(format!(
+            "Block '{}' has the scale {:?} in the first file, but {:?} in the second",
+            name,
+            a.scale,
+            b.scale
+        ));
+        return;
+    }
+    let entries = |block: &RawBlock| {
+        let mut entries: BTreeMap<Vec<i64>, Vec<Vec<String>>> = BTreeMap::new();
+        for line in &block.lines {
+            let (key, value) = split_entry(line.data);
+            entries.entry(key).or_default().push(value);
+        }
+        for values in entries.values_mut() {
+            values.sort_by(|a, b| compare_words(&a.join(" "), &b.join(" ")));
+        }
+        entries
+    };
+    for (key, values_a, values_b) in pair_entries(&entries(a), &entries(b)) {
+        match (values_a, values_b) {
+            (Some(values_a), Some(values_b)) if values_a.len() != values_b.len() => {
+                differences.push(format!(
+                    "Entry {:?} of block '{}' appears {} times in the first file, but {} times in \
+                     the second",
+                    key,
+                    name,
+                    values_a.len(),
+                    values_b.len()
+                ));
+            }
+            (Some(values_a), Some(values_b)) => {
+                // Values that are in both files are removed first, so that only the ones that
+                // actually differ are reported.
+                let mut unmatched_b: Vec<_> = values_b.iter().collect();
+                let mut unmatched_a = Vec::new();
+                for value_a in values_a {
+                    match unmatched_b.iter().position(|value_b| words_eq(value_a, value_b, tol)) {
+                        Some(i) => {
+                            unmatched_b.remove(i);
+                        }
+                        None => unmatched_a.push(value_a),
+                    }
+                }
+                for (value_a, value_b) in unmatched_a.into_iter().zip(unmatched_b) {
+                    differences.push(format!(
+                        "Entry {:?} of block '{}' is '{}' in the first file, but '{}' in the \
+                         second",
+                        key,
+                        name,
+                        value_a.join(" "),
+                        value_b.join(" ")
+                    ));
+                }
+            }
+            (_, None) => {
+                differences.push(format!(
+                    "Entry {:?} of block '{}' is only in the first file",
+                    key,
+                    name
+                ))
+            }
+            (None, _) => {
+                differences.push(format!(
+                    "Entry {:?} of block '{}' is only in the second file",
+                    key,
+                    name
+                ))
+            }
+        }
+    }
+}
+
+/// Compares the values of two entries word by word.
+///
+/// Words that can both be read as numbers are compared up to the relative tolerance `tol`.
+fn words_eq(a: &[String], b: &[String], tol: f64) -> bool {
+    a.len() == b.len() &&
+        a.iter().zip(b).all(|(word_a, word_b)| {
+            match (f64::parse(word_a).end(), f64::parse(word_b).end()) {
+                (Ok(x), Ok(y)) => approx_eq(x, y, tol),
+                _ => word_a == word_b,
+            }
+        })
+}
+
+/// Splits a data line into its integer keys and the remaining words.
+///
+/// A word is an integer key if its value is an integer, independent of how it is written, so
+/// that e.g. `100` and `1.0E+02` are the same key.
+/// The last word is always part of the value, even if it is an integer.
+fn split_entry(data: &str) -> (Vec<i64>, Vec<String>) {
+    // Above this all floating point numbers are integers, so they are not used as keys.
+    const MAX_KEY: f64 = 9007199254740992.;
+    let words: Vec<_> = data.split_whitespace().collect();
+    let mut keys = Vec::new();
+    for word in &words[..words.len().saturating_sub(1)] {
+        if let Ok(key) = word.parse() {
+            keys.push(key);
+            continue;
+        }
+        match word.parse::<f64>() {
+            Ok(key) if key.fract() == 0. && key.abs() < MAX_KEY => keys.push(key as i64),
+            _ => break,
+        }
+    }
+    let value = words[keys.len()..].iter().map(|word| word.to_string()).collect();
+    (keys, value)
+}
+
+/// Pairs the entries of two maps by their keys.
+///
+/// Keys that are only present in one of the maps are paired with `None`.
+fn pair_entries<'m, K: Ord, V>(
+    a: &'m BTreeMap<K, V>,
+    b: &'m BTreeMap<K, V>,
+) -> Vec<(&'m K, Option<&'m V>, Option<&'m V>)> {
+    let mut pairs: Vec<_> = a.iter().map(|(key, value)| (key, Some(value), b.get(key))).collect();
+    pairs.extend(
+        b.iter().filter(|&(key, _)| !a.contains_key(key)).map(|(key, value)| (key, None, Some(value))),
+    );
+    pairs
+}
+
+fn approx_eq(x: f64, y: f64, tol: f64) -> bool {
+    x == y || (x - y).abs() <= tol * x.abs().max(y.abs())
+}
+
+/// Returns the standard name of a block if its name is listed in `KNOWN_BLOCK_ALIASES`.
+///
+/// The standard name is returned in upper case if the alias is written in upper case, which is
+/// only relevant if the block names are case sensitive.
+fn standard_block_name(name: String) -> String {
+    let standard = KNOWN_BLOCK_ALIASES.iter().find(|&&(alias, _)| {
+        alias.eq_ignore_ascii_case(&name)
+    });
+    match standard {
+        Some(&(_, standard)) if name.chars().all(|c| !c.is_lowercase()) => standard.to_uppercase(),
+        Some(&(_, standard)) => standard.to_string(),
+        None => name,
+    }
+}
+
+fn find_duplicates(mut list: Vec<f64>, eps: f64) -> Option<f64> {
+    if list.len() < 2 {
+        return None;
+    }
+    list.sort_unstable_by(|e1, e2| e1.partial_cmp(e2).unwrap());
+    for (e1, e2) in list.iter().zip(list.iter().skip(1)) {
+        if e1 == e2 || e2 - e1 <= eps {
+            return Some(*e1);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Slha, Block, BlockSingle, BlockStr, Parseable, ParseOptions, ParseResult,
+                ParseStats, BlockReport, TrailingInput, TrailingPolicy, Decay, DecayFormat, DecayTable, DuplicatePolicy, Line, MergePolicy, MergedBlock,
+                ScaleGrid, SlhaDeserialize, assert_equivalent, differences};
+    use super::errors::{Error, ErrorKind};
+
+    #[test]
+    fn test_parse_tuple() {
+        macro_rules! unwrap_parseresult {
+            ($result:expr) => {
+                match $result {
+                    ParseResult::Done(rest, value) => (rest, value),
+                    ParseResult::Error(err) => panic!(err),
+                }
+            }
+        }
+        type T2 = (u8, u8);
+        assert_eq!(unwrap_parseresult!(T2::parse("1 2")), ("", (1, 2)));
+        assert_eq!(unwrap_parseresult!(T2::parse("    1 2")), ("", (1, 2)));
+        assert_eq!(unwrap_parseresult!(T2::parse("1 2   456")), (
+            "   456",
+            (1, 2),
+        ));
+        assert_eq!(unwrap_parseresult!(T2::parse(" 1    2      foobar")), (
+            "      foobar",
+            (1, 2),
+        ));
+    }
+
+    #[test]
+    fn test_parse_int_plus_sign() {
+        macro_rules! check_plus_sign {
+            ($($int:ty),*) => {
+                $(
+                    match <$int>::parse("+6  rest") {
+                        ParseResult::Done(rest, value) => {
+                            assert_eq!(rest, "  rest");
+                            assert_eq!(value, 6);
+                        }
+                        ParseResult::Error(err) => panic!("Unexpected error: {}", err),
+                    }
+                    match <$int>::parse("++6") {
+                        ParseResult::Done(_, value) => panic!("Unexpected success: {:?}", value),
+                        ParseResult::Error(Error(ErrorKind::InvalidInt(_), _)) => (),
+                        ParseResult::Error(err) => panic!("Wrong error variant {:?}", err),
+                    }
+                )*
+            }
+        }
+        check_plus_sign!(i8, i16, i32, i64, u8, u16, u32, u64);
+
+        let input = "\
+BLOCK TEST
+    +1    0
+    +2    0
+    +3   +8
+";
+        let slha = Slha::parse(input).unwrap();
+        let test: Block<i64, i64> = slha.get_block("test").unwrap().unwrap();
+        assert_eq!(test.map[&1], 0);
+        assert_eq!(test.map[&3], 8);
+    }
+
+    #[test]
+    fn test_parse_float_negative_zero_and_tiny() {
+        match f64::parse("-0.00000000E+00") {
+            ParseResult::Done(_, value) => {
+                assert_eq!(value, 0.);
+                assert!(value.is_sign_negative());
+            }
+            ParseResult::Error(err) => panic!("Unexpected error: {}", err),
+        }
+        match f64::parse("1e-300") {
+            ParseResult::Done(_, value) => assert_eq!(value, 1e-300),
+            ParseResult::Error(err) => panic!("Unexpected error: {}", err),
+        }
+
+        let input = "\
+DECAY   1000022   1.00000000E-300
+    -0.00000000E+00   2   22   1000039
+";
+        let slha = Slha::parse(input).unwrap();
+        let neutralino = slha.get_decay(1000022).unwrap();
+        assert_eq!(neutralino.width, 1e-300);
+        let decay = &neutralino.decays[0];
+        assert!(decay.branching_ratio.is_sign_negative());
+        let line = decay.format(&DecayFormat::default());
+        assert!(line.contains("-0.00000000E+00"));
+        let input = format!("DECAY 1000022 1e-300\n{}\n", line);
+        let reparsed = Slha::parse(&input).unwrap();
+        let br = reparsed.get_decay(1000022).unwrap().decays[0].branching_ratio;
+        assert!(br == 0. && br.is_sign_negative());
+    }
+
+    #[test]
+    fn test_custom_parseable_errors() {
+        use std::num::ParseIntError;
+
+        #[derive(Debug, PartialEq)]
+        enum Sign {
+            Plus,
+            Minus,
+        }
+        impl Parseable for Sign {
+            fn parse<'input>(input: &'input str) -> ParseResult<'input, Sign> {
+                let input = input.trim_start();
+                let (word, rest) = match input.find(char::is_whitespace) {
+                    Some(end) => input.split_at(end),
+                    None => (input, ""),
+                };
+                let sign: i8 = match word.parse() {
+                    Ok(sign) => sign,
+                    Err(err) => return ParseResult::Error(err.into()),
+                };
+                match sign {
+                    1 => ParseResult::Done(rest, Sign::Plus),
+                    -1 => ParseResult::Done(rest, Sign::Minus),
+                    _ => ParseResult::Error(format!("Invalid sign: {}", sign).into()),
+                }
+            }
+        }
+
+        let input = "\
+BLOCK MINPAR
+    4   -1   # sign(mu)
+BLOCK EXTPAR
+    4   +   # sign(mu)
+BLOCK SIGNS
+    4   2
+";
+        let slha = Slha::parse(input).unwrap();
+        let minpar: Block<u8, Sign> = slha.get_block("minpar").unwrap().unwrap();
+        assert_eq!(minpar.map[&4], Sign::Minus);
+        let err = slha.get_block::<Block<u8, Sign>>("extpar").unwrap().unwrap_err();
+        assert!(err.parse_error().unwrap().downcast_ref::<ParseIntError>().is_some());
+        let err = slha.get_block::<Block<u8, Sign>>("signs").unwrap().unwrap_err();
+        assert!(err.parse_error().is_none());
+        match Sign::parse("1.0") {
+            ParseResult::Error(Error(ErrorKind::InvalidInt(_), _)) => (),
+            ParseResult::Error(err) => panic!("Wrong error variant {:?} instead of InvalidInt", err),
+            ParseResult::Done(_, value) => panic!("Unexpected success: {:?}", value),
+        }
+        match ErrorKind::from("x".parse::<f64>().unwrap_err()) {
+            ErrorKind::InvalidFloat(_) => (),
+            kind => panic!("Wrong error variant {:?} instead of InvalidFloat", kind),
+        }
+    }
+
+    #[test]
+    fn test_parse_int_out_of_range() {
+        match i8::parse("200") {
+            ParseResult::Done(_, value) => panic!("Unexpected success: {:?}", value),
+            ParseResult::Error(Error(ErrorKind::IntOutOfRange(_, value, ty), _)) => {
+                assert_eq!(value, "200");
+                assert_eq!(ty, "i8");
+            }
+            ParseResult::Error(err) => panic!("Wrong error variant {:?}", err),
+        }
+        match u8::parse("256") {
+            ParseResult::Done(_, value) => panic!("Unexpected success: {:?}", value),
+            ParseResult::Error(err) => {
+                assert_eq!(err.to_string(), "The integer '256' is out of range for u8")
+            }
+        }
+        match i8::parse("2x") {
+            ParseResult::Done(_, value) => panic!("Unexpected success: {:?}", value),
+            ParseResult::Error(Error(ErrorKind::InvalidInt(_), _)) => (),
+            ParseResult::Error(err) => panic!("Wrong error variant {:?}", err),
+        }
+
+        let input = "\
+BLOCK MODSEL
+    1    1
+  200    3
+";
+        let slha = Slha::parse(input).unwrap();
+        let err = slha.get_block::<Block<i8, i64>>("modsel")
+            .unwrap()
+            .unwrap_err();
+        let messages: Vec<_> = err.iter().map(|err| err.to_string()).collect();
+        assert_eq!(
+            messages[messages.len() - 2..],
+            [
+                "The integer '200' is out of range for i8",
+                "number too large to fit in target type",
+            ]
+        );
+        assert!(err.parse_error().is_some());
+    }
+
+    #[test]
+    fn test_parse_block() {
+        let input = "\
+BLOCK TEST
+ 1 3
+ 4 6
+block Mass
+  6  173.2";
+        let slha = Slha::parse(input).unwrap();
+        println!("{:?}", slha);
+        let block: Block<i64, i64> = slha.get_block("test").unwrap().unwrap();
+        assert_eq!(block.map.len(), 2);
+        assert_eq!(block.map[&1], 3);
+        assert_eq!(block.map[&4], 6);
+        let block: Block<i64, f64> = slha.get_block("mass").unwrap().unwrap();
+        assert_eq!(block.map.len(), 1);
+        assert_eq!(block.map[&6], 173.2);
+    }
+
+    #[test]
+    fn test_parse_raw_blocks() {
+        let input = "\
+BLOCK TEST
+ 1 3
+ 4 6
+block Mass
+  6  173.2
+block Mass
+  5  0.
+  ";
+        let slha = Slha::parse(input).unwrap();
+        println!("{:?}", slha);
+        let blocks = slha.get_raw_blocks("foo");
+        assert_eq!(blocks.len(), 0);
+        let blocks = slha.get_raw_blocks("test");
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].lines.len(), 2);
+        assert_eq!(
+            blocks[0].lines[0],
+            Line {
+                data: "1 3",
+                comment: None,
+            }
+        );
+        assert_eq!(
+            blocks[0].lines[1],
+            Line {
+                data: "4 6",
+                comment: None,
+            }
+        );
+        let blocks = slha.get_raw_blocks("mass");
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].lines.len(), 1);
+        assert_eq!(
+            blocks[0].lines[0],
+            Line {
+                data: "6  173.2",
+                comment: None,
+            }
+        );
+        assert_eq!(blocks[1].lines.len(), 1);
+        assert_eq!(
+            blocks[1].lines[0],
+            Line {
+                data: "5  0.",
+                comment: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_block_comment() {
+        let input = "\
+# This block contains information
+# about testing.
+BLOCK TEST # This is the block header
+# Lets put a comment here, because why not
+ 1 3 # Testcase number one
+# How about we separate the two lines here
+ # by two comment lines, one of which is indented
+ 4 6     # Testcase number two
+
+# The masses of all particles
+block Mass
+  6  173.2    # M_top
+# A trailing comment can't hurt, now can it?
+";
+        let slha = Slha::parse(input).unwrap();
+        println!("{:?}", slha);
+        let block: Block<i64, i64> = slha.get_block("test").unwrap().unwrap();
+        assert_eq!(block.map.len(), 2);
+        assert_eq!(block.map[&1], 3);
+        assert_eq!(block.map[&4], 6);
+        let block: Block<i64, f64> = slha.get_block("mass").unwrap().unwrap();
+        assert_eq!(block.map.len(), 1);
+        assert_eq!(block.map[&6], 173.2);
+    }
+
+    #[test]
+    fn test_raw_block_retain_lines() {
+        let input = "\
+BLOCK EXTPAR Q= 1000 # Input parameters
+    1   2.5e+02   # M_1
+    # Secret parameters
+    2   5.0e+02   # M_2
+    3   1.2e+03   # M_3
+";
+        let slha = Slha::parse(input).unwrap();
+        let mut extpar = slha.get_raw_blocks("extpar")[0].clone();
+        extpar.retain_lines(|line| !line.data.starts_with("2 "));
+        assert_eq!(extpar.scale, Some(1000.));
+        assert_eq!(
+            extpar.lines,
+            vec![
+                Line {
+                    data: "1   2.5e+02   ",
+                    comment: Some("# M_1"),
+                },
+                Line {
+                    data: "3   1.2e+03   ",
+                    comment: Some("# M_3"),
+                },
+            ]
+        );
+        let block: Block<i64, f64> = extpar.to_block("extpar").unwrap();
+        assert_eq!(block.map.len(), 2);
+        assert!(!block.map.contains_key(&2));
+        assert_eq!(slha.get_raw_blocks("extpar")[0].lines.len(), 3);
+    }
+
+    #[test]
+    fn test_parse_raw_blocks_comment() {
+        let input = "\
+# This block contains information
+# about testing.
+BLOCK TEST # This is the block header
+# Lets put a comment here, because why not
+ 1 3 # Testcase number one
+# How about we separate the two lines here
+ # by two comment lines, one of which is indented
+ 4 6     # Testcase number two
+
+# The masses of all particles
+block Mass
+  6  173.2    # M_top
+block Mass # Why not split the masses?
+  5  0.   #     Mass of the b-quark
+# A trailing comment can't hurt, now can it?
+";
+        let slha = Slha::parse(input).unwrap();
+        println!("{:?}", slha);
+        let blocks = slha.get_raw_blocks("test");
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].lines.len(), 2);
+        assert_eq!(
+            blocks[0].lines[0],
+            Line {
+                data: "1 3 ",
+                comment: Some("# Testcase number one"),
+            }
+        );
+        assert_eq!(
+            blocks[0].lines[1],
+            Line {
+                data: "4 6     ",
+                comment: Some("# Testcase number two"),
+            }
+        );
+        let blocks = slha.get_raw_blocks("mass");
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].lines.len(), 1);
+        assert_eq!(
+            blocks[0].lines[0],
+            Line {
+                data: "6  173.2    ",
+                comment: Some("# M_top"),
+            }
+        );
+        assert_eq!(blocks[1].lines.len(), 1);
+        assert_eq!(
+            blocks[1].lines[0],
+            Line {
+                data: "5  0.   ",
+                comment: Some("#     Mass of the b-quark"),
+            }
+        );
+    }
+
+    #[test]
+    fn test_blank_lines_do_not_terminate_block() {
+        let input = "\
+BLOCK TEST
+ 1 3
+
+ 4 6
+
+# A comment after an empty line
+
+ 5 7
+block Mass
+  6  173.2
+";
+        let slha = Slha::parse(input).unwrap();
+        let blocks = slha.get_raw_blocks("test");
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].lines.len(), 3);
+        assert_eq!(slha.get_raw_blocks("").len(), 0);
+        let block: Block<i64, i64> = slha.get_block("test").unwrap().unwrap();
+        assert_eq!(block.map.len(), 3);
+        assert_eq!(block.map[&5], 7);
+    }
+
+    #[test]
+    fn test_case_sensitive() {
+        let input = "\
+BLOCK Foo
+    1   2.5
+BLOCK foo
+    1   3.5
+BLOCK FOO Q= 100
+    1   4.5
+";
+        assert!(Slha::parse(input).unwrap().get_block::<Block<i8, f64>>("foo").unwrap().is_err());
+        let options = ParseOptions {
+            case_sensitive: true,
+            ..ParseOptions::default()
+        };
+        let slha = Slha::parse_with_options(input, &options).unwrap();
+        let upper: Block<i8, f64> = slha.get_block("Foo").unwrap().unwrap();
+        assert_eq!(upper.map[&1], 2.5);
+        let lower: Block<i8, f64> = slha.get_block("foo").unwrap().unwrap();
+        assert_eq!(lower.map[&1], 3.5);
+        let all_caps: Vec<Block<i8, f64>> = slha.get_blocks("FOO").unwrap();
+        assert_eq!(all_caps.len(), 1);
+        assert_eq!(all_caps[0].scale, Some(100.));
+        assert!(slha.get_block::<Block<i8, f64>>("fOO").is_none());
+        assert!(slha.get_raw_blocks("fOo").is_empty());
+    }
+
+    #[test]
+    fn test_br_as_percent() {
+        let input = "\
+DECAY   25   4.07e-03
+    57.75    2    5   -5
+    21.53    2    24   -24
+    20.72    2    15   -15
+";
+        let options = ParseOptions {
+            br_as_percent: true,
+            ..ParseOptions::default()
+        };
+        let slha = Slha::parse_with_options(input, &options).unwrap();
+        let higgs = slha.get_decay(25).unwrap();
+        assert_eq!(higgs.width, 4.07e-03);
+        assert_eq!(higgs.decays.len(), 3);
+        assert_eq!(higgs.decays[1].daughters, vec![24, -24]);
+        assert!((higgs.decays[0].branching_ratio - 0.5775).abs() < 1e-12);
+        let sum: f64 = higgs.decays.iter().map(|decay| decay.branching_ratio).sum();
+        assert!((sum - 1.).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_on_trailing() {
+        let input = "\
+Block MASS
+     6    173.2    pole
+     5    4.25
+    24    80.4     pole   PDG   # W boson
+Block ALPHA
+    -1.13825210e-01   extra
+Block YE Q= 4.64649125e+02
+    3  3  9.97405356e-02
+";
+        let err = Slha::parse(input).unwrap().get_block::<Block<i64, f64>>("mass").unwrap()
+            .unwrap_err();
+        match err {
+            Error(ErrorKind::InvalidBlock(ref name), _) => assert_eq!(name, "mass"),
+            ref err => panic!("Wrong error variant {:?} instead of InvalidBlock", err),
+        }
+        assert_eq!(
+            err.iter().nth(3).unwrap().to_string(),
+            "The parser did not consume the whole line, '    pole' was left over"
+        );
+
+        let options = ParseOptions {
+            on_trailing: TrailingPolicy::Ignore,
+            ..ParseOptions::default()
+        };
+        let slha = Slha::parse_with_options(input, &options).unwrap();
+        let mass: Block<i64, f64> = slha.get_block("mass").unwrap().unwrap();
+        assert_eq!(mass.map.len(), 3);
+        assert_eq!(mass.map[&6], 173.2);
+        assert_eq!(mass.map[&24], 80.4);
+        let alpha: BlockSingle<f64> = slha.get_block("alpha").unwrap().unwrap();
+        assert_eq!(alpha.value, -1.13825210e-01);
+        let ye: Vec<Block<(u8, u8), f64>> = slha.get_blocks("ye").unwrap();
+        assert_eq!(ye[0].map[&(3, 3)], 9.97405356e-02);
+        let (_, trailing) = slha.get_block_with_trailing::<Block<i64, f64>>("mass")
+            .unwrap()
+            .unwrap();
+        assert!(trailing.is_empty());
+        // Input that is not left over at the end of a line is still an error.
+        let err = slha.get_block::<Block<(u8, u8), u8>>("ye").unwrap().unwrap_err();
+        match err {
+            Error(ErrorKind::InvalidBlock(ref name), _) => assert_eq!(name, "ye"),
+            ref err => panic!("Wrong error variant {:?} instead of InvalidBlock", err),
+        }
+
+        let options = ParseOptions {
+            on_trailing: TrailingPolicy::Capture,
+            ..ParseOptions::default()
+        };
+        let slha = Slha::parse_with_options(input, &options).unwrap();
+        let (mass, trailing) = slha.get_block_with_trailing::<Block<i64, f64>>("mass")
+            .unwrap()
+            .unwrap();
+        assert_eq!(mass.map[&5], 4.25);
+        assert_eq!(
+            trailing,
+            vec![
+                TrailingInput {
+                    line: 1,
+                    input: "pole".to_string(),
+                },
+                TrailingInput {
+                    line: 3,
+                    input: "pole   PDG".to_string(),
+                },
+            ]
+        );
+        let (_, trailing) = slha.get_block_with_trailing::<BlockSingle<f64>>("alpha")
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            trailing,
+            vec![
+                TrailingInput {
+                    line: 1,
+                    input: "extra".to_string(),
+                },
+            ]
+        );
+        // Errors involving several lines are still found after removing the trailing input.
+        let input = "Block MASS\n    6   173.2   pole\n    6   172.5\n";
+        let slha = Slha::parse_with_options(input, &options).unwrap();
+        let err = slha.get_block_with_trailing::<Block<i64, f64>>("mass")
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(
+            err.iter().nth(1).unwrap().to_string(),
+            "The key in line 2 appears more than once in the block"
+        );
+    }
+
+    #[test]
+    fn test_skip_block_header_row() {
+        let input = "\
+Block NMIX
+    i   j   value        # Neutralino mixing matrix
+    1   1   -9.86e-01
+    1   2    1.05e-02
+Block MASS
+    6    173.2
+Block EMPTY
+";
+        let options = ParseOptions {
+            skip_block_header_row: true,
+            ..ParseOptions::default()
+        };
+        let slha = Slha::parse_with_options(input, &options).unwrap();
+        let nmix: Block<(u8, u8), f64> = slha.get_block("nmix").unwrap().unwrap();
+        assert_eq!(nmix.map.len(), 2);
+        assert_eq!(nmix.map[&(1, 1)], -9.86e-01);
+        assert_eq!(nmix.map[&(1, 2)], 1.05e-02);
+        let mass: Block<i64, f64> = slha.get_block("mass").unwrap().unwrap();
+        assert_eq!(mass.map[&6], 173.2);
+        assert!(slha.get_raw_blocks("empty")[0].lines.is_empty());
+
+        let slha = Slha::parse(input).unwrap();
+        assert_eq!(slha.get_raw_blocks("nmix")[0].lines.len(), 3);
+        assert!(slha.get_block::<Block<(u8, u8), f64>>("nmix").unwrap().is_err());
+    }
+
+    #[test]
+    fn test_scale_eps() {
+        let input = "\
+Block ye Q= 4.64649125e+02
+    3  3 9.97405356e-02
+Block ye Q= 464.6491250001
+    3  3 9.97405356e-02
+Block ye Q= 500.
+    3  3 9.97405356e-02
+";
+        let slha = Slha::parse(input).unwrap();
+        let ye: Vec<Block<(u8, u8), f64>> = slha.get_blocks("ye").unwrap();
+        assert_eq!(ye.len(), 3);
+
+        let options = ParseOptions {
+            scale_eps: 1e-6,
+            ..ParseOptions::default()
+        };
+        let slha = Slha::parse_with_options(input, &options).unwrap();
+        match slha.get_blocks::<Block<(u8, u8), f64>>("ye") {
+            Err(Error(ErrorKind::DuplicateBlockScale(name, scale), _)) => {
+                assert_eq!(name, "ye");
+                assert_eq!(scale, 464.649125);
+            }
+            other => panic!("Expected a DuplicateBlockScale error, found {:?}", other),
+        }
+        assert_eq!(slha.get_blocks_unchecked::<Block<(u8, u8), f64>>("ye").unwrap().len(), 3);
+
+        let options = ParseOptions {
+            scale_eps: 1e-12,
+            ..ParseOptions::default()
+        };
+        let slha = Slha::parse_with_options(input, &options).unwrap();
+        assert_eq!(slha.get_blocks::<Block<(u8, u8), f64>>("ye").unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_known_aliases() {
+        let input = "\
+Block FRALPHA   # Effective Higgs mixing angle
+    -1.13825210e-01
+Block MASS
+    6    173.2
+";
+        let options = ParseOptions {
+            known_aliases: true,
+            ..ParseOptions::default()
+        };
+        let slha = Slha::parse_with_options(input, &options).unwrap();
+        let alpha: BlockSingle<f64> = slha.get_block("alpha").unwrap().unwrap();
+        assert_eq!(alpha.value, -1.13825210e-01);
+        assert!(slha.get_raw_blocks("fralpha").is_empty());
+        let mass: Block<i64, f64> = slha.get_block("mass").unwrap().unwrap();
+        assert_eq!(mass.map[&6], 173.2);
+
+        let slha = Slha::parse(input).unwrap();
+        assert!(slha.get_block::<BlockSingle<f64>>("alpha").is_none());
+        assert_eq!(slha.get_raw_blocks("fralpha").len(), 1);
+
+        let options = ParseOptions {
+            known_aliases: true,
+            case_sensitive: true,
+            ..ParseOptions::default()
+        };
+        let slha = Slha::parse_with_options(input, &options).unwrap();
+        assert_eq!(slha.get_raw_blocks("ALPHA").len(), 1);
+        let slha = Slha::parse_with_options("Block FRAlpha\n    -0.11\n", &options).unwrap();
+        assert_eq!(slha.get_raw_blocks("alpha").len(), 1);
+
+        // Both names are read into the same block.
+        let input = "Block ALPHA\n    -0.11\nBlock FRALPHA\n    -0.12\n";
+        let slha = Slha::parse_with_options(input, &ParseOptions {
+            known_aliases: true,
+            ..ParseOptions::default()
+        }).unwrap();
+        assert_eq!(slha.get_raw_blocks("alpha").len(), 2);
+    }
+
+    #[test]
+    fn test_bare_scale() {
+        let input = "\
+Block ye 464.6
+    3  3 9.97405356e-02
+Block yu   4.646e+02   # Comment
+    3  3 8.9e-01
+Block yd Q= 464.6
+    3  3 1.4e-01
+Block mass
+    6    173.2
+";
+        let options = ParseOptions {
+            bare_scale: true,
+            ..ParseOptions::default()
+        };
+        let slha = Slha::parse_with_options(input, &options).unwrap();
+        for name in &["ye", "yu", "yd"] {
+            let block: Block<(u8, u8), f64> = slha.get_block(name).unwrap().unwrap();
+            assert_eq!(block.scale, Some(464.6));
+        }
+        let mass: Block<i64, f64> = slha.get_block("mass").unwrap().unwrap();
+        assert_eq!(mass.scale, None);
+
+        let err = Slha::parse(input).unwrap_err();
+        match err {
+            Error(ErrorKind::InvalidBlock(ref name), _) => assert_eq!(name, "ye"),
+            ref err => panic!("Wrong error variant {:?} instead of InvalidBlock", err),
+        }
+        assert_eq!(
+            err.iter().nth(1).unwrap().to_string(),
+            "Encountered trailing non-whitespace characters after block header: ' 464.6'"
+        );
+
+        // Only a single number is read as the scale.
+        let err = Slha::parse_with_options("Block ye 464.6 1.0\n    3  3 1.0\n", &options)
+            .unwrap_err();
+        match err {
+            Error(ErrorKind::InvalidBlock(ref name), _) => assert_eq!(name, "ye"),
+            ref err => panic!("Wrong error variant {:?} instead of InvalidBlock", err),
+        }
+    }
+
+    #[test]
+    fn test_max_daughters() {
+        let input = "\
+DECAY   6   1.35
+    0.9    2    5   24
+    0.1    200    5   24
+";
+        let options = ParseOptions {
+            max_daughters: Some(4),
+            ..ParseOptions::default()
+        };
+        let err = Slha::parse_with_options(input, &options).unwrap_err();
+        match err {
+            Error(ErrorKind::InvalidDecay(6), _) => (),
+            ref err => panic!("Wrong error variant {:?} instead of InvalidDecay", err),
+        }
+        assert_eq!(
+            err.iter().nth(2).unwrap().to_string(),
+            "The decay has 200 daughter particles, but at most 4 are allowed"
+        );
+        // Without the limit, the line fails because the daughters are missing.
+        let err = Slha::parse(input).unwrap_err();
+        assert_eq!(
+            err.iter().nth(2).unwrap().to_string(),
+            "Did not find enough daughter particles, expected 200 but found 2"
+        );
+
+        let options = ParseOptions {
+            max_daughters: Some(2),
+            ..ParseOptions::default()
+        };
+        let slha = Slha::parse_with_options("DECAY 6 1.35\n    1.0    2    5   24\n", &options)
+            .unwrap();
+        assert_eq!(slha.get_decay(6).unwrap().decays[0].daughters, vec![5, 24]);
+    }
+
+    #[test]
+    fn test_line_continuation() {
+        let input = "\
+BLOCK AUTHORS
+    1   Jane  \\   # First author
+        Doe   \\
+        Junior
+    2   John Doe   # Second author
+    3   Max \\
+        Mustermann   # Third author
+";
+        let options = ParseOptions {
+            line_continuation: true,
+            ..ParseOptions::default()
+        };
+        let slha = Slha::parse_with_options(input, &options).unwrap();
+        let raw = &slha.get_raw_blocks("authors")[0];
+        assert_eq!(raw.lines.len(), 6);
+        assert_eq!(raw.lines[0].data, "1   Jane  \\   ");
+        assert_eq!(raw.lines[0].comment, Some("# First author"));
+        let authors: Block<i8, String> = slha.get_block("authors").unwrap().unwrap();
+        assert_eq!(authors.map.len(), 3);
+        assert_eq!(authors.map[&1], "Jane   Doe    Junior");
+        assert_eq!(authors.map[&2], "John Doe");
+        assert_eq!(authors.map[&3], "Max  Mustermann");
+        let authors: Vec<Block<i8, String>> = slha.get_blocks("authors").unwrap();
+        assert_eq!(authors[0].map[&3], "Max  Mustermann");
+
+        let slha = Slha::parse(input).unwrap();
+        assert_eq!(slha.get_raw_blocks("authors")[0].lines.len(), 6);
+        assert!(slha.get_block::<Block<i8, String>>("authors").unwrap().is_err());
+    }
+
+    #[test]
+    fn test_line_continuation_raw_block() {
+        let input = "\
+BLOCK MASS
+    6   \\
+        173,2   # M_t
+    5   4,25
+    x   1,0
+";
+        let options = ParseOptions {
+            line_continuation: true,
+            decimal_comma: true,
+            ..ParseOptions::default()
+        };
+        let slha = Slha::parse_with_options(input, &options).unwrap();
+        let raw = slha.get_raw_block("mass").unwrap();
+        assert_eq!(raw.lines.len(), 4);
+        assert_eq!(
+            raw.logical_lines(),
+            vec![
+                Line { data: "6    173.2   ", comment: Some("# M_t") },
+                Line { data: "5   4.25", comment: None },
+                Line { data: "x   1.0", comment: None },
+            ]
+        );
+        let mut retained = raw.clone();
+        retained.retain_lines(|line| !line.data.starts_with('x'));
+        assert_eq!(retained.lines.len(), 3);
+        let from_raw: Block<i64, f64> = retained.to_block("mass").unwrap();
+        assert_eq!(from_raw.map[&6], 173.2);
+        assert_eq!(from_raw.map[&5], 4.25);
+        let from_str = retained.to_block_str::<f64>().unwrap();
+        assert_eq!(from_str.map[&vec!["6".to_string()]], 173.2);
+
+        for err in &[
+            raw.to_block::<Block<i64, f64>>("mass").unwrap_err(),
+            raw.to_block_with_policy::<Block<i64, f64>>("mass", TrailingPolicy::Ignore)
+                .unwrap_err(),
+            slha.get_block::<Block<i64, f64>>("mass").unwrap().unwrap_err(),
+        ] {
+            match *err {
+                Error(ErrorKind::InvalidBlock(ref name), _) => assert_eq!(name, "mass"),
+                _ => panic!("Wrong error type"),
+            }
+            assert_eq!(
+                err.iter().nth(1).unwrap().to_string(),
+                "Failed to parse the 4th data line in the body"
+            );
+        }
+    }
+
+    #[test]
+    fn test_line_continuation_stops() {
+        let options = ParseOptions {
+            line_continuation: true,
+            ..ParseOptions::default()
+        };
+        let input = "Block SMINPUTS\n  3 0.118 \\\nBlock MASS\n  6 173.2\n";
+        let slha = Slha::parse_with_options(input, &options).unwrap();
+        let sminputs: Block<i64, f64> = slha.get_block("sminputs").unwrap().unwrap();
+        assert_eq!(sminputs.map.len(), 1);
+        assert_eq!(sminputs.map[&3], 0.118);
+        let mass: Block<i64, f64> = slha.get_block("mass").unwrap().unwrap();
+        assert_eq!(mass.map[&6], 173.2);
+
+        let input = "\
+Block MASS
+    5   4.25 \\
+# A comment ends the continuation
+    6   173.2 \\
+
+    25  125.0 \\
+DECAY 6 1.35
+    1.0   2   5   24
+";
+        let slha = Slha::parse_with_options(input, &options).unwrap();
+        let raw = &slha.get_raw_blocks("mass")[0];
+        assert_eq!(raw.lines[0].data, "5   4.25");
+        assert_eq!(raw.lines[2].data, "25  125.0");
+        let mass: Block<i64, f64> = slha.get_block("mass").unwrap().unwrap();
+        assert_eq!(mass.map.len(), 3);
+        assert_eq!(mass.map[&6], 173.2);
+        assert_eq!(slha.get_decay(6).unwrap().decays[0].daughters, vec![5, 24]);
+    }
+
+    #[test]
+    fn test_decimal_comma() {
+        let input = "\
+BLOCK MASS Q= 9,1e+01
+    6   173,2   # M_t
+    5   4.25
+BLOCK NAMES
+    1   foo,bar
+    2   1,2,3
+DECAY   6    1,35
+    1,0    2    5   24
+";
+        assert!(Slha::parse(input).is_err());
+        let options = ParseOptions {
+            decimal_comma: true,
+            ..ParseOptions::default()
+        };
+        let slha = Slha::parse_with_options(input, &options).unwrap();
+        let mass: Block<i64, f64> = slha.get_block("mass").unwrap().unwrap();
+        assert_eq!(mass.scale, Some(91.));
+        assert_eq!(mass.map[&6], 173.2);
+        assert_eq!(mass.map[&5], 4.25);
+        let names: Block<i64, String> = slha.get_block("names").unwrap().unwrap();
+        assert_eq!(names.map[&1], "foo,bar");
+        assert_eq!(names.map[&2], "1,2,3");
+        let top = slha.get_decay(6).unwrap();
+        assert_eq!(top.width, 1.35);
+        assert_eq!(top.decays[0].branching_ratio, 1.0);
+        assert_eq!(top.decays[0].daughters, vec![5, 24]);
+    }
+
+    #[test]
+    fn test_blank_terminates() {
+        let input = "\
+BLOCK TEST
+ 1 3
+# A comment does not end the block
+ 4 6
+
+ 5 7
+   \t
+ 8 9
+block Mass
+  6  173.2
+
+DECAY   6    1.3
+    0.5    2    3   4
+
+    0.5    2    5   6
+";
+        let options = ParseOptions {
+            blank_terminates: true,
+            ..ParseOptions::default()
+        };
+        let slha = Slha::parse_with_options(input, &options).unwrap();
+        let block: Block<i64, i64> = slha.get_block("test").unwrap().unwrap();
+        assert_eq!(block.map.len(), 2);
+        assert_eq!(block.map[&1], 3);
+        assert_eq!(block.map[&4], 6);
+        let anonymous = slha.get_raw_blocks("");
+        assert_eq!(anonymous.len(), 2);
+        assert_eq!(anonymous[0].scale, None);
+        assert_eq!(
+            anonymous[0].lines,
+            vec![
+                Line {
+                    data: "5 7",
+                    comment: None,
+                },
+            ]
+        );
+        assert_eq!(
+            anonymous[1].lines,
+            vec![
+                Line {
+                    data: "8 9",
+                    comment: None,
+                },
+            ]
+        );
+        let block: Block<i64, f64> = slha.get_block("mass").unwrap().unwrap();
+        assert_eq!(block.map[&6], 173.2);
+        assert_eq!(slha.get_decay(6).unwrap().decays.len(), 2);
+    }
+
+    #[test]
+    fn test_recognize_commented_blocks() {
+        let input = "\
+BLOCK MASS
+    6    173.2
+    25   125.0
+# BLOCK MASS Q= 100   # old spectrum
+#   6    175.0
+# Comments in a commented out block are skipped
+
+#   5    4.25   # bottom
+## Block sminputs
+    3    0.118
+# This comment is not a header
+DECAY   6    1.3
+#   Comments in decay tables are skipped
+    1.0    2    5   24
+";
+        let options = ParseOptions {
+            recognize_commented_blocks: true,
+            ..ParseOptions::default()
+        };
+        let slha = Slha::parse_with_options(input, &options).unwrap();
+        let mass: Block<i64, f64> = slha.get_block("mass").unwrap().unwrap();
+        assert_eq!(mass.map.len(), 2);
+        assert_eq!(mass.map[&6], 173.2);
+        assert!(slha.get_raw_block("sminputs").is_none());
+        let disabled = slha.get_disabled_blocks("mass");
+        assert_eq!(disabled.len(), 1);
+        assert_eq!(disabled[0].scale, Some(100.));
+        assert_eq!(disabled[0].lines.len(), 2);
+        assert_eq!(disabled[0].lines[0].data, "6    175.0");
+        assert_eq!(disabled[0].lines[1].data, "5    4.25   ");
+        assert_eq!(disabled[0].lines[1].comment, Some("# bottom"));
+        let disabled = slha.get_disabled_blocks("SMINPUTS");
+        assert_eq!(disabled.len(), 1);
+        assert_eq!(disabled[0].lines[0].data, "3    0.118");
+        assert_eq!(slha.get_decay(6).unwrap().decays.len(), 1);
+
+        let slha = Slha::parse(input).unwrap();
+        let mass: Block<i64, f64> = slha.get_block("mass").unwrap().unwrap();
+        assert_eq!(mass.map.len(), 3);
+        assert!(slha.get_disabled_blocks("mass").is_empty());
+    }
+
+    #[test]
+    fn test_recognize_commented_blocks_end_active_block() {
+        let options = ParseOptions {
+            recognize_commented_blocks: true,
+            ..ParseOptions::default()
+        };
+        // Only the header has been commented out.
+        let input = "BLOCK SMINPUTS\n 3 0.118\n# BLOCK MASS\n 6 173.2\n";
+        let slha = Slha::parse_with_options(input, &options).unwrap();
+        let sminputs = slha.get_raw_block("sminputs").unwrap();
+        assert_eq!(sminputs.lines.len(), 1);
+        assert_eq!(sminputs.lines[0].data, "3 0.118");
+        let disabled = slha.get_disabled_blocks("mass");
+        assert_eq!(disabled.len(), 1);
+        assert_eq!(disabled[0].lines.len(), 1);
+        assert_eq!(disabled[0].lines[0].data, "6 173.2");
+
+        // Data lines following a commented out block are not part of it.
+        let input = "BLOCK MASS\n 6 173.2\n# BLOCK MASS Q= 100\n# 6 175.0\n 5 4.25\n";
+        match Slha::parse_with_options(input, &options) {
+            Err(Error(ErrorKind::UnexpectedIdent(ref line), _)) => assert_eq!(line, " 5 4.25"),
+            other => panic!("Unexpected result {:?} instead of UnexpectedIdent", other),
+        }
+        let input = "\
+BLOCK MASS
+ 6 173.2
+# BLOCK MASS Q= 100
+# 6 175.0
+BLOCK SMINPUTS
+ 3 0.118
+";
+        let slha = Slha::parse_with_options(input, &options).unwrap();
+        assert_eq!(slha.get_raw_block("mass").unwrap().lines.len(), 1);
+        assert_eq!(slha.get_disabled_blocks("mass")[0].lines[0].data, "6 175.0");
+        assert_eq!(slha.get_raw_block("sminputs").unwrap().lines.len(), 1);
+    }
+
+    #[test]
+    fn test_recognize_commented_blocks_ordinary_comments() {
+        let options = ParseOptions {
+            recognize_commented_blocks: true,
+            ..ParseOptions::default()
+        };
+        // Not a valid block header.
+        let input = "Block MASS\n# Block masses in GeV\n   6 173.2\n";
+        let slha = Slha::parse_with_options(input, &options).unwrap();
+        let mass: Block<i64, f64> = slha.get_block("mass").unwrap().unwrap();
+        assert_eq!(mass.map[&6], 173.2);
+        assert!(slha.get_disabled_blocks("masses").is_empty());
+
+        // A valid header always ends the enclosing block or decay table.
+        let input = "\
+Block MASS
+# Block masses
+   6 173.2
+DECAY 6 1.35
+# Block decays
+    1.0   2   5   24
+";
+        let slha = Slha::parse_with_options(input, &options).unwrap();
+        assert!(slha.get_raw_block("mass").unwrap().lines.is_empty());
+        assert_eq!(slha.get_disabled_blocks("masses")[0].lines[0].data, "6 173.2");
+        assert!(slha.get_decay(6).unwrap().decays.is_empty());
+        assert_eq!(slha.get_disabled_blocks("decays")[0].lines.len(), 1);
+
+        // At the start of the file there is no enclosing block.
+        let input = "# Block masses in GeV\n# BLOCK MASS\n   6 173.2\n";
+        let slha = Slha::parse_with_options(input, &options).unwrap();
+        assert!(slha.get_raw_block("mass").is_none());
+        assert_eq!(slha.get_disabled_blocks("mass")[0].lines[0].data, "6 173.2");
+    }
+
+    #[test]
+    fn test_keep_unknown_segments() {
+        let input = "\
+FOO version 2   # header comment
+first line
+    second line   # with comment
+# a comment
+Block MASS
+    6    173.2
+foo
+DECAY 6 1.3
+    1.0    2    5   24
+";
+        assert!(Slha::parse(input).is_err());
+        let options = ParseOptions {
+            keep_unknown_segments: true,
+            ..ParseOptions::default()
+        };
+        let slha = Slha::parse_with_options(input, &options).unwrap();
+        let segments = slha.extra_segments();
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].0, "FOO");
+        assert_eq!(
+            segments[0].1,
+            vec![
+                Line {
+                    data: "version 2   ",
+                    comment: Some("# header comment"),
+                },
+                Line {
+                    data: "first line",
+                    comment: None,
+                },
+                Line {
+                    data: "second line   ",
+                    comment: Some("# with comment"),
+                },
+            ]
+        );
+        assert_eq!(segments[1].0, "foo");
+        assert!(segments[1].1.is_empty());
+        let mass: Block<i64, f64> = slha.get_block("mass").unwrap().unwrap();
+        assert_eq!(mass.map[&6], 173.2);
+        assert_eq!(slha.get_decay(6).unwrap().decays.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_lenient() {
+        let input = "\
+# The first lines are not part of any block
+    1    2
+    3    4
+BLOCK MASS
+    6    173.2
+
+BLOCK YE Q= foo
+    3  3  9.97405356e-02
+# Another comment
+
+DECAY 6 1.3
+    1.0    2    5   24
+DECAY 6 1.3
+    1.0    2    5   24
+DECAY 25 4.07e-03
+    0.5    x    5   -5
+Block SMINPUTS
+    3    0.1172
+";
+        assert!(Slha::parse(input).is_err());
+        let (slha, errors) = Slha::parse_lenient(input);
+        let lines: Vec<_> = errors.iter().map(|&(_, line)| line).collect();
+        assert_eq!(lines, vec![2, 7, 13, 16]);
+        match errors[0].0 {
+            Error(ErrorKind::UnexpectedIdent(_), _) => (),
+            ref err => panic!("Wrong error variant {:?} instead of UnexpectedIdent", err),
+        }
+        match errors[1].0 {
+            Error(ErrorKind::InvalidBlock(ref name), _) => assert_eq!(name, "ye"),
+            ref err => panic!("Wrong error variant {:?} instead of InvalidBlock", err),
+        }
+        match errors[2].0 {
+            Error(ErrorKind::DuplicateDecay(6), _) => (),
+            ref err => panic!("Wrong error variant {:?} instead of DuplicateDecay", err),
+        }
+        match errors[3].0 {
+            Error(ErrorKind::InvalidDecay(25), _) => (),
+            ref err => panic!("Wrong error variant {:?} instead of InvalidDecay", err),
+        }
+        let mass: Block<i64, f64> = slha.get_block("mass").unwrap().unwrap();
+        assert_eq!(mass.map[&6], 173.2);
+        assert!(slha.get_raw_block("ye").is_none());
+        assert_eq!(slha.get_decay(6).unwrap().width, 1.3);
+        assert!(slha.get_decay(25).is_none());
+        let sminputs: Block<i64, f64> = slha.get_block("sminputs").unwrap().unwrap();
+        assert_eq!(sminputs.map[&3], 0.1172);
+
+        let (slha, errors) = Slha::parse_lenient("");
+        assert!(errors.is_empty());
+        assert_eq!(slha, Slha::parse("").unwrap());
+    }
+
+    #[test]
+    fn test_parse_lenient_with_options() {
+        let input = "\
+BLOCK MASS
+    6    173,2
+DECAY 6 1,3
+    0,5    2    5   24
+# A comment in the body
+
+    0,5    2    x   24
+DECAY 25 4,07e-03
+    1,0    2    5   -5
+";
+        let (slha, errors) = Slha::parse_lenient(input);
+        let lines: Vec<_> = errors.iter().map(|&(_, line)| line).collect();
+        assert_eq!(lines, vec![3, 8]);
+        assert!(slha.get_decay(6).is_none());
+
+        let options = ParseOptions {
+            decimal_comma: true,
+            ..ParseOptions::default()
+        };
+        let (slha, errors) = Slha::parse_lenient_with_options(input, &options);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].1, 7);
+        match errors[0].0 {
+            Error(ErrorKind::InvalidDecay(6), _) => (),
+            ref err => panic!("Wrong error variant {:?} instead of InvalidDecay", err),
+        }
+        let mass: Block<i64, f64> = slha.get_block("mass").unwrap().unwrap();
+        assert_eq!(mass.map[&6], 173.2);
+        assert_eq!(slha.get_decay(25).unwrap().width, 4.07e-03);
+    }
+
+    #[test]
+    fn test_parse_selective() {
+        let input = "\
+# A header comment
+BLOCK MODSEL
+    1    1
+Block SMINPUTS   # Standard Model inputs
+    3    0.1172
+# A comment in the body
+    6    174.3
+BLOCK NMIX Q= foo
+    1  1   0.9
+BLOCK MASS
+    6    173.2
+Block mass Q= 100
+    5    4.25
+DECAY 6 1.35
+    1.0    2    5    24
+DECAY 25 foo
+BLOCK DECAYS
+    1    2
+";
+        assert!(Slha::parse(input).is_err());
+        let slha = Slha::parse_selective(input, &["MASS", "sminputs", "hmix"], false).unwrap();
+        assert_eq!(slha.blocks_matching("*").len(), 3);
+        assert_eq!(slha.get_raw_blocks("mass").len(), 2);
+        let sminputs: Block<i64, f64> = slha.get_block("sminputs").unwrap().unwrap();
+        assert_eq!(sminputs.map.len(), 2);
+        assert!(slha.get_raw_block("modsel").is_none());
+        assert!(slha.get_raw_block("nmix").is_none());
+        assert!(slha.get_raw_block("hmix").is_none());
+        assert!(slha.get_decay(6).is_none());
+        assert_eq!(slha.entries().count(), 4);
+
+        // A block named like the decay tables does not select them.
+        let slha = Slha::parse_selective(input, &["decays"], false).unwrap();
+        assert_eq!(slha.get_raw_block("decays").unwrap().lines.len(), 1);
+        assert!(slha.get_decay(6).is_none());
+        let err = Slha::parse_selective(input, &["mass"], true).unwrap_err();
+        match err {
+            Error(ErrorKind::InvalidDecay(25), _) => (),
+            ref err => panic!("Wrong error variant {:?} instead of InvalidDecay", err),
+        }
+        let err = Slha::parse_selective(input, &["nmix"], false).unwrap_err();
+        match err {
+            Error(ErrorKind::InvalidBlock(ref name), _) => assert_eq!(name, "nmix"),
+            ref err => panic!("Wrong error variant {:?} instead of InvalidBlock", err),
+        }
+    }
+
+    #[test]
+    fn test_parse_selective_with_options() {
+        let input = "\
+BLOCK Mass
+    6    173,2
+# BLOCK HMIX
+#   1    200,0
+BLOCK MASS
+    5    4,25
+# BLOCK Mass Q= 100
+#   6    175,0
+DECAY 6 1,35
+    1,0    2    5    24
+";
+        let options = ParseOptions {
+            case_sensitive: true,
+            decimal_comma: true,
+            recognize_commented_blocks: true,
+            ..ParseOptions::default()
+        };
+        assert!(Slha::parse_selective(input, &["Mass"], true).is_err());
+        let slha = Slha::parse_selective_with_options(input, &["Mass"], true, &options).unwrap();
+        let names: Vec<_> = slha.blocks_matching("*").iter().map(|&(name, _)| name).collect();
+        assert_eq!(names, vec!["Mass"]);
+        let mass: Block<i64, f64> = slha.get_block("Mass").unwrap().unwrap();
+        assert_eq!(mass.map[&6], 173.2);
+        let disabled = slha.get_disabled_blocks("Mass");
+        assert_eq!(disabled.len(), 1);
+        assert_eq!(disabled[0].scale, Some(100.));
+        assert!(slha.get_disabled_blocks("HMIX").is_empty());
+        assert_eq!(slha.get_decay(6).unwrap().width, 1.35);
+    }
+
+    #[test]
+    fn test_parse_multi() {
+        let input = "\
+BLOCK MASS
+   6    173.2
+DECAY 6 1.35
+   1   2   5   24
+ENDSLHA
+BLOCK MASS
+   6    175.0
+   5    4.25
+ENDSLHA
+";
+        let points = Slha::parse_multi(input, "ENDSLHA").unwrap();
+        assert_eq!(points.len(), 2);
+        let mass: Block<i64, f64> = points[0].get_block("mass").unwrap().unwrap();
+        assert_eq!(mass.map.len(), 1);
+        assert_eq!(mass.map[&6], 173.2);
+        assert_eq!(points[0].get_decay(6).unwrap().width, 1.35);
+        let mass: Block<i64, f64> = points[1].get_block("mass").unwrap().unwrap();
+        assert_eq!(mass.map.len(), 2);
+        assert_eq!(mass.map[&6], 175.0);
+        assert!(points[1].get_decay(6).is_none());
+    }
+
+    #[test]
+    fn test_parse_multi_invalid() {
+        let input = "\
+# === point 1 ===
+BLOCK MASS
+   6    173.2
+# === point 2 ===
+DECAY 6
+";
+        // The empty piece before the first separator is counted as well.
+        let err = Slha::parse_multi(input, "# === point").unwrap_err();
+        assert_eq!(err.to_string(), "Failed to parse SLHA document 3");
+        if let Error(ErrorKind::InvalidDocument(n), _) = err {
+            assert_eq!(n, 3);
+        } else {
+            panic!("Wrong error variant {:?} instead of InvalidDocument", err);
+        }
+
+        let input = "\
+BLOCK MASS
+   6    173.2
+ENDSLHA
+
+ENDSLHA
+   6    175.0
+";
+        let err = Slha::parse_multi(input, "ENDSLHA").unwrap_err();
+        if let Error(ErrorKind::InvalidDocument(n), _) = err {
+            assert_eq!(n, 3);
+        } else {
+            panic!("Wrong error variant {:?} instead of InvalidDocument", err);
+        }
+
+        let input = "\
+BLOCK MASS
+   6    173.2
+ENDSLHA
+
+ENDSLHA
+BLOCK Mass
+   6    175,0
+";
+        let options = ParseOptions {
+            decimal_comma: true,
+            case_sensitive: true,
+            ..ParseOptions::default()
+        };
+        let points = Slha::parse_multi_with_options(input, "ENDSLHA", &options).unwrap();
+        assert_eq!(points.len(), 2);
+        let mass: Block<i64, f64> = points[1].get_block("Mass").unwrap().unwrap();
+        assert_eq!(mass.map[&6], 175.0);
+    }
+
+    #[test]
+    fn test_parse_with_stats() {
+        let input = "\
+# This block contains information
+# about testing.
+BLOCK TEST # This is the block header
+# Lets put a comment here, because why not
+ 1 3 # Testcase number one
+ 4 6     # Testcase number two
+
+# The masses of all particles
+block Mass
+  6  173.2    # M_top
+block Mass # Why not split the masses?
+  5  0.   #     Mass of the b-quark
+DECAY   6    1.3
+    0.5    2    3   4
+    0.5    2    5   6
+DECAY   -6    1.3
+    # charge conjugate
+    0.5    2    -3   -4
+    0.5    2    -5   -6
+";
+        let (_, stats) = Slha::parse_with_stats(input).unwrap();
+        assert_eq!(
+            stats,
+            ParseStats {
+                lines: 19,
+                blocks: 3,
+                decays: 2,
+                comments: 5,
+            }
+        );
+
+        let input = "\
+# BLOCK MASS
+#   6  173.2   # M_top
+    # Not a data line
+# BLOCK Mass is the next block
+BLOCK MASS
+   5    4.25
+   # BLOCK in a comment
+# Trailing comment
+";
+        let (_, stats) = Slha::parse_with_stats(input).unwrap();
+        assert_eq!(stats.blocks, 1);
+        assert_eq!(stats.comments, 6);
+        let options = ParseOptions {
+            recognize_commented_blocks: true,
+            ..ParseOptions::default()
+        };
+        let (slha, stats) = Slha::parse_with_stats_with_options(input, &options).unwrap();
+        assert_eq!(
+            stats,
+            ParseStats {
+                lines: 8,
+                blocks: 1,
+                decays: 0,
+                comments: 4,
+            }
+        );
+        assert_eq!(slha.disabled_blocks.len(), 1);
+    }
+
+    #[test]
+    fn test_get_block_case() {
+        let input = "\
+BLOCK TEST
+ 1 3
+ 4 6
+";
+        let slha = Slha::parse(input).unwrap();
+        println!("{:?}", slha);
+        let _: Block<i64, i64> = slha.get_block("TEST").unwrap().unwrap();
+        let _: Block<i64, i64> = slha.get_block("tEsT").unwrap().unwrap();
+        let _: Block<i64, i64> = slha.get_block("TesT").unwrap().unwrap();
+        let block: Block<i64, i64> = slha.get_block("test").unwrap().unwrap();
+        assert_eq!(block.map.len(), 2);
+        assert_eq!(block.map[&1], 3);
+        assert_eq!(block.map[&4], 6);
+    }
+
+    #[test]
+    fn test_parse_decay_table() {
+        let input = "\
+DECAY   6    1.3
+    0.5    2    3   4
+    0.25    3    4   5  6
+    0.25    4    5   6  7  8
+";
+        let slha = Slha::parse(input).unwrap();
+        let dec = slha.get_decay(6).unwrap();
+        assert_eq!(dec.width, 1.3);
+        assert_eq!(dec.decays.len(), 3);
+        assert_eq!(
+            dec.decays[0],
+            Decay {
+                branching_ratio: 0.5,
+                daughters: vec![3, 4],
+            }
+        );
+        assert_eq!(
+            dec.decays[1],
+            Decay {
+                branching_ratio: 0.25,
+                daughters: vec![4, 5, 6],
+            }
+        );
+        assert_eq!(
+            dec.decays[2],
+            Decay {
+                branching_ratio: 0.25,
+                daughters: vec![5, 6, 7, 8],
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_decay_table_line_comments() {
+        let input = "\
+DECAY   6    1.3   # top quark decays
+    0.5    2    3   4   # BR(t -> 3 4)
+    0.25    3    4   5  6 # BR(t -> 4 5 6)
+    0.25    4    5   6  7  8      # BR(t -> 5 6 7 8)
+";
+        let slha = Slha::parse(input).unwrap();
+        let dec = slha.get_decay(6).unwrap();
+        assert_eq!(dec.width, 1.3);
+        assert_eq!(dec.decays.len(), 3);
+        assert_eq!(
+            dec.decays[0],
+            Decay {
+                branching_ratio: 0.5,
+                daughters: vec![3, 4],
+            }
+        );
+        assert_eq!(
+            dec.decays[1],
+            Decay {
+                branching_ratio: 0.25,
+                daughters: vec![4, 5, 6],
+            }
+        );
+        assert_eq!(
+            dec.decays[2],
+            Decay {
+                branching_ratio: 0.25,
+                daughters: vec![5, 6, 7, 8],
+            }
+        );
+    }
+
+    #[test]
+    fn test_decay_display() {
+        // Line from the decay table of the gluino written by SDECAY
+        let decay = Decay {
+            branching_ratio: 2.08271916e-02,
+            daughters: vec![1000001, -1],
+        };
+        assert_eq!(decay.to_string(), "     2.08271916E-02    2     1000001        -1");
+        let table = DecayTable { width: 1.14378450, decays: vec![decay] };
+        assert_eq!(
+            table.format(1000021, &DecayFormat::default()).lines().next().unwrap(),
+            "DECAY   1000021     1.14378450E+00"
+        );
+        let decay = Decay {
+            branching_ratio: 1.,
+            daughters: vec![1000022, 11, -12],
+        };
+        assert_eq!(
+            decay.to_string(),
+            "     1.00000000E+00    3     1000022        11       -12"
+        );
+    }
+
+    #[test]
+    fn test_decay_table_format_round_trip() {
+        let tables = vec![
+            // A stable particle without any decays
+            (1000022, DecayTable { width: 0., decays: Vec::new() }),
+            // Modes with several daughters and negative pdg ids
+            (
+                -1000024,
+                DecayTable {
+                    width: 1.70414503e-02,
+                    decays: vec![
+                        Decay {
+                            branching_ratio: 9.76149321e-01,
+                            daughters: vec![-1000022, -211],
+                        },
+                        Decay {
+                            branching_ratio: 2.38506791e-02,
+                            daughters: vec![-1000022, 11, -12],
+                        },
+                        Decay {
+                            branching_ratio: 9.99999e-11,
+                            daughters: vec![-1000022, 1, -2, 21],
+                        },
+                    ],
+                },
+            ),
+            (
+                6,
+                DecayTable {
+                    width: 1.35,
+                    decays: vec![
+                        Decay {
+                            branching_ratio: 1.,
+                            daughters: vec![5, 24],
+                        },
+                    ],
+                },
+            ),
+        ];
+        let mut input = String::new();
+        for &(pdg_id, ref table) in &tables {
+            input.push_str(&table.format(pdg_id, &DecayFormat::default()));
+        }
+        assert_eq!(
+            input.lines().take(4).collect::<Vec<_>>(),
+            vec![
+                "DECAY   1000022     0.00000000E+00",
+                "DECAY  -1000024     1.70414503E-02",
+                "     9.76149321E-01    2    -1000022      -211",
+                "     2.38506791E-02    3    -1000022        11       -12",
+            ]
+        );
+        let slha = Slha::parse(&input).unwrap();
+        for &(pdg_id, ref table) in &tables {
+            assert_eq!(slha.get_decay(pdg_id), Some(table));
+        }
+    }
+
+    #[test]
+    fn test_decay_format() {
+        let decay = Decay {
+            branching_ratio: 1.5e-123,
+            daughters: vec![5, 24],
+        };
+        let format = DecayFormat {
+            indent: 4,
+            branching_ratio_width: 0,
+            precision: 3,
+            nda_width: 3,
+            daughters_indent: 1,
+            daughter_width: 4,
+        };
+        assert_eq!(decay.format(&format), "    1.500E-123  2    5  24");
+    }
+
+    #[test]
+    fn test_get_decay_abs() {
+        let input = "\
+DECAY   6    1.3
+    1.0    2    5   24
+DECAY   -1000024    0.7
+    1.0    2    -1000022   -24
+DECAY   1000024    0.8
+    1.0    2    1000022   24
+";
+        let slha = Slha::parse(input).unwrap();
+        assert!(slha.get_decay(-6).is_none());
+        assert_eq!(slha.get_decay_abs(-6), slha.get_decay(6));
+        assert_eq!(slha.get_decay_abs(6), slha.get_decay(6));
+        assert_eq!(slha.get_decay_abs(1000024).unwrap().width, 0.8);
+        assert_eq!(slha.get_decay_abs(-1000024).unwrap().width, 0.7);
+        assert!(slha.get_decay_abs(5).is_none());
+    }
+
+    #[test]
+    fn test_decay_pdg_ids() {
+        let input = "\
+BLOCK MASS
+    6   173.2
+DECAY   6    1.3
+    1.0    2    5   24
+DECAY   -1000024    0.7
+    1.0    2    -1000022   -24
+DECAY   1000024    0.8
+    1.0    2    1000022   24
+";
+        let slha = Slha::parse(input).unwrap();
+        let mut ids: Vec<_> = slha.decay_pdg_ids().collect();
+        ids.sort();
+        assert_eq!(ids, vec![-1000024, 6, 1000024]);
+        assert_eq!(Slha::parse("").unwrap().decay_pdg_ids().count(), 0);
+    }
+
+    #[test]
+    fn test_tab_indented_comments() {
+        let input = "\
+\t# A comment before the first segment
+BLOCK MASS
+\t# A comment inside of a block
+    6   173.2
+\t\t# Another comment inside of a block
+    5   4.25
+  \t# A comment between two segments
+DECAY   6    1.3
+\t# A comment inside of a decay table
+    0.5    2    3   4
+\t  \t# Another comment inside of a decay table
+    0.5    2    5   6
+\t# A comment at the end
+";
+        let slha = Slha::parse(input).unwrap();
+        let mass: Block<i64, f64> = slha.get_block("mass").unwrap().unwrap();
+        assert_eq!(mass.map.len(), 2);
+        assert_eq!(mass.map[&6], 173.2);
+        assert_eq!(mass.map[&5], 4.25);
+        let dec = slha.get_decay(6).unwrap();
+        assert_eq!(dec.width, 1.3);
+        assert_eq!(dec.decays.len(), 2);
+        assert_eq!(dec.decays[1].daughters, vec![5, 6]);
+
+        let options = ParseOptions {
+            blank_terminates: true,
+            ..ParseOptions::default()
+        };
+        let slha = Slha::parse_with_options(input, &options).unwrap();
+        let mass: Block<i64, f64> = slha.get_block("mass").unwrap().unwrap();
+        assert_eq!(mass.map.len(), 2);
+        assert!(slha.get_raw_blocks("").is_empty());
+        assert_eq!(slha.get_decay(6).unwrap().decays.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_blocks_only() {
+        let input = "\
+BLOCK MASS
+    6   173.2
+DECAY   6    1.3   # top quark decays
+    0.5    2    5   24   # t -> b W+
+    0.5    2    3   24
+decay   1000021    0.7
+    1.0    2    1000022   21
+BLOCK MASS
+    5   4.25
+";
+        let eager = Slha::parse(input).unwrap();
+        let lazy = Slha::parse_blocks_only(input, &ParseOptions::default()).unwrap();
+        assert_eq!(lazy.get_raw_blocks("mass"), eager.get_raw_blocks("mass"));
+        let mut ids: Vec<_> = lazy.decay_pdg_ids().collect();
+        ids.sort();
+        assert_eq!(ids, vec![6, 1000021]);
+        assert_eq!(lazy.get_decay(6), eager.get_decay(6));
+        // The second lookup returns the cached table.
+        assert_eq!(lazy.get_decay(6), eager.get_decay(6));
+        assert_eq!(lazy.get_decay_abs(-1000021), eager.get_decay(1000021));
+        assert!(lazy.get_decay(25).is_none());
+        assert_eq!(lazy.clone().into_decays(), eager.clone().into_decays());
+    }
+
+    #[test]
+    fn test_parse_blocks_only_invalid() {
+        let input = "\
+DECAY   6    1.3
+    0.5    2    5   24
+    0.5    3    3   24
+";
+        let slha = Slha::parse_blocks_only(input, &ParseOptions::default()).unwrap();
+        assert!(slha.get_decay(6).is_none());
+        let err = match slha.try_get_decay(6) {
+            Some(Err(err @ Error(ErrorKind::InvalidDecay(6), _))) => err,
+            Some(Err(err)) => panic!("Wrong error variant {:?} instead of InvalidDecay", err),
+            Some(Ok(table)) => panic!("Unexpected success: {:?}", table),
+            None => panic!("Missing decay table"),
+        };
+        // The failure is cached and the same error is returned on every access.
+        let again = slha.try_get_decay(6).unwrap().unwrap_err();
+        assert_eq!(again.to_string(), err.to_string());
+        assert_eq!(
+            again.iter().map(|e| e.to_string()).collect::<Vec<_>>(),
+            err.iter().map(|e| e.to_string()).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            err.iter().nth(1).unwrap().to_string(),
+            "Failed to parse the 2th data line in the body"
+        );
+        assert!(slha.clone_decays().is_empty());
+        assert!(slha.into_decays().is_empty());
+
+        let input = "\
+DECAY   6    1.3
+    1.0    2    5   24
+DECAY   6    1.3
+    1.0    2    5   24
+";
+        match Slha::parse_blocks_only(input, &ParseOptions::default()) {
+            Err(Error(ErrorKind::DuplicateDecay(6), _)) => (),
+            Err(err) => panic!("Wrong error variant {:?} instead of DuplicateDecay", err),
+            Ok(_) => panic!("Unexpected success"),
+        }
+
+        let input = "\
+DECAY   6    foo
+    1.0    2    5   24
+";
+        assert!(Slha::parse_blocks_only(input, &ParseOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_parse_blocks_only_options() {
+        let input = "\
+BLOCK MASS
+    6    173,2
+DECAY 6 1,35
+    100   2   5   24
+";
+        let options = ParseOptions {
+            decimal_comma: true,
+            br_as_percent: true,
+            ..ParseOptions::default()
+        };
+        let eager = Slha::parse_with_options(input, &options).unwrap();
+        let lazy = Slha::parse_blocks_only(input, &options).unwrap();
+        let top = lazy.get_decay(6).unwrap();
+        assert_eq!(top, eager.get_decay(6).unwrap());
+        assert_eq!(top.width, 1.35);
+        assert_eq!(top.decays[0].branching_ratio, 1.);
+        let mass: Block<i64, f64> = lazy.get_block("mass").unwrap().unwrap();
+        assert_eq!(mass.map[&6], 173.2);
+    }
+
+    #[test]
+    fn test_slha_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Slha>();
+    }
+
+    #[test]
+    fn test_parse_blocks_only_merge_decays() {
+        let input = "\
+DECAY   6    1.3
+    1.0    2    5   24
+";
+        let other = Slha::parse("DECAY 6 1.5\n    1.0    2    5   24\n").unwrap();
+        let mut slha = Slha::parse_blocks_only(input, &ParseOptions::default()).unwrap();
+        assert!(
+            slha.merge_decays(other.clone().into_decays(), MergePolicy::Error)
+                .is_err()
+        );
+        slha.merge_decays(other.clone().into_decays(), MergePolicy::KeepFirst)
+            .unwrap();
+        assert_eq!(slha.get_decay(6).unwrap().width, 1.3);
+        slha.merge_decays(other.into_decays(), MergePolicy::Override)
+            .unwrap();
+        assert_eq!(slha.get_decay(6).unwrap().width, 1.5);
+        assert_eq!(slha.decay_pdg_ids().count(), 1);
+    }
+
+    #[test]
+    fn test_decay_same_channel() {
+        let decay = |branching_ratio: f64, daughters: Vec<i64>| {
+            Decay {
+                branching_ratio,
+                daughters,
+            }
+        };
+        assert!(decay(0.5, vec![5, 24]).same_channel(&decay(0.5, vec![24, 5]), 0.));
+        assert!(decay(0.5, vec![5, 24]).same_channel(&decay(0.5, vec![5, 24]), 0.));
+        assert!(decay(0.1, vec![1, 2, 1]).same_channel(&decay(0.1, vec![1, 1, 2]), 0.));
+        assert!(!decay(0.1, vec![1, 2, 2]).same_channel(&decay(0.1, vec![1, 1, 2]), 0.));
+        assert!(!decay(0.5, vec![5, 24]).same_channel(&decay(0.5, vec![-5, 24]), 0.));
+        assert!(!decay(0.5, vec![5, 24]).same_channel(&decay(0.5, vec![5, 24, 22]), 0.));
+        assert!(decay(0.5, vec![5, 24]).same_channel(&decay(0.49, vec![24, 5]), 0.05));
+        assert!(decay(0.49, vec![5, 24]).same_channel(&decay(0.5, vec![24, 5]), 0.05));
+        assert!(!decay(0.5, vec![5, 24]).same_channel(&decay(0.4, vec![24, 5]), 0.05));
+    }
+
+    #[test]
+    fn test_decay_try_new() {
+        use super::DecayTable;
+        use std::f64;
+
+        assert!(Decay::try_new(1., vec![5, 24]).is_ok());
+        assert!(Decay::try_new(0., vec![22]).is_ok());
+        for &br in &[f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            match Decay::try_new(br, vec![5, 24]) {
+                Err(Error(ErrorKind::NonFiniteBranchingRatio(_), _)) => (),
+                Err(err) => {
+                    panic!("Wrong error variant {:?} instead of NonFiniteBranchingRatio", err)
+                }
+                Ok(decay) => panic!("Unexpected success: {:?}", decay),
+            }
+        }
+        match Decay::try_new(1., Vec::new()) {
+            Err(Error(ErrorKind::NoDaughters, _)) => (),
+            Err(err) => panic!("Wrong error variant {:?} instead of NoDaughters", err),
+            Ok(decay) => panic!("Unexpected success: {:?}", decay),
+        }
+
+        assert!(DecayTable::try_new(0., Vec::new()).is_ok());
+        for &width in &[-1e-10, f64::NAN, f64::INFINITY] {
+            match DecayTable::try_new(width, Vec::new()) {
+                Err(Error(ErrorKind::InvalidWidthValue(_), _)) => (),
+                Err(err) => panic!("Wrong error variant {:?} instead of InvalidWidthValue", err),
+                Ok(table) => panic!("Unexpected success: {:?}", table),
+            }
+        }
+    }
+
+    #[test]
+    fn test_decay_table_check_nda() {
+        use super::DecayTable;
+
+        let input = "\
+DECAY 6 1.35
+    0.9   2   5   24
+    0.1   2   3   24
+";
+        let slha = Slha::parse(input).unwrap();
+        let mut top = slha.get_decay(6).unwrap().clone();
+        assert!(top.check_nda().is_ok());
+        top.decays.push(Decay {
+            branching_ratio: 0.,
+            daughters: Vec::new(),
+        });
+        match top.check_nda() {
+            Err(Error(ErrorKind::NoDaughters, _)) => (),
+            other => panic!("Expected NoDaughters, got {:?}", other),
+        }
+        top.decays[2].daughters = vec![5; 256];
+        match top.check_nda() {
+            Err(Error(ErrorKind::TooManyDaughters(256, 255), _)) => (),
+            other => panic!("Expected TooManyDaughters, got {:?}", other),
+        }
+        top.decays.pop();
+        top.decays[0].branching_ratio = f64::NAN;
+        match top.check_nda() {
+            Err(Error(ErrorKind::NonFiniteBranchingRatio(_), _)) => (),
+            other => panic!("Expected NonFiniteBranchingRatio, got {:?}", other),
+        }
+        assert!(DecayTable::try_new(0., Vec::new()).unwrap().check_nda().is_ok());
+    }
+
+    #[test]
+    fn test_parse_decay_table_comments() {
+        let input = "\
+# The decay table for a VERY fictional top quark
+DECAY   6    1.3   # top quark decays
+    # A top decaying into c and s would be very weird...
+    0.5    2    3   4   # BR(t -> 3 4)
+    # but not nearly as bad as decaying into c b t
+    # where the top actually decays into itself.
+    0.25    3    4   5  6 # BR(t -> 4 5 6)
+    # And again, top -> top + other crap.
+    0.25    4    5   6  7  8      # BR(t -> 5 6 7 8)
+    # So, very fictional indeed.
+    # But it's just an example to test the parser, so this doesn't matter at all.
+";
+        let slha = Slha::parse(input).unwrap();
+        let dec = slha.get_decay(6).unwrap();
+        assert_eq!(dec.width, 1.3);
+        assert_eq!(dec.decays.len(), 3);
+        assert_eq!(
+            dec.decays[0],
+            Decay {
+                branching_ratio: 0.5,
+                daughters: vec![3, 4],
+            }
+        );
+        assert_eq!(
+            dec.decays[1],
+            Decay {
+                branching_ratio: 0.25,
+                daughters: vec![4, 5, 6],
+            }
+        );
+        assert_eq!(
+            dec.decays[2],
+            Decay {
+                branching_ratio: 0.25,
+                daughters: vec![5, 6, 7, 8],
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_block_str() {
+        let input = "\
+BLOCK TEST
+ 1 3
+ 4 6
+block Mass
+  6  173.2
+BloCk FooBar
+  1 2 3 4 0.5
+  1 assdf 3 4 8
+  1 2 4 8.98
+";
+        let slha = Slha::parse(input).unwrap();
+        println!("{:?}", slha);
+        let block: BlockStr<i64> = slha.get_block("test").unwrap().unwrap();
+        assert_eq!(block.map.len(), 2);
+        assert_eq!(block.map[&vec!["1".to_string()]], 3);
+        assert_eq!(block.map[&vec!["4".to_string()]], 6);
+        let block: BlockStr<(i64, f64)> = slha.get_block("mass").unwrap().unwrap();
+        assert_eq!(block.map.len(), 1);
+        assert_eq!(block.map[&Vec::new()], (6, 173.2));
+        let block: BlockStr<f64> = slha.get_block("foobar").unwrap().unwrap();
+        assert_eq!(block.map.len(), 3);
+        assert_eq!(
+            block.map[&vec![
+                "1".to_string(),
+                "2".to_string(),
+                "3".to_string(),
+                "4".to_string(),
+            ]],
+            0.5
+        );
+        assert_eq!(
+            block.map[&vec![
+                "1".to_string(),
+                "assdf".to_string(),
+                "3".to_string(),
+                "4".to_string(),
+            ]],
+            8.
+        );
+        assert_eq!(
+            block.map[&vec!["1".to_string(), "2".to_string(), "4".to_string()]],
+            8.98
+        );
+    }
+
+    #[test]
+    fn test_parse_block_single() {
+        let input = "\
+BLOCK TEST
+   3
+  ";
+        let slha = Slha::parse(input).unwrap();
+        println!("{:?}", slha);
+        let block: BlockSingle<i64> = slha.get_block("test").unwrap().unwrap();
+        assert_eq!(block.value, 3);
+    }
+
+    #[test]
+    fn test_parse_block_single_comments() {
+        let input = "\
+# This is a test
+BLOCK TEST # A blkoc of type test
+# Single blocks only contain one line wiht one isngle value on it
+   3  # The value of this single block
+# That was it. No more stuff
+# is allowed in this block
+  ";
+        let slha = Slha::parse(input).unwrap();
+        println!("{:?}", slha);
+        let block: BlockSingle<i64> = slha.get_block("test").unwrap().unwrap();
+        assert_eq!(block.value, 3);
+    }
+
+    #[test]
+    fn test_get_block_at_scale() {
+        let input = "\
+Block yd
+    3  3 1.4e-01
+Block ye Q= 4.64649125e+02
+    3  3 9.97405356e-02   # Ytau(Q)MSSM DRbar
+Block ye Q= 4.64649125e+03
+    3  3 9.97405356e-03   # Ytau(Q)MSSM DRbar
+         ";
+
+        let slha = Slha::parse(input).unwrap();
+        let ye: Block<(i8, i8), f64> = slha.get_block_at_scale("ye", 464.649, 1e-5)
+            .unwrap()
+            .unwrap();
+        assert_eq!(ye.scale, Some(4.64649125e+02));
+        assert_eq!(ye.map[&(3, 3)], 9.97405356e-02);
+        assert!(ye.scale_approx(464.649, 1e-5));
+        let ye: Block<(i8, i8), f64> = slha.get_block_at_scale("YE", 4646.5, 1e-4)
+            .unwrap()
+            .unwrap();
+        assert_eq!(ye.map[&(3, 3)], 9.97405356e-03);
+        assert!(
+            slha.get_block_at_scale::<Block<(i8, i8), f64>>("ye", 464.649, 1e-9)
+                .is_none()
+        );
+        assert!(
+            slha.get_block_at_scale::<Block<(i8, i8), f64>>("yd", 0., 1.)
+                .is_none()
+        );
+        let err = slha.get_block_at_scale::<Block<(i8, i8), f64>>("ye", 1000., 10.)
+            .unwrap()
+            .unwrap_err();
+        if let Error(ErrorKind::DuplicateBlockScale(name, scale), _) = err {
+            assert_eq!(&name, "ye");
+            assert_eq!(scale, 1000.);
+        } else {
+            panic!("Wrong error variant {:?} instead of DuplicateBlockScale", err);
         }
-        Some(blocks[0].to_block(&name))
     }
 
-    /// Lookup all blocks with a given name but different scale and parse them into a vector of
-    /// rust objects.
-    ///
-    /// This function checks that all blocks have different scales.
-    /// If one block does have a scale, then all of them must have one.
-    /// Only one block without a scale is allowed.
-    /// As an alternative the method `get_blocks_unchecked` exists, which does not perform these
-    /// sanity checks.
-    /// If there is no block with the given name, the returned vector is empty.
-    ///
-    /// The returned blocks are in the same order as they appear in the SLHA file.
-    ///
-    /// # Errors
-    ///
-    /// If (at least) one of the blocks could not be parsed into an object of type `B`.
-    /// If the blocks do not have unique scales as described above.
-    ///
-    /// # Examples
-    ///
-    /// ```rust
-    /// use slha::{Slha, Block};
-    ///
-    /// let input = "\
-    /// BLOCK Mass
-    ///     6    173.2
-    /// Block ye Q= 20
-    ///     3  3 9.0e-02   # Ytau(Q)MSSM DRbar
-    /// Block yu Q= 10
-    ///     3  3 8.88194465e-01   # Yt(Q)MSSM DRbar
-    /// Block ye Q= 40
-    ///     3  3 7.0e-03   # Ytau(Q)MSSM DRbar
-    /// ";
-    ///
-    /// let slha = Slha::parse(input).unwrap();
-    /// let mass: Vec<Block<i64, f64>> = match slha.get_blocks("mass") {
-    ///     Ok(mass) => mass,
-    ///     Err(err) => panic!("Error while parsing block mass: {}", err),
-    /// };
-    /// assert_eq!(mass.len(), 1);
-    /// assert_eq!(mass[0].map[&6], 173.2);
-    ///
-    /// let ye: Vec<Block<(i8,i8), f64>> = match slha.get_blocks("ye") {
-    ///     Ok(ye) => ye,
-    ///     Err(err) => panic!("Error while parsing block ye: {}", err),
-    /// };
-    /// assert_eq!(ye.len(), 2);
-    /// assert_eq!(ye[0].scale, Some(20.));
-    /// assert_eq!(ye[0].map[&(3,3) ], 9.0e-02);
-    /// assert_eq!(ye[1].scale, Some(40.));
-    /// assert_eq!(ye[1].map[&(3,3) ], 7.0e-03);
-    /// ```
-    pub fn get_blocks<B: SlhaBlock>(&self, name: &str) -> Result<Vec<B>> {
-        let blocks: Vec<B> = self.get_blocks_unchecked(name)?;
-        let mut no_scale = false;
-        let mut seen_scales = Vec::new();
-        for block in &blocks {
-            match block.scale() {
-                Some(scale) => seen_scales.push(scale),
-                None if no_scale => bail!(ErrorKind::DuplicateBlock(name.to_lowercase())),
-                None => no_scale = true,
-            }
-        }
-        if no_scale && !seen_scales.is_empty() {
-            bail!(ErrorKind::RedefinedBlockWithQ(name.to_lowercase()));
+    #[test]
+    fn test_get_block_where() {
+        let input = "\
+Block ye Q= 4.64649125e+01
+    3  3 9.97405356e-02
+Block ye Q= 4.64649125e+02
+    3  3 9.87405356e-02
+Block ye
+    3  3 9.77405356e-02
+Block yu Q= 4.64649125e+03
+    3  3 foo
+";
+        let slha = Slha::parse(input).unwrap();
+        let ye: Block<(u8, u8), f64> = slha.get_block_where("YE", |scale| match scale {
+            Some(q) => q > 100.,
+            None => false,
+        }).unwrap()
+            .unwrap();
+        assert_eq!(ye.scale, Some(4.64649125e+02));
+        assert_eq!(ye.map[&(3, 3)], 9.87405356e-02);
+
+        let ye: Block<(u8, u8), f64> = slha.get_block_where("ye", |scale| scale.is_none())
+            .unwrap()
+            .unwrap();
+        assert_eq!(ye.map[&(3, 3)], 9.77405356e-02);
+
+        let err = slha.get_block_where::<Block<(u8, u8), f64>>("ye", |scale| scale.is_some())
+            .unwrap()
+            .unwrap_err();
+        match err {
+            Error(ErrorKind::DuplicateBlock(ref name), _) => assert_eq!(name, "ye"),
+            ref err => panic!("Wrong error variant {:?} instead of DuplicateBlock", err),
         }
-        if let Some(scale) = find_duplicates(seen_scales) {
-            bail!(ErrorKind::DuplicateBlockScale(name.to_lowercase(), scale));
+        assert!(slha.get_block_where::<Block<(u8, u8), f64>>("ye", |_| false).is_none());
+        assert!(slha.get_block_where::<Block<(u8, u8), f64>>("yd", |_| true).is_none());
+        let err = slha.get_block_where::<Block<(u8, u8), f64>>("yu", |_| true)
+            .unwrap()
+            .unwrap_err();
+        match err {
+            Error(ErrorKind::InvalidBlock(ref name), _) => assert_eq!(name, "yu"),
+            ref err => panic!("Wrong error variant {:?} instead of InvalidBlock", err),
         }
-        Ok(blocks)
     }
 
-    /// Lookup all blocks with a given name and parse them into a vector of rust objects.
-    ///
-    /// Unlike `get_blocks` this function does not check if the scales of the blocks are consistent
-    /// with each other.
-    /// If there is no block with the given name, the returned vector is empty.
-    ///
-    /// The returned blocks are in the same order as they appear in the SLHA file.
-    ///
-    /// # Errors
-    ///
-    /// If (at least) one of the blocks could not be parsed into an object of type `B`.
-    ///
-    /// # Examples
-    ///
-    /// ```rust
-    /// use slha::{Slha, Block};
-    ///
-    /// let input = "\
-    /// BLOCK Mass
-    ///     6    173.2
-    /// Block ye Q= 20
-    ///     3  3 9.0e-02   # Ytau(Q)MSSM DRbar
-    /// Block ye Q= 30
-    ///     3  3 8.0e-01   # Yt(Q)MSSM DRbar
-    /// BLOCK Mass
-    ///     5    5.2
-    /// Block ye Q= 20
-    ///     3  3 7.0e-03   # Ytau(Q)MSSM DRbar
-    /// ";
-    ///
-    /// let slha = Slha::parse(input).unwrap();
-    /// let mass: Vec<Block<i64, f64>> = match slha.get_blocks_unchecked("mass") {
-    ///     Ok(mass) => mass,
-    ///     Err(err) => panic!("Error while parsing block mass: {}", err),
-    /// };
-    /// assert_eq!(mass.len(), 2);
-    /// assert_eq!(mass[0].map[&6], 173.2);
-    /// assert_eq!(mass[1].map[&5], 5.2);
-    ///
-    /// let ye: Vec<Block<(i8,i8), f64>> = match slha.get_blocks_unchecked("ye") {
-    ///     Ok(ye) => ye,
-    ///     Err(err) => panic!("Error while parsing block ye: {}", err),
-    /// };
-    /// assert_eq!(ye.len(), 3);
-    /// assert_eq!(ye[0].scale, Some(20.));
-    /// assert_eq!(ye[0].map[&(3,3) ], 9.0e-02);
-    /// assert_eq!(ye[1].scale, Some(30.));
-    /// assert_eq!(ye[1].map[&(3,3) ], 8.0e-01);
-    /// assert_eq!(ye[2].scale, Some(20.));
-    /// assert_eq!(ye[2].map[&(3,3) ], 7.0e-03);
-    /// ```
-    pub fn get_blocks_unchecked<B: SlhaBlock>(&self, name: &str) -> Result<Vec<B>> {
-        let name = name.to_lowercase();
-        let blocks = match self.blocks.get(&name) {
-            Some(blocks) => blocks,
-            None => return Ok(Vec::new()),
-        };
-        blocks.iter().map(|block| block.to_block(&name)).collect()
-    }
+    #[test]
+    fn test_get_block_by_scale() {
+        let input = "\
+Block ye Q= 4.64649125e+02
+    3  3 9.97405356e-02   # Ytau(Q)MSSM DRbar
+Block ye Q= 4.64649125e+03
+    3  3 9.97405356e-03   # Ytau(Q)MSSM DRbar
+Block yd
+    3  3 1.4e-01
+Block yd
+    3  3 1.5e-01
+";
 
-    /// Returns the raw bodies of all blocks with the given names.
-    ///
-    /// The returned `RawBlock` objects contain all non-whitespace, non-comment lines that belong
-    /// to the block.
-    /// While leading whitespace is not included and neither is the newline at the end of the line,
-    /// all other whitespace is still present.
-    ///
-    /// The returned blocks are in the same order as they appear in the SLHA file.
-    ///
-    /// # Examples
-    ///
-    /// ```rust
-    /// use slha::{Slha, Line};
-    ///
-    /// let input = "\
-    /// BLOCK Mass
-    ///     6    173.2
-    /// Block ye Q= 20
-    ///     3  3 9.0e-02   # First line
-    /// Block ye Q= 30
-    ///     3  3 8.0e-01   #    Second line
-    /// BLOCK Mass
-    ///     5    5.2
-    /// Block ye Q= 20
-    ///     3  3 7.0e-03   # Third
-    /// ";
-    ///
-    /// let slha = Slha::parse(input).unwrap();
-    /// let mass = slha.get_raw_blocks("mass");
-    /// assert_eq!(mass.len(), 2);
-    /// assert_eq!(mass[0].lines[0], Line { data: "6    173.2", comment: None });
-    /// assert_eq!(mass[1].lines[0], Line { data: "5    5.2", comment: None });
-    ///
-    /// let ye = slha.get_raw_blocks("ye");
-    /// assert_eq!(ye.len(), 3);
-    /// assert_eq!(ye[0].scale, Some(20.));
-    /// assert_eq!(ye[0].lines[0], Line { data: "3  3 9.0e-02   ", comment: Some("# First line") });
-    /// assert_eq!(ye[1].scale, Some(30.));
-    /// assert_eq!(ye[1].lines[0], Line { data: "3  3 8.0e-01   ", comment: Some("#    Second line") });
-    /// assert_eq!(ye[2].scale, Some(20.));
-    /// assert_eq!(ye[2].lines[0], Line { data: "3  3 7.0e-03   ", comment: Some("# Third") });
-    /// ```
-    pub fn get_raw_blocks<'s>(&'s self, name: &str) -> &'s [RawBlock<'a>] {
-        let name = name.to_lowercase();
-        match self.blocks.get(&name) {
-            Some(blocks) => &blocks,
-            None => &[],
+        let slha = Slha::parse(input).unwrap();
+        assert!(slha.get_block::<Block<(i8, i8), f64>>("ye").unwrap().is_err());
+        let ye: Block<(i8, i8), f64> = slha.get_block_by_scale("ye", Some(4646.49125))
+            .unwrap()
+            .unwrap();
+        assert_eq!(ye.scale, Some(4.64649125e+03));
+        assert_eq!(ye.map[&(3, 3)], 9.97405356e-03);
+        let ye: Block<(i8, i8), f64> = slha.get_block_by_scale("YE", Some(464.6491))
+            .unwrap()
+            .unwrap();
+        assert_eq!(ye.map[&(3, 3)], 9.97405356e-02);
+        assert!(
+            slha.get_block_by_scale::<Block<(i8, i8), f64>>("ye", Some(464.6))
+                .is_none()
+        );
+        assert!(
+            slha.get_block_by_scale::<Block<(i8, i8), f64>>("ye", None)
+                .is_none()
+        );
+        let err = slha.get_block_by_scale::<Block<(i8, i8), f64>>("yd", None)
+            .unwrap()
+            .unwrap_err();
+        if let Error(ErrorKind::DuplicateBlock(name), _) = err {
+            assert_eq!(&name, "yd");
+        } else {
+            panic!("Wrong error variant {:?} instead of DuplicateBlock", err);
         }
     }
 
-    /// Returns the decay table of the particle with the given pdg id.
-    ///
-    /// If there is no decay table for the given particle in the SLHA file, then `None` is
-    /// returned.
-    ///
-    /// ```rust
-    /// use slha::{Slha, DecayTable};
-    ///
-    /// let input = "\
-    /// Block SMINPUTS   # Standard Model inputs
-    ///      3      0.1172  # alpha_s(MZ) SM MSbar
-    ///      5      4.25    # Mb(mb) SM MSbar
-    ///      6    174.3     # Mtop(pole)
-    /// DECAY 6 1.35
-    ///     1   2   5   24
-    /// ";
-    ///
-    /// let slha = match Slha::parse(input) {
-    ///     Ok(slha) => slha,
-    ///     Err(err) => panic!("Failed to deserialize SLHA file: {}", err),
-    /// };
-    ///
-    /// let decay_table = match slha.get_decay(6) {
-    ///     Some(dec) => dec,
-    ///     None => panic!("Missing decay table for the top quark."),
-    /// };
-    /// assert_eq!(decay_table.width, 1.35);
-    /// let decay = &decay_table.decays;
-    /// assert_eq!(decay.len(), 1);
-    /// assert_eq!(decay[0].branching_ratio, 1.);
-    /// assert_eq!(decay[0].daughters, vec![5, 24]);
-    /// ```
-    pub fn get_decay(&self, pdg_id: i64) -> Option<&DecayTable> {
-        self.decays.get(&pdg_id)
+    #[test]
+    fn test_content_hash() {
+        let input = "\
+Block ye Q= 4.64649125e+02
+    3  3 9.97405356e-02
+    2  2 5.78e-03
+Block yd Q= 4.64649125e+02
+    2  2 5.78e-03
+    3  3 9.97405356e-02
+Block yu Q= 4.64649125e+03
+    2  2 5.78e-03
+    3  3 9.97405356e-02
+DECAY   6    1.3
+    0.5    2    3   4
+    0.5    2    5   6
+DECAY   7    1.3
+    0.5    2    3   4
+    0.5    2    5   6
+         ";
+
+        let slha = Slha::parse(input).unwrap();
+        let ye: Block<(i8, i8), f64> = slha.get_block("ye").unwrap().unwrap();
+        let mut yd: Block<(i8, i8), f64> = slha.get_block("yd").unwrap().unwrap();
+        let yu: Block<(i8, i8), f64> = slha.get_block("yu").unwrap().unwrap();
+        assert_eq!(ye.content_hash(), yd.content_hash());
+        assert!(ye.content_hash() != yu.content_hash());
+        *yd.get_mut(&(3, 3)).unwrap() = 9.97405357e-02;
+        assert!(ye.content_hash() != yd.content_hash());
+
+        let top = slha.get_decay(6).unwrap();
+        let mut other = slha.get_decay(7).unwrap().clone();
+        assert_eq!(top.content_hash(), other.content_hash());
+        other.decays[1].daughters[1] = 7;
+        assert!(top.content_hash() != other.content_hash());
+        other.decays[1].daughters[1] = 6;
+        other.width = 1.4;
+        assert!(top.content_hash() != other.content_hash());
     }
 
-    fn insert_decay(&mut self, pdg_id: i64, width: f64, decays: Vec<Decay>) -> Result<()> {
-        if self.decays.contains_key(&pdg_id) {
-            bail!(ErrorKind::DuplicateDecay(pdg_id));
-        }
-        self.decays.insert(pdg_id, DecayTable { width, decays });
-        Ok(())
+    #[test]
+    fn test_content_hash_value() {
+        use super::FnvHasher;
+        use std::hash::Hasher;
+
+        let mut hasher = FnvHasher::new();
+        hasher.write(b"a");
+        assert_eq!(hasher.finish(), 0xaf63_dc4c_8601_ec8c);
+
+        let input = "\
+Block MASS
+    6    173.2
+DECAY 6 1.35
+    1.0   2   5   24
+";
+        let slha = Slha::parse(input).unwrap();
+        let mass: Block<i64, f64> = slha.get_block("mass").unwrap().unwrap();
+        assert_eq!(mass.content_hash(), 0xbb70_5c1c_f905_bfa4);
+        assert_eq!(slha.get_decay(6).unwrap().content_hash(), 0xb244_6e24_2dda_389a);
     }
-}
 
-fn find_duplicates<T: Clone + PartialOrd>(mut list: Vec<T>) -> Option<T> {
-    if list.len() < 2 {
-        return None;
+    #[test]
+    fn test_clone_decays() {
+        let decays = {
+            let input = String::from(
+                "\
+BLOCK MASS
+    6   173.2
+DECAY 6 1.35
+    1.0   2   5   24
+DECAY 25 4.07e-03
+    0.6   2   5   -5
+    0.4   2   24  -24
+",
+            );
+            let slha = Slha::parse_blocks_only(&input, &ParseOptions::default()).unwrap();
+            assert_eq!(slha.get_decay(6).unwrap().width, 1.35);
+            slha.clone_decays()
+        };
+        assert_eq!(decays.len(), 2);
+        assert_eq!(decays[&6].decays[0].daughters, vec![5, 24]);
+        assert_eq!(decays[&25].width, 4.07e-03);
+        assert_eq!(decays[&25].decays.len(), 2);
     }
-    list.sort_unstable_by(|e1, e2| e1.partial_cmp(e2).unwrap());
-    for (e1, e2) in list.iter().zip(list.iter().skip(1)) {
-        if e1 == e2 {
-            return Some(e1.clone());
+
+    #[test]
+    fn test_merge_decays() {
+        let input = "\
+DECAY   6    1.3
+    1.0    2    5   24
+DECAY   25   4.07e-03
+    1.0    2    5   -5
+";
+        let other = "\
+DECAY   6    1.5
+    1.0    2    3   24
+DECAY   1000022   0.0
+";
+        let other = Slha::parse(other).unwrap().into_decays();
+
+        let mut slha = Slha::parse(input).unwrap();
+        let err = slha.merge_decays(other.clone(), MergePolicy::Error).unwrap_err();
+        if let Error(ErrorKind::DuplicateDecay(pdg_id), _) = err {
+            assert_eq!(pdg_id, 6);
+        } else {
+            panic!("Wrong error variant {:?} instead of DuplicateDecay", err);
         }
+        assert!(slha.get_decay(1000022).is_none());
+
+        let mut slha = Slha::parse(input).unwrap();
+        slha.merge_decays(other.clone(), MergePolicy::KeepFirst).unwrap();
+        assert_eq!(slha.get_decay(6).unwrap().width, 1.3);
+        assert_eq!(slha.get_decay(6).unwrap().decays[0].daughters, vec![5, 24]);
+        assert_eq!(slha.get_decay(25).unwrap().width, 4.07e-03);
+        assert_eq!(slha.get_decay(1000022).unwrap().width, 0.0);
+
+        let mut slha = Slha::parse(input).unwrap();
+        slha.merge_decays(other, MergePolicy::Override).unwrap();
+        assert_eq!(slha.get_decay(6).unwrap().width, 1.5);
+        assert_eq!(slha.get_decay(6).unwrap().decays[0].daughters, vec![3, 24]);
+        assert_eq!(slha.get_decay(25).unwrap().width, 4.07e-03);
+        assert_eq!(slha.get_decay(1000022).unwrap().width, 0.0);
+
+        let mut slha = Slha::parse(input).unwrap();
+        let other = Slha::parse("DECAY 1000022 0.0").unwrap().into_decays();
+        slha.merge_decays(other, MergePolicy::Error).unwrap();
+        assert_eq!(slha.get_decay(1000022).unwrap().width, 0.0);
     }
-    None
-}
-
-#[cfg(test)]
-mod tests {
-    use super::{Slha, Block, BlockSingle, BlockStr, Parseable, ParseResult, Decay, Line};
-    use super::errors::{Error, ErrorKind};
 
     #[test]
-    fn test_parse_tuple() {
-        macro_rules! unwrap_parseresult {
-            ($result:expr) => {
-                match $result {
-                    ParseResult::Done(rest, value) => (rest, value),
-                    ParseResult::Error(err) => panic!(err),
-                }
-            }
+    fn test_line_trimmed() {
+        let input = "\
+Block SMINPUTS   # Standard Model inputs
+     3      0.1172  # alpha_s(MZ) SM MSbar
+     5      4.25    # Mb(mb) SM MSbar
+     6    174.3\t# Mtop(pole)
+Block SMINPUTS2
+     3      0.1172    # alpha_s(MZ) SM MSbar
+     5      4.25 # Mb(mb) SM MSbar
+     6    174.3  # Mtop(pole)
+";
+        let slha = Slha::parse(input).unwrap();
+        let first = &slha.get_raw_blocks("sminputs")[0].lines;
+        let second = &slha.get_raw_blocks("sminputs2")[0].lines;
+        assert_eq!(first[0].data_trimmed(), "3      0.1172");
+        assert_eq!(first[2].data_trimmed(), "6    174.3");
+        for (a, b) in first.iter().zip(second) {
+            assert!(a != b);
+            assert!(a.eq_ignoring_trailing_ws(b));
         }
-        type T2 = (u8, u8);
-        assert_eq!(unwrap_parseresult!(T2::parse("1 2")), ("", (1, 2)));
-        assert_eq!(unwrap_parseresult!(T2::parse("    1 2")), ("", (1, 2)));
-        assert_eq!(unwrap_parseresult!(T2::parse("1 2   456")), (
-            "   456",
-            (1, 2),
-        ));
-        assert_eq!(unwrap_parseresult!(T2::parse(" 1    2      foobar")), (
-            "      foobar",
-            (1, 2),
-        ));
+        let other = Line {
+            data: "6    174.3",
+            comment: None,
+        };
+        assert!(!first[2].eq_ignoring_trailing_ws(&other));
+        assert!(!first[1].eq_ignoring_trailing_ws(&first[0]));
     }
 
     #[test]
-    fn test_parse_block() {
+    fn test_deserialize_segments_default() {
+        #[derive(Debug, PartialEq)]
+        struct Manual {
+            mass: Block<i64, f64>,
+            hmix: Block<i64, f64>,
+            top: DecayTable,
+        }
+        impl SlhaDeserialize for Manual {
+            fn deserialize(input: &str) -> Result<Manual, Error> {
+                let slha = Slha::parse(input)?;
+                Ok(Manual {
+                    mass: slha.get_block("mass").unwrap()?,
+                    hmix: slha.get_block("hmix").unwrap()?,
+                    top: slha.get_decay(6).unwrap().clone(),
+                })
+            }
+        }
+
         let input = "\
-BLOCK TEST
- 1 3
- 4 6
-block Mass
-  6  173.2";
-        let slha = Slha::parse(input).unwrap();
-        println!("{:?}", slha);
-        let block: Block<i64, i64> = slha.get_block("test").unwrap().unwrap();
-        assert_eq!(block.map.len(), 2);
-        assert_eq!(block.map[&1], 3);
-        assert_eq!(block.map[&4], 6);
-        let block: Block<i64, f64> = slha.get_block("mass").unwrap().unwrap();
-        assert_eq!(block.map.len(), 1);
-        assert_eq!(block.map[&6], 173.2);
+Block MASS
+    6   1.73e2   # top
+   25   125.09
+# BLOCK DISABLED
+#   1   2.0
+Block HMIX Q= 4.67125045e+02
+    1   3.57580322e+02
+DECAY 6 1.3587
+    0.333333333333    2     5    24
+    0.666666666667    3    -5    24   22
+";
+        let slha = Slha::parse_with_options(
+            input,
+            &ParseOptions {
+                recognize_commented_blocks: true,
+                ..Default::default()
+            },
+        ).unwrap();
+        let manual: Manual = slha.to_typed().unwrap();
+        assert_eq!(manual, Manual::deserialize(input).unwrap());
+        assert_eq!(manual.hmix.scale, Some(4.67125045e+02));
+        assert_eq!(manual.top.decays[0].branching_ratio, 0.333333333333);
     }
 
     #[test]
-    fn test_parse_raw_blocks() {
+    fn test_block_debug_sorted() {
         let input = "\
-BLOCK TEST
- 1 3
- 4 6
-block Mass
-  6  173.2
-block Mass
-  5  0.
-  ";
+Block stopmix Q= 100
+  2  2    -5.37975095e-01
+  1  2     8.42960733e-01
+  2  1     8.42960733e-01
+  1  1     5.37975095e-01
+";
         let slha = Slha::parse(input).unwrap();
-        println!("{:?}", slha);
-        let blocks = slha.get_raw_blocks("foo");
-        assert_eq!(blocks.len(), 0);
-        let blocks = slha.get_raw_blocks("test");
-        assert_eq!(blocks.len(), 1);
-        assert_eq!(blocks[0].lines.len(), 2);
-        assert_eq!(
-            blocks[0].lines[0],
-            Line {
-                data: "1 3",
-                comment: None,
-            }
-        );
+        let stopmix: Block<(u8, u8), f64> = slha.get_block("stopmix").unwrap().unwrap();
         assert_eq!(
-            blocks[0].lines[1],
-            Line {
-                data: "4 6",
-                comment: None,
-            }
+            format!("{:?}", stopmix.sorted_debug()),
+            "Block { scale: Some(100.0), map: {(1, 1): 0.537975095, (1, 2): 0.842960733, \
+             (2, 1): 0.842960733, (2, 2): -0.537975095} }"
         );
-        let blocks = slha.get_raw_blocks("mass");
-        assert_eq!(blocks.len(), 2);
-        assert_eq!(blocks[0].lines.len(), 1);
+        let mut copy = stopmix.clone();
+        copy.map.shrink_to_fit();
         assert_eq!(
-            blocks[0].lines[0],
-            Line {
-                data: "6  173.2",
-                comment: None,
-            }
+            format!("{:?}", copy.sorted_debug()),
+            format!("{:?}", stopmix.sorted_debug())
         );
-        assert_eq!(blocks[1].lines.len(), 1);
+
+        // Keys without an ordering still have the derived `Debug` output.
+        let pairs: Block<::value::SortedPair<i64>, f64> =
+            Block::from_body("   1  2   3.0\n", None).unwrap();
         assert_eq!(
-            blocks[1].lines[0],
-            Line {
-                data: "5  0.",
-                comment: None,
-            }
+            format!("{:?}", pairs),
+            "Block { scale: None, map: {SortedPair { low: 1, high: 2 }: 3.0} }"
         );
     }
 
     #[test]
-    fn test_parse_block_comment() {
-        let input = "\
-# This block contains information
-# about testing.
-BLOCK TEST # This is the block header
-# Lets put a comment here, because why not
- 1 3 # Testcase number one
-# How about we separate the two lines here
- # by two comment lines, one of which is indented
- 4 6     # Testcase number two
+    fn test_block_from_body() {
+        let body = "
+# Stop mixing
+   1  1     5.37975095e-01   # O_{11}
 
-# The masses of all particles
-block Mass
-  6  173.2    # M_top
-# A trailing comment can't hurt, now can it?
+   1  2     8.42960733e-01   # O_{12}
 ";
-        let slha = Slha::parse(input).unwrap();
-        println!("{:?}", slha);
-        let block: Block<i64, i64> = slha.get_block("test").unwrap().unwrap();
-        assert_eq!(block.map.len(), 2);
-        assert_eq!(block.map[&1], 3);
-        assert_eq!(block.map[&4], 6);
-        let block: Block<i64, f64> = slha.get_block("mass").unwrap().unwrap();
-        assert_eq!(block.map.len(), 1);
-        assert_eq!(block.map[&6], 173.2);
+        let stopmix = Block::<(i8, i8), f64>::from_body(body, Some(100.)).unwrap();
+        assert_eq!(stopmix.scale, Some(100.));
+        assert_eq!(stopmix.map.len(), 2);
+        assert_eq!(stopmix.map[&(1, 1)], 5.37975095e-01);
+        assert_eq!(stopmix.map[&(1, 2)], 8.42960733e-01);
+        assert!(Block::<i64, f64>::from_body("6 173.2\n6 172.5", None).is_err());
+        assert!(Block::<i64, f64>::from_body("", None).unwrap().map.is_empty());
     }
 
     #[test]
-    fn test_parse_raw_blocks_comment() {
+    fn test_scale_grid() {
         let input = "\
-# This block contains information
-# about testing.
-BLOCK TEST # This is the block header
-# Lets put a comment here, because why not
- 1 3 # Testcase number one
-# How about we separate the two lines here
- # by two comment lines, one of which is indented
- 4 6     # Testcase number two
+Block ye Q= 1000
+    3  3 1.0e-01   # Ytau(Q)MSSM DRbar
+    2  2 5.0e-03   # Ymu(Q)MSSM DRbar
+Block ye Q= 10
+    3  3 3.0e-01   # Ytau(Q)MSSM DRbar
+";
+        let slha = Slha::parse(input).unwrap();
+        let ye: ScaleGrid<Block<(i8, i8), f64>> = slha.get_scale_grid("ye").unwrap();
+        assert_eq!(ye.scales(), vec![10., 1000.]);
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-12;
+        assert!(close(ye.value_at(&(3, 3), 100.).unwrap(), 2.0e-01));
+        assert!(close(ye.value_at(&(3, 3), 31.622776601683793).unwrap(), 2.5e-01));
+        assert_eq!(ye.value_at(&(3, 3), 10.), Some(3.0e-01));
+        assert_eq!(ye.value_at(&(3, 3), 1000.), Some(1.0e-01));
+        assert_eq!(ye.value_at(&(3, 3), 1.), Some(3.0e-01));
+        assert_eq!(ye.value_at(&(3, 3), 1e5), Some(1.0e-01));
+        assert_eq!(ye.value_at(&(2, 2), 10.), Some(5.0e-03));
+        assert_eq!(ye.value_at(&(1, 1), 10.), None);
+
+        let yu: ScaleGrid<Block<(i8, i8), f64>> = slha.get_scale_grid("yu").unwrap();
+        assert!(yu.blocks().is_empty());
+        assert_eq!(yu.value_at(&(3, 3), 10.), None);
+    }
 
-# The masses of all particles
-block Mass
-  6  173.2    # M_top
-block Mass # Why not split the masses?
-  5  0.   #     Mass of the b-quark
-# A trailing comment can't hurt, now can it?
+    #[test]
+    fn test_error_parse_error() {
+        use errors::ErrorCode;
+        use std::error::Error as StdError;
+        use std::num::{ParseFloatError, ParseIntError};
+
+        let input = "\
+BLOCK MASS
+    6   173.2
+    x   4.25
+BLOCK ALPHA
+    foo
 ";
         let slha = Slha::parse(input).unwrap();
-        println!("{:?}", slha);
-        let blocks = slha.get_raw_blocks("test");
-        assert_eq!(blocks.len(), 1);
-        assert_eq!(blocks[0].lines.len(), 2);
+        let err = slha.get_block::<Block<i64, f64>>("mass")
+            .unwrap()
+            .unwrap_err();
+        let source = err.parse_error().unwrap();
+        assert!(source.downcast_ref::<ParseIntError>().is_some());
+        assert_eq!(source.to_string(), "invalid digit found in string");
+        // The same error is found by following `source`.
+        let mut current: &(dyn StdError + 'static) = &err;
+        while let Some(next) = current.source() {
+            current = next;
+        }
         assert_eq!(
-            blocks[0].lines[0],
-            Line {
-                data: "1 3 ",
-                comment: Some("# Testcase number one"),
-            }
+            current.downcast_ref::<ParseIntError>(),
+            source.downcast_ref::<ParseIntError>()
         );
-        assert_eq!(
-            blocks[0].lines[1],
-            Line {
-                data: "4 6     ",
-                comment: Some("# Testcase number two"),
+
+        fn parse_err<T: Parseable>(input: &str) -> Error {
+            match T::parse(input) {
+                ParseResult::Error(err) => err,
+                ParseResult::Done(..) => panic!("Unexpected success for '{}'", input),
             }
-        );
-        let blocks = slha.get_raw_blocks("mass");
-        assert_eq!(blocks.len(), 2);
-        assert_eq!(blocks[0].lines.len(), 1);
+        }
+        let err = parse_err::<i64>("4.25");
+        assert_eq!(err.code(), ErrorCode::InvalidInt);
+        assert!(err.source().unwrap().downcast_ref::<ParseIntError>().is_some());
+        // The message of the source is only printed once in the chain.
+        let messages: Vec<_> = err.iter().map(|err| err.to_string()).collect();
+        assert_eq!(messages, ["Failed to parse an integer", "invalid digit found in string"]);
+        let err = parse_err::<i8>("300");
+        assert_eq!(err.code(), ErrorCode::IntOutOfRange);
+        assert!(err.source().unwrap().downcast_ref::<ParseIntError>().is_some());
+        let err = parse_err::<f64>("foo");
+        assert_eq!(err.code(), ErrorCode::InvalidFloat);
+        assert!(err.source().unwrap().downcast_ref::<ParseFloatError>().is_some());
+        let messages: Vec<_> = err.iter().map(|err| err.to_string()).collect();
         assert_eq!(
-            blocks[0].lines[0],
-            Line {
-                data: "6  173.2    ",
-                comment: Some("# M_top"),
-            }
+            messages,
+            ["Failed to parse a floating point number", "invalid float literal"]
         );
-        assert_eq!(blocks[1].lines.len(), 1);
+
+        let err = slha.get_block::<BlockSingle<f64>>("alpha")
+            .unwrap()
+            .unwrap_err();
+        let source = err.parse_error().unwrap();
+        assert!(source.downcast_ref::<ParseFloatError>().is_some());
+
+        let err = Slha::parse("BLOCK MASS\n    6   173.2\nBLOCK MASS\n    5   4.25\n")
+            .unwrap()
+            .get_block::<Block<i64, f64>>("mass")
+            .unwrap()
+            .unwrap_err();
+        assert!(err.parse_error().is_none());
+    }
+
+    #[test]
+    fn test_error_code() {
+        use errors::ErrorCode;
+
+        let input = "\
+BLOCK MASS
+    x   4.25
+DECAY 6 1.3
+    1.0   2   5   24
+DECAY 6 1.3
+    1.0   2   5   24
+";
+        let err = Slha::parse(input).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::DuplicateDecay);
+        assert_eq!(err.kind().code(), ErrorCode::DuplicateDecay);
+
+        let slha = Slha::parse("BLOCK MASS\n    x   4.25\n").unwrap();
+        let err = slha.get_block::<Block<i64, f64>>("mass")
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(err.code(), ErrorCode::InvalidBlock);
+
+        let err: Error = ErrorKind::MissingKey("mass".to_string(), "6".to_string()).into();
+        assert_eq!(err.code(), ErrorCode::MissingKey);
+        let err: Error = "custom".into();
+        assert_eq!(err.code(), ErrorCode::Msg);
+    }
+
+    #[test]
+    fn test_parse_block_negative_keys() {
+        let input = "\
+BLOCK EXTPAR
+     1    1.0e+03   # M_1
+    -1    2.5e+02
+     0    1.0e+03   # Q_input
+";
+        let slha = Slha::parse(input).unwrap();
+        let extpar: Block<i64, f64> = slha.get_block("extpar").unwrap().unwrap();
+        assert_eq!(extpar.map.len(), 3);
+        assert_eq!(extpar.map[&-1], 2.5e+02);
+        assert_eq!(extpar.map[&0], 1.0e+03);
+        assert_eq!(extpar.map[&1], 1.0e+03);
         assert_eq!(
-            blocks[1].lines[0],
-            Line {
-                data: "5  0.   ",
-                comment: Some("#     Mass of the b-quark"),
-            }
+            extpar.to_sorted_vec(),
+            vec![(&-1, &2.5e+02), (&0, &1.0e+03), (&1, &1.0e+03)]
         );
+        let extpar: Block<i8, f64> = slha.get_block("extpar").unwrap().unwrap();
+        assert_eq!(extpar.map[&-1], 2.5e+02);
+        let extpar: Result<Block<u8, f64>, Error> = slha.get_block("extpar").unwrap();
+        assert!(extpar.is_err());
     }
 
     #[test]
-    fn test_get_block_case() {
+    fn test_map_block() {
         let input = "\
-BLOCK TEST
- 1 3
- 4 6
+BLOCK MASS
+    6   173.2
+    5   4.25
+BLOCK ALPHA
+    foo
 ";
         let slha = Slha::parse(input).unwrap();
-        println!("{:?}", slha);
-        let _: Block<i64, i64> = slha.get_block("TEST").unwrap().unwrap();
-        let _: Block<i64, i64> = slha.get_block("tEsT").unwrap().unwrap();
-        let _: Block<i64, i64> = slha.get_block("TesT").unwrap().unwrap();
-        let block: Block<i64, i64> = slha.get_block("test").unwrap().unwrap();
-        assert_eq!(block.map.len(), 2);
-        assert_eq!(block.map[&1], 3);
-        assert_eq!(block.map[&4], 6);
+        let sum = slha.map_block("mass", |mass: Block<i64, f64>| mass.map.values().sum::<f64>());
+        assert_eq!(sum.unwrap().unwrap(), 177.45);
+        let mut called = false;
+        let alpha = slha.map_block("alpha", |alpha: BlockSingle<f64>| {
+            called = true;
+            alpha.value
+        });
+        assert!(alpha.unwrap().is_err());
+        assert!(!called);
+        assert!(slha.map_block("hmix", |hmix: BlockSingle<f64>| hmix.value).is_none());
     }
 
     #[test]
-    fn test_parse_decay_table() {
+    fn test_block_single_possibly_keyed() {
         let input = "\
-DECAY   6    1.3
-    0.5    2    3   4
-    0.25    3    4   5  6
-    0.25    4    5   6  7  8
+BLOCK ALPHA
+    1   -0.11
+BLOCK BETA Q= 100
+    -0.11
+BLOCK GAMMA
+    foo   -0.11
+";
+        let slha = Slha::parse(input).unwrap();
+        let alpha: Result<BlockSingle<f64>, Error> = slha.get_block("alpha").unwrap();
+        assert!(alpha.is_err());
+        let raw = &slha.get_raw_blocks("alpha")[0];
+        let alpha = BlockSingle::<f64>::from_possibly_keyed(&raw.lines, raw.scale).unwrap();
+        assert_eq!(alpha, BlockSingle { value: -0.11, scale: None });
+        let raw = &slha.get_raw_blocks("beta")[0];
+        let beta = BlockSingle::<f64>::from_possibly_keyed(&raw.lines, raw.scale).unwrap();
+        assert_eq!(beta, BlockSingle { value: -0.11, scale: Some(100.) });
+        let raw = &slha.get_raw_blocks("gamma")[0];
+        match BlockSingle::<f64>::from_possibly_keyed(&raw.lines, raw.scale) {
+            Err(Error(ErrorKind::InvalidFloat(_), _)) => (),
+            other => panic!("Expected InvalidFloat, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_blocks_matching() {
+        let input = "\
+Block yu Q= 4.64649125e+02
+    3  3 8.88193465e-01
+Block YD Q= 4.64649125e+02
+    3  3 1.38840206e-01
+Block ye Q= 4.64649125e+02
+    3  3 9.97405356e-02
+Block ye Q= 1.0e+03
+    3  3 1.0e-01
+Block mass
+    6   173.2
+Block y
+    1   1
+";
+        let slha = Slha::parse(input).unwrap();
+        let matching = slha.blocks_matching("y*");
+        let names: Vec<_> = matching.iter().map(|&(name, _)| name).collect();
+        assert_eq!(names, vec!["y", "yd", "ye", "ye", "yu"]);
+        assert_eq!(matching[2].1.scale, Some(4.64649125e+02));
+        assert_eq!(matching[3].1.scale, Some(1.0e+03));
+        let names: Vec<_> = slha.blocks_matching("Y?*").iter().map(|&(name, _)| name).collect();
+        assert!(names.is_empty());
+        let names: Vec<_> = slha.blocks_matching("YE").iter().map(|&(name, _)| name).collect();
+        assert_eq!(names, vec!["ye", "ye"]);
+        assert_eq!(slha.blocks_matching("*").len(), 6);
+        assert!(slha.blocks_matching("hmix").is_empty());
+    }
+
+    #[test]
+    fn test_block_entry_count() {
+        let input = "\
+BLOCK YE Q= 20
+    1  1 2.0e-06
+    2  2 4.0e-04
+    3  3 9.0e-02   # Ytau
+BLOCK MASS
+    6   173.2
+Block ye Q= 30
+    3  3 8.0e-02
+# Comment lines are not counted
+Block ye Q= 40
+";
+        let slha = Slha::parse(input).unwrap();
+        assert_eq!(slha.get_raw_blocks("ye").len(), 3);
+        assert_eq!(slha.block_entry_count("ye"), 4);
+        assert_eq!(slha.block_entry_count("YE"), 4);
+        assert_eq!(slha.block_entry_count("mass"), 1);
+        assert_eq!(slha.block_entry_count("hmix"), 0);
+    }
+
+    #[test]
+    fn test_merged_block() {
+        let base = Slha::parse("\
+Block MASS
+    6    173.2
+    5    4.25
+   24    80.4
+").unwrap();
+        let no_mass = Slha::parse("Block ALPHA\n    -0.11\n").unwrap();
+        let custom = Slha::parse("\
+Block MASS Q= 91.2
+    6    172.5
+   25    125.1
+").unwrap();
+        let slhas = [base, no_mass, custom];
+        let mass: MergedBlock<i64, f64> = MergedBlock::from_slhas(&slhas, "MASS").unwrap();
+        assert_eq!(mass.block.scale, Some(91.2));
+        assert_eq!(mass.block.map.len(), 4);
+        assert_eq!(mass.block.map[&6], 172.5);
+        assert_eq!(mass.block.map[&24], 80.4);
+        assert_eq!(mass.source(&6), Some(2));
+        assert_eq!(mass.source(&5), Some(0));
+        assert_eq!(mass.source(&24), Some(0));
+        assert_eq!(mass.source(&25), Some(2));
+        assert_eq!(mass.source(&1), None);
+
+        let blocks: Vec<Block<i64, f64>> = slhas.iter()
+            .filter_map(|slha| slha.get_block("mass"))
+            .map(|block| block.unwrap())
+            .collect();
+        let from_blocks = MergedBlock::from_blocks(blocks);
+        assert_eq!(from_blocks.block, mass.block);
+        // The file without the block is not part of the sources here.
+        assert_eq!(from_blocks.source(&6), Some(1));
+        assert_eq!(from_blocks.source(&5), Some(0));
+
+        let empty: MergedBlock<i64, f64> = MergedBlock::from_blocks(Vec::new());
+        assert!(empty.block.map.is_empty());
+        assert_eq!(empty.block.scale, None);
+
+        let broken = Slha::parse("Block MASS\n    6    foo\n").unwrap();
+        let err = MergedBlock::<i64, f64>::from_slhas(&[broken], "mass").unwrap_err();
+        match err {
+            Error(ErrorKind::InvalidBlock(ref name), _) => assert_eq!(name, "mass"),
+            ref err => panic!("Wrong error variant {:?} instead of InvalidBlock", err),
+        }
+    }
+
+    #[test]
+    fn test_assert_equivalent() {
+        let a = "\
+# SPheno output
+BLOCK SPINFO   # Program information
+     1   SPheno   # spectrum calculator
+     2   4.0.3    # version number
+BLOCK MODSEL
+     1    1
+BLOCK MASS   # Mass spectrum
+        6     1.73200000E+02   # M_t
+       25     1.25090000E+02   # M_h
+BLOCK ALPHA   # Effective Higgs mixing angle
+    -1.13825210E-01   # alpha
+BLOCK YE Q=  4.64649125E+02
+  3  3     9.97405356E-02   # Y_tau(Q)
+BLOCK YE Q=  1.00000000E+03
+  3  3     9.87405356E-02   # Y_tau(Q)
+DECAY   1000021   5.50675438E+00   # gluino
+     4.18263072E-02    2     1000001        -1   # BR(~g -> ~d_L db)
+     1.55141598E-02    3     1000022         1        -1
 ";
-        let slha = Slha::parse(input).unwrap();
-        let dec = slha.get_decay(6).unwrap();
-        assert_eq!(dec.width, 1.3);
-        assert_eq!(dec.decays.len(), 3);
+        let b = "\
+Block spinfo
+  2 4.0.3
+  1 SPheno
+Block modsel
+  1 1.0
+Block mass
+  25 125.09
+  6 173.2
+Block alpha
+  -0.11382521
+Block ye Q= 1000
+  3 3 0.0987405356
+Block ye Q= 464.649125
+  3 3 0.0997405356
+DECAY 1000021 5.506754
+  0.0155141598 3 -1 1 1000022
+  0.0418263072 2 1000001 -1
+";
+        assert_equivalent(a, b, 1e-6);
+        let differences = differences(&Slha::parse(a).unwrap(), &Slha::parse(b).unwrap(), 1e-9);
         assert_eq!(
-            dec.decays[0],
-            Decay {
-                branching_ratio: 0.5,
-                daughters: vec![3, 4],
-            }
+            differences,
+            vec![
+                "Decay table 1000021 has the width 5.50675438 in the first file, but 5.506754 in \
+                 the second",
+            ]
         );
-        assert_eq!(
-            dec.decays[1],
-            Decay {
-                branching_ratio: 0.25,
-                daughters: vec![4, 5, 6],
-            }
+    }
+
+    #[test]
+    #[should_panic(expected = "Entry [6] of block 'mass' is '172.5' in the first file, but \
+                               '173.2' in the second")]
+    fn test_assert_equivalent_mismatch() {
+        assert_equivalent(
+            "Block MASS\n   6   172.5\n   5   4.25\n",
+            "Block MASS\n   6   173.2\n   5   4.25\n",
+            1e-3,
         );
+    }
+
+    #[test]
+    fn test_equivalence_differences() {
+        let a = Slha::parse("\
+Block MASS
+    6    173.2
+    5    4.25
+Block YE Q= 100
+    3  3  0.1
+Block ALPHA
+    -0.11
+DECAY 6 1.35
+    0.9   2   5   24
+    0.1   2   3   24
+").unwrap();
+        let b = Slha::parse("\
+Block MASS
+    6    173.2
+   24    80.4
+Block YE Q= 200
+    3  3  0.1
+Block SPINFO
+    1    SPheno
+DECAY 6 1.5
+    0.9   2   24   5
+DECAY 25 4.07e-03
+    1.0   2   5   -5
+").unwrap();
         assert_eq!(
-            dec.decays[2],
-            Decay {
-                branching_ratio: 0.25,
-                daughters: vec![5, 6, 7, 8],
-            }
+            differences(&a, &b, 1e-6),
+            vec![
+                "Block 'alpha' is only in the first file",
+                "Entry [5] of block 'mass' is only in the first file",
+                "Entry [24] of block 'mass' is only in the second file",
+                "Block 'spinfo' is only in the second file",
+                "Block 'ye' has the scale Some(100.0) in the first file, but Some(200.0) in the \
+                 second",
+                "Decay table 6 has the width 1.35 in the first file, but 1.5 in the second",
+                "Decay 6 -> [3, 24] is only in the first file",
+                "Decay table 25 is only in the second file",
+            ]
         );
+        assert!(differences(&a, &a, 0.).is_empty());
     }
 
     #[test]
-    fn test_parse_decay_table_line_comments() {
-        let input = "\
-DECAY   6    1.3   # top quark decays
-    0.5    2    3   4   # BR(t -> 3 4)
-    0.25    3    4   5  6 # BR(t -> 4 5 6)
-    0.25    4    5   6  7  8      # BR(t -> 5 6 7 8)
-";
-        let slha = Slha::parse(input).unwrap();
-        let dec = slha.get_decay(6).unwrap();
-        assert_eq!(dec.width, 1.3);
-        assert_eq!(dec.decays.len(), 3);
+    fn test_equivalence_repeated_keys() {
+        let diff = |a: &str, b: &str| {
+            differences(&Slha::parse(a).unwrap(), &Slha::parse(b).unwrap(), 1e-6)
+        };
         assert_eq!(
-            dec.decays[0],
-            Decay {
-                branching_ratio: 0.5,
-                daughters: vec![3, 4],
-            }
+            diff("Block MASS\n 6 1.0\n 6 2.0\n", "Block MASS\n 6 2.0\n"),
+            vec![
+                "Entry [6] of block 'mass' appears 2 times in the first file, but 1 times in the \
+                 second",
+            ]
         );
+        assert!(diff("Block MASS\n 6 1.0\n 6 2.0\n", "Block MASS\n 6 2.0\n 6 1.0\n").is_empty());
         assert_eq!(
-            dec.decays[1],
-            Decay {
-                branching_ratio: 0.25,
-                daughters: vec![4, 5, 6],
-            }
+            diff(
+                "Block SPINFO\n 1 SPheno\n 3 first warning\n 3 second warning\n",
+                "Block SPINFO\n 1 SPheno\n 3 second warning\n 3 third warning\n",
+            ),
+            vec![
+                "Entry [3] of block 'spinfo' is 'first warning' in the first file, but 'third \
+                 warning' in the second",
+            ]
         );
         assert_eq!(
-            dec.decays[2],
-            Decay {
-                branching_ratio: 0.25,
-                daughters: vec![5, 6, 7, 8],
-            }
+            diff(
+                "Block SPINFO\n 3 first warning\n 3 second warning\n 4 error\n",
+                "Block SPINFO\n 3 first warning\n 4 error\n",
+            ),
+            vec![
+                "Entry [3] of block 'spinfo' appears 2 times in the first file, but 1 times in the \
+                 second",
+            ]
+        );
+        // Keys are matched independently of their formatting.
+        assert!(diff("Block TEST\n 1 100 2.0\n", "Block TEST\n 1 1.0E+02 2.0\n").is_empty());
+        assert_eq!(
+            diff("Block TEST\n 1 100 2.0\n", "Block TEST\n 1 1.0E+02 3.0\n"),
+            vec![
+                "Entry [1, 100] of block 'test' is '2.0' in the first file, but '3.0' in the \
+                 second",
+            ]
+        );
+        assert_eq!(
+            diff(
+                "DECAY 6 1.35\n 0.5 2 5 24\n 0.5 2 5 24\n",
+                "DECAY 6 1.35\n 1.0 2 5 24\n",
+            ),
+            vec!["Decay 6 -> [5, 24] appears 2 times in the first file, but 1 times in the second"]
         );
     }
 
     #[test]
-    fn test_parse_decay_table_comments() {
+    fn test_block_report() {
         let input = "\
-# The decay table for a VERY fictional top quark
-DECAY   6    1.3   # top quark decays
-    # A top decaying into c and s would be very weird...
-    0.5    2    3   4   # BR(t -> 3 4)
-    # but not nearly as bad as decaying into c b t
-    # where the top actually decays into itself.
-    0.25    3    4   5  6 # BR(t -> 4 5 6)
-    # And again, top -> top + other crap.
-    0.25    4    5   6  7  8      # BR(t -> 5 6 7 8)
-    # So, very fictional indeed.
-    # But it's just an example to test the parser, so this doesn't matter at all.
+BLOCK MODSEL
+    1    1
+BLOCK MASS
+    6    173.2
+    5    4.25
+BLOCK YE Q= 4.64649125e+02
+    3  3 9.97405356e-02
+BLOCK ALPHA
+    -1.13825210e-01
+BLOCK YE Q= 1.0e+03
+    1  1 2.9e-06
+    3  3 9.9e-02
+BLOCK YE
+BLOCK MASS
+    4    1.27
 ";
         let slha = Slha::parse(input).unwrap();
-        let dec = slha.get_decay(6).unwrap();
-        assert_eq!(dec.width, 1.3);
-        assert_eq!(dec.decays.len(), 3);
-        assert_eq!(
-            dec.decays[0],
-            Decay {
-                branching_ratio: 0.5,
-                daughters: vec![3, 4],
-            }
-        );
         assert_eq!(
-            dec.decays[1],
-            Decay {
-                branching_ratio: 0.25,
-                daughters: vec![4, 5, 6],
-            }
+            slha.block_report(),
+            vec![
+                BlockReport {
+                    name: "alpha".to_string(),
+                    occurrences: 1,
+                    total_entries: 1,
+                    scales: vec![None],
+                },
+                BlockReport {
+                    name: "mass".to_string(),
+                    occurrences: 2,
+                    total_entries: 3,
+                    scales: vec![None, None],
+                },
+                BlockReport {
+                    name: "modsel".to_string(),
+                    occurrences: 1,
+                    total_entries: 1,
+                    scales: vec![None],
+                },
+                BlockReport {
+                    name: "ye".to_string(),
+                    occurrences: 3,
+                    total_entries: 3,
+                    scales: vec![Some(4.64649125e+02), Some(1.0e+03), None],
+                },
+            ]
         );
-        assert_eq!(
-            dec.decays[2],
-            Decay {
-                branching_ratio: 0.25,
-                daughters: vec![5, 6, 7, 8],
+        assert!(Slha::parse("").unwrap().block_report().is_empty());
+    }
+
+    #[test]
+    fn test_entries() {
+        let input = "\
+BLOCK YE Q= 20
+    1  1 2.0e-06
+    2  2 4.0e-04
+    3  3 9.0e-02   # Ytau
+BLOCK MASS
+    6   173.2
+Block ye Q= 30
+    3  3 8.0e-02
+# Comment lines are not counted
+Block ye Q= 40
+# BLOCK HMIX
+    1   3.5e+02
+DECAY 6 1.35
+    1.0   2   5   24
+";
+        let options = ParseOptions {
+            recognize_commented_blocks: true,
+            ..ParseOptions::default()
+        };
+        let mut slha = Slha::parse_with_options(input, &options).unwrap();
+        assert_eq!(slha.entries().count(), 5);
+        let names: Vec<_> = slha.entries().map(|(name, _, _)| name).collect();
+        assert_eq!(names, vec!["ye", "ye", "ye", "mass", "ye"]);
+        let (_, scale, line) = slha.entries().nth(2).unwrap();
+        assert_eq!(scale, Some(20.));
+        assert_eq!(line.comment, Some("# Ytau"));
+        let (_, scale, line) = slha.entries().last().unwrap();
+        assert_eq!(scale, Some(30.));
+        assert_eq!(line.data, "3  3 8.0e-02");
+
+        assert!(slha.enable_block("hmix"));
+        slha.rename_block("mass", "masses").unwrap();
+        let names: Vec<_> = slha.entries().map(|(name, _, _)| name).collect();
+        assert_eq!(names, vec!["ye", "ye", "ye", "masses", "ye", "hmix"]);
+
+        slha.canonicalize();
+        let names: Vec<_> = slha.entries().map(|(name, _, _)| name).collect();
+        assert_eq!(names, vec!["hmix", "masses", "ye", "ye", "ye", "ye"]);
+        let scales: Vec<_> = slha.entries().map(|(_, scale, _)| scale).collect();
+        assert_eq!(scales[5], Some(30.));
+    }
+
+    #[test]
+    fn test_resolve_widths() {
+        let input = "\
+BLOCK WIDTH
+    6    1.35
+   23    2.4952
+   25    4.07e-03
+DECAY 6
+    1   2   5   24
+DECAY 23   2.5
+    1   2   11   -11
+DECAY   25   # Higgs
+    1   2   5   -5
+DECAY 1000022 0
+";
+        let options = ParseOptions {
+            optional_width: true,
+            ..ParseOptions::default()
+        };
+        let mut slha = Slha::parse_with_options(input, &options).unwrap();
+        assert_eq!(slha.get_decay(6).unwrap().width, 0.);
+        assert_eq!(slha.get_decay(25).unwrap().width, 0.);
+        slha.resolve_widths().unwrap();
+        assert_eq!(slha.get_decay(6).unwrap().width, 1.35);
+        assert_eq!(slha.get_decay(23).unwrap().width, 2.5);
+        assert_eq!(slha.get_decay(25).unwrap().width, 4.07e-03);
+        assert_eq!(slha.get_decay(25).unwrap().decays.len(), 1);
+        assert_eq!(slha.get_decay(1000022).unwrap().width, 0.);
+
+        assert!(Slha::parse("DECAY 6\n    1   2   5   24\n").is_err());
+    }
+
+    #[test]
+    fn test_decay_table_merge() {
+        use super::{ChannelPolicy, DecayTable};
+
+        let first = "\
+DECAY   1000022   0.3
+    0.5   2   1000039   22
+    0.5   2   1000039   23
+";
+        let second = "\
+DECAY   1000022   0.1
+    1.0   3   1000039   11   -11
+";
+        let third = "\
+DECAY   1000022   0.2
+    0.5   2   23   1000039
+    0.5   2   1000039   25
+";
+        let first = Slha::parse(first).unwrap().get_decay(1000022).unwrap().clone();
+        let second = Slha::parse(second).unwrap().get_decay(1000022).unwrap().clone();
+        let third = Slha::parse(third).unwrap().get_decay(1000022).unwrap().clone();
+
+        let mut table = first.clone();
+        table.merge(second, ChannelPolicy::Error).unwrap();
+        assert!((table.width - 0.4).abs() < 1e-12);
+        assert_eq!(table.decays.len(), 3);
+        assert!((table.decays[0].branching_ratio - 0.375).abs() < 1e-12);
+        assert!((table.decays[1].branching_ratio - 0.375).abs() < 1e-12);
+        assert!((table.decays[2].branching_ratio - 0.25).abs() < 1e-12);
+        assert_eq!(table.decays[2].daughters, vec![1000039, 11, -11]);
+
+        let before = table.clone();
+        match table.merge(third.clone(), ChannelPolicy::Error) {
+            Err(Error(ErrorKind::DuplicateChannel(daughters), _)) => {
+                assert_eq!(daughters, vec![23, 1000039])
             }
-        );
+            other => panic!("Expected DuplicateChannel, got {:?}", other),
+        }
+        assert_eq!(table, before);
+
+        table.merge(third, ChannelPolicy::Sum).unwrap();
+        assert!((table.width - 0.6).abs() < 1e-12);
+        assert_eq!(table.decays.len(), 4);
+        let total: f64 = table.decays.iter().map(|decay| decay.branching_ratio).sum();
+        assert!((total - 1.).abs() < 1e-12);
+        assert!((table.branching_ratio_for(&[1000039, 23]).unwrap() - 5. / 12.).abs() < 1e-12);
+        assert!((table.branching_ratio_for(&[1000039, 25]).unwrap() - 1. / 6.).abs() < 1e-12);
+
+        let mut empty = DecayTable::try_new(0., Vec::new()).unwrap();
+        empty.merge(DecayTable::try_new(0., first.decays.clone()).unwrap(), ChannelPolicy::Error)
+            .unwrap();
+        assert_eq!(empty.decays, first.decays);
     }
 
     #[test]
-    fn test_parse_block_str() {
+    fn test_branching_ratio_for() {
         let input = "\
-BLOCK TEST
- 1 3
- 4 6
-block Mass
-  6  173.2
-BloCk FooBar
-  1 2 3 4 0.5
-  1 assdf 3 4 8
-  1 2 4 8.98
+DECAY   6   1.35
+    8.5E-01   2   5   24
+    1.0E-01   2   24   3
+    5.0E-02   3   5   24   22
 ";
         let slha = Slha::parse(input).unwrap();
-        println!("{:?}", slha);
-        let block: BlockStr<i64> = slha.get_block("test").unwrap().unwrap();
-        assert_eq!(block.map.len(), 2);
-        assert_eq!(block.map[&vec!["1".to_string()]], 3);
-        assert_eq!(block.map[&vec!["4".to_string()]], 6);
-        let block: BlockStr<(i64, f64)> = slha.get_block("mass").unwrap().unwrap();
-        assert_eq!(block.map.len(), 1);
-        assert_eq!(block.map[&Vec::new()], (6, 173.2));
-        let block: BlockStr<f64> = slha.get_block("foobar").unwrap().unwrap();
-        assert_eq!(block.map.len(), 3);
-        assert_eq!(
-            block.map[&vec![
-                "1".to_string(),
-                "2".to_string(),
-                "3".to_string(),
-                "4".to_string(),
-            ]],
-            0.5
-        );
+        let top = slha.get_decay(6).unwrap();
+        assert_eq!(top.branching_ratio_for(&[5, 24]), Some(0.85));
+        assert_eq!(top.branching_ratio_for(&[24, 5]), Some(0.85));
+        assert_eq!(top.branching_ratio_for(&[3, 24]), Some(0.1));
+        assert_eq!(top.branching_ratio_for(&[22, 24, 5]), Some(0.05));
+        assert_eq!(top.branching_ratio_for(&[5]), None);
+        assert_eq!(top.branching_ratio_for(&[5, 24, 24]), None);
+        assert_eq!(top.branching_ratio_for(&[]), None);
+    }
+
+    #[test]
+    fn test_from_bytes_lossy() {
+        let mut input = b"\
+BLOCK MASS   # Mass spectrum
+    6   173.2   # Top \xff\xfe quark
+".to_vec();
+        input.extend_from_slice("    5   4.25    # Bottom quark, caf\u{e9}\n".as_bytes());
+        input.extend_from_slice(b"# Latin-1 caf\xe9\nDECAY 6 1.35\n    1   2   5   24\n");
+        assert!(::std::str::from_utf8(&input).is_err());
+        let owned = Slha::from_bytes_lossy(&input).unwrap();
+        let slha = owned.slha();
+        let mass: Block<i64, f64> = slha.get_block("mass").unwrap().unwrap();
+        assert_eq!(mass.map.len(), 2);
+        assert_eq!(mass.map[&6], 173.2);
+        assert_eq!(mass.map[&5], 4.25);
+        let comment = slha.get_raw_block("mass").unwrap().lines[0].comment;
+        assert_eq!(comment, Some("# Top \u{fffd}\u{fffd} quark"));
+        assert_eq!(slha.get_decay(6).unwrap().width, 1.35);
+        assert!(owned.input().contains("caf\u{e9}"));
+        // The input is only read once, and clones keep it alive on their own.
+        assert!(::std::ptr::eq(owned.slha(), slha));
+        let cloned = owned.clone();
+        drop(owned);
+        let comment = cloned.slha().get_raw_block("mass").unwrap().lines[0].comment;
+        assert_eq!(comment, Some("# Top \u{fffd}\u{fffd} quark"));
+
+        assert!(Slha::from_bytes_lossy(b"BLOCK\n").is_err());
+
+        // The options are used both for checking and for reading the input.
+        let input = b"BLOCK MASS\n    6   173.2\n# caf\xe9\nBLOCK mass\n    5   4.25\n";
+        let mut options = ParseOptions {
+            on_duplicate_block: DuplicatePolicy::Error,
+            ..ParseOptions::default()
+        };
+        assert!(Slha::from_bytes_lossy(input).is_ok());
+        assert!(Slha::from_bytes_lossy_with_options(input, &options).is_err());
+        options.case_sensitive = true;
+        let owned = Slha::from_bytes_lossy_with_options(input, &options).unwrap();
+        assert_eq!(owned.options(), &options);
+        let slha = owned.slha();
+        assert_eq!(slha.get_raw_block("MASS").unwrap().lines.len(), 1);
+        assert_eq!(slha.get_raw_block("mass").unwrap().lines.len(), 1);
+    }
+
+    #[test]
+    fn test_canonicalize() {
+        let first = "\
+BLOCK MASS   # Mass spectrum
+    6    1.73200000e+02   # top
+   25    1.25e+02         # h
+    5    4.25             # bottom
+Block ye Q= 20
+    3  3 9.0e-02
+Block ye
+    3  3 1.0e-01
+Block ye Q= 10
+    3  3 8.0e-02
+DECAY 6 1.35
+    0.1   2   3   24
+    0.9   2   5   24
+DECAY 25 4.07e-03
+    0.2   2   15   -15
+    0.2   2   -4   4
+    0.6   2   5   -5
+";
+        let second = "\
+Block ye
+  3 3   1.0e-01
+Block ye Q= 10
+  3  3 8.0e-02
+DECAY 25 4.07e-03
+    0.6   2   5   -5
+    0.2   2   -4   4
+    0.2   2   15   -15
+BLOCK MASS   # Mass spectrum
+  5 4.25  # bottom
+  25\t1.25e+02  # h
+  6    1.73200000e+02  # top
+Block ye Q= 20
+  3    3    9.0e-02
+DECAY 6 1.35
+    0.9   2   5   24
+    0.1   2   3   24
+";
+        let mut first = Slha::parse(first).unwrap();
+        let mut second = Slha::parse_blocks_only(second, &ParseOptions::default()).unwrap();
+        assert!(first != second);
+        first.canonicalize();
+        second.canonicalize();
+        assert_eq!(first, second);
+
+        let mass = first.get_raw_block("mass").unwrap();
+        let data: Vec<_> = mass.lines.iter().map(|line| line.data).collect();
+        assert_eq!(data, vec!["5    4.25", "6    1.73200000e+02", "25    1.25e+02"]);
+        let lines = mass.logical_lines();
+        let data: Vec<_> = lines.iter().map(|line| line.data).collect();
+        assert_eq!(data, vec!["5 4.25", "6 1.73200000e+02", "25 1.25e+02"]);
+        assert_eq!(lines, second.get_raw_block("mass").unwrap().logical_lines());
+        assert_eq!(mass.lines[0].comment, Some("# bottom"));
+        let masses: Block<i64, f64> = first.get_block("mass").unwrap().unwrap();
+        assert_eq!(masses.map[&25], 125.);
+        let scales: Vec<_> = first.get_raw_blocks("ye").iter().map(|ye| ye.scale).collect();
+        assert_eq!(scales, vec![None, Some(10.), Some(20.)]);
+        let higgs = &first.get_decay(25).unwrap().decays;
+        assert_eq!(higgs[0].daughters, vec![5, -5]);
+        assert_eq!(higgs[1].daughters, vec![-4, 4]);
+        assert_eq!(higgs[2].daughters, vec![15, -15]);
+    }
+
+    #[test]
+    fn test_rename_block() {
+        let input = "\
+BLOCK SMINPUT
+    3   0.1172
+BLOCK MASS
+    6   173.2
+";
+        let mut slha = Slha::parse(input).unwrap();
+        slha.rename_block("SMINPUT", "sminputs").unwrap();
+        assert!(slha.get_raw_block("sminput").is_none());
+        let sminputs: Block<i64, f64> = slha.get_block("sminputs").unwrap().unwrap();
+        assert_eq!(sminputs.map[&3], 0.1172);
+        // The block keeps its position and its lines are not changed.
+        let entries: Vec<_> = slha.entries()
+            .map(|(name, scale, line)| (name, scale, line.data))
+            .collect();
         assert_eq!(
-            block.map[&vec![
-                "1".to_string(),
-                "assdf".to_string(),
-                "3".to_string(),
-                "4".to_string(),
-            ]],
-            8.
+            entries,
+            vec![("sminputs", None, "3   0.1172"), ("mass", None, "6   173.2")]
         );
         assert_eq!(
-            block.map[&vec!["1".to_string(), "2".to_string(), "4".to_string()]],
-            8.98
+            slha.get_raw_block("sminputs").unwrap().lines,
+            vec![Line { data: "3   0.1172", comment: None }]
         );
+        match slha.rename_block("sminputs", "mass") {
+            Err(Error(ErrorKind::DuplicateBlock(ref name), _)) if name == "mass" => (),
+            other => panic!("Expected DuplicateBlock, got {:?}", other),
+        }
+        match slha.rename_block("sminput", "foo") {
+            Err(Error(ErrorKind::MissingBlock(ref name), _)) if name == "sminput" => (),
+            other => panic!("Expected MissingBlock, got {:?}", other),
+        }
+        assert!(slha.get_raw_block("sminputs").is_some());
+        assert!(slha.get_raw_block("mass").is_some());
     }
 
     #[test]
-    fn test_parse_block_single() {
+    fn test_duplicate_policy_error() {
         let input = "\
-BLOCK TEST
-   3
-  ";
+BLOCK MODSEL
+    1    1
+BLOCK MASS
+    6   173.2
+Block modsel
+    1    2
+";
+        let options = ParseOptions {
+            on_duplicate_block: DuplicatePolicy::Error,
+            ..ParseOptions::default()
+        };
+        match Slha::parse_with_options(input, &options) {
+            Err(Error(ErrorKind::DuplicateBlock(name), _)) => assert_eq!(name, "modsel"),
+            Err(err) => panic!("Wrong error variant {:?} instead of DuplicateBlock", err),
+            Ok(_) => panic!("Unexpected success"),
+        }
+
         let slha = Slha::parse(input).unwrap();
-        println!("{:?}", slha);
-        let block: BlockSingle<i64> = slha.get_block("test").unwrap().unwrap();
-        assert_eq!(block.value, 3);
+        assert_eq!(slha.get_raw_blocks("modsel").len(), 2);
+        let modsel: Result<Block<i64, i64>, Error> = slha.get_block("modsel").unwrap();
+        assert!(modsel.is_err());
+
+        let input = "\
+BLOCK MODSEL
+    1    1
+BLOCK MASS
+    6   173.2
+";
+        let slha = Slha::parse_with_options(input, &options).unwrap();
+        assert_eq!(slha.get_raw_blocks("modsel").len(), 1);
     }
 
     #[test]
-    fn test_parse_block_single_comments() {
+    fn test_block_to_matrix() {
+        let mut input = String::from("Block USQMIX Q= 4.67034192e+02\n");
+        for i in 1..7 {
+            for j in 1..7 {
+                input.push_str(&format!("   {}  {}   {:.8e}\n", i, j, (10 * i + j) as f64));
+            }
+        }
+        let slha = Slha::parse(&input).unwrap();
+        let usqmix: Block<(u8, u8), f64> = slha.get_block("usqmix").unwrap().unwrap();
+        let (n, matrix) = usqmix.to_matrix().unwrap();
+        assert_eq!(n, 6);
+        assert_eq!(matrix.len(), 6);
+        for (i, row) in matrix.iter().enumerate() {
+            assert_eq!(row.len(), 6);
+            for (j, &value) in row.iter().enumerate() {
+                assert_eq!(value, (10 * (i + 1) + j + 1) as f64);
+            }
+        }
+
+        let mut usqmix = usqmix;
+        usqmix.map.remove(&(3, 4));
+        match usqmix.to_matrix() {
+            Err(Error(ErrorKind::MissingMatrixEntry(3, 4), _)) => (),
+            Err(err) => panic!("Wrong error variant {:?} instead of MissingMatrixEntry", err),
+            Ok(_) => panic!("Unexpected success"),
+        }
+
         let input = "\
-# This is a test
-BLOCK TEST # A blkoc of type test
-# Single blocks only contain one line wiht one isngle value on it
-   3  # The value of this single block
-# That was it. No more stuff
-# is allowed in this block
-  ";
+Block NMIX
+   1  1   0.9
+   0  1   0.1
+";
         let slha = Slha::parse(input).unwrap();
-        println!("{:?}", slha);
-        let block: BlockSingle<i64> = slha.get_block("test").unwrap().unwrap();
-        assert_eq!(block.value, 3);
+        let nmix: Block<(i8, i8), f64> = slha.get_block("nmix").unwrap().unwrap();
+        match nmix.to_matrix() {
+            Err(Error(ErrorKind::InvalidMatrixIndex(0, 1), _)) => (),
+            Err(err) => panic!("Wrong error variant {:?} instead of InvalidMatrixIndex", err),
+            Ok(_) => panic!("Unexpected success"),
+        }
+
+        let empty = Block::<(u8, u8), f64>::from_body("", None).unwrap();
+        assert_eq!(empty.to_matrix().unwrap(), (0, Vec::new()));
+
+        // A huge index must not allocate the full matrix before the entries are checked.
+        let huge = Block::<(i32, i32), f64>::from_body("   2000000000  1   1.0\n", None).unwrap();
+        match huge.to_matrix() {
+            Err(Error(ErrorKind::MissingMatrixEntry(1, 1), _)) => (),
+            Err(err) => panic!("Wrong error variant {:?} instead of MissingMatrixEntry", err),
+            Ok(_) => panic!("Unexpected success"),
+        }
+        let huge = Block::<(i64, i64), f64>::from_body(
+            "   1  1   1.0\n   4294967296  1   1.0\n",
+            None,
+        ).unwrap();
+        match huge.to_matrix() {
+            Err(Error(ErrorKind::MissingMatrixEntry(1, 2), _)) => (),
+            Err(err) => panic!("Wrong error variant {:?} instead of MissingMatrixEntry", err),
+            Ok(_) => panic!("Unexpected success"),
+        }
+    }
+
+    #[test]
+    fn test_block_to_tensor3() {
+        let input = "\
+Block RVLAMLLE Q= 1.0e+03   # lambda_{ijk}
+   1  2  1    1.0e-02
+   1  2  2    2.0e-02
+   1  1  1    0.0
+   1  1  2    0.0
+   2  1  1   -1.0e-02
+   2  1  2   -2.0e-02
+   2  2  1    0.0
+   2  2  2    0.0
+";
+        let slha = Slha::parse(input).unwrap();
+        let lambda: Block<(i8, i8, i8), f64> = slha.get_block("rvlamlle").unwrap().unwrap();
+        assert_eq!(lambda.scale, Some(1000.));
+        assert_eq!(lambda.map[&(2, 1, 2)], -2.0e-02);
+        let (n, tensor) = lambda.to_tensor3().unwrap();
+        assert_eq!(n, 2);
+        assert_eq!(tensor.len(), 2);
+        assert_eq!(tensor[0], vec![vec![0., 0.], vec![1.0e-02, 2.0e-02]]);
+        assert_eq!(tensor[1], vec![vec![-1.0e-02, -2.0e-02], vec![0., 0.]]);
+
+        let mut lambda = lambda;
+        lambda.map.remove(&(2, 2, 1));
+        match lambda.to_tensor3() {
+            Err(Error(ErrorKind::MissingTensorEntry(2, 2, 1), _)) => (),
+            Err(err) => panic!("Wrong error variant {:?} instead of MissingTensorEntry", err),
+            Ok(_) => panic!("Unexpected success"),
+        }
+        lambda.map.insert((0, 1, 1), 1.);
+        match lambda.to_tensor3() {
+            Err(Error(ErrorKind::InvalidTensorIndex(0, 1, 1), _)) => (),
+            Err(err) => panic!("Wrong error variant {:?} instead of InvalidTensorIndex", err),
+            Ok(_) => panic!("Unexpected success"),
+        }
+
+        let empty = Block::<(u8, u8, u8), f64>::from_body("", None).unwrap();
+        assert_eq!(empty.to_tensor3().unwrap(), (0, Vec::new()));
+
+        // A huge index must not allocate the full tensor before the entries are checked.
+        let huge = Block::<(i32, i32, i32), f64>::from_body("   1  1  2000000   1.0\n", None)
+            .unwrap();
+        match huge.to_tensor3() {
+            Err(Error(ErrorKind::MissingTensorEntry(1, 1, 1), _)) => (),
+            Err(err) => panic!("Wrong error variant {:?} instead of MissingTensorEntry", err),
+            Ok(_) => panic!("Unexpected success"),
+        }
+        let huge = Block::<(i64, i64, i64), f64>::from_body("   1  1  3000000   1.0\n", None)
+            .unwrap();
+        match huge.to_tensor3() {
+            Err(Error(ErrorKind::MissingTensorEntry(1, 1, 1), _)) => (),
+            Err(err) => panic!("Wrong error variant {:?} instead of MissingTensorEntry", err),
+            Ok(_) => panic!("Unexpected success"),
+        }
     }
 
     #[test]
@@ -2712,6 +10861,48 @@ Block hmix Q= 4.64649125e+02  # Higgs mixing parameters
         assert_eq!(hmix.map[&4], 1.69697051e+04);
     }
 
+    #[test]
+    fn test_decay_table_multiplicity() {
+        let input = "\
+DECAY   1000021    1.01752300e+00   # gluino decays
+    4.18313300E-02     2     1000001        -1   # BR(~g -> ~d_L dbar)
+    1.55587600E-02     2     2000001        -1   # BR(~g -> ~d_R dbar)
+    1.13021900E-01     2     1000005        -5   # BR(~g -> ~b_1 bbar)
+    9.60140900E-02     2     1000006        -6   # BR(~g -> ~t_1 tbar)
+DECAY   1000023    2.07770048E-02   # neutralino2 decays
+    1.90e-01    2     1000022        23
+    2.00e-01    3     1000022        -1         1
+    3.00e-01    3     1000022        -5         5
+    1.00e-01    4     1000022        -5         5        22
+    2.10e-01    2     1000022        25
+DECAY   1000022    0.0
+";
+        let slha = Slha::parse(input).unwrap();
+        let gluino = slha.get_decay(1000021).unwrap();
+        assert_eq!(gluino.n_body(2).len(), 4);
+        assert!(gluino.n_body(3).is_empty());
+        let groups = gluino.group_by_multiplicity();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[&2], gluino.decays.iter().collect::<Vec<_>>());
+
+        let neutralino = slha.get_decay(1000023).unwrap();
+        let two_body = neutralino.n_body(2);
+        assert_eq!(two_body.len(), 2);
+        assert_eq!(two_body[0].daughters, vec![1000022, 23]);
+        assert_eq!(two_body[1].daughters, vec![1000022, 25]);
+        assert_eq!(neutralino.n_body(3).len(), 2);
+        assert_eq!(neutralino.n_body(4)[0].branching_ratio, 1.00e-01);
+        assert!(neutralino.n_body(1).is_empty());
+        let groups = neutralino.group_by_multiplicity();
+        assert_eq!(groups.keys().cloned().collect::<Vec<_>>(), vec![2, 3, 4]);
+        assert_eq!(groups[&3][0].branching_ratio, 2.00e-01);
+        assert_eq!(groups[&3][1].branching_ratio, 3.00e-01);
+
+        let stable = slha.get_decay(1000022).unwrap();
+        assert!(stable.n_body(2).is_empty());
+        assert!(stable.group_by_multiplicity().is_empty());
+    }
+
     #[test]
     fn test_example_decay() {
         // Pieces of the example file from appendix D.3 of the slha1 paper(arXiv:hep-ph/0311123)
@@ -3136,6 +11327,27 @@ Block MINPAR  # SUSY breaking input parameters
         }
     }
 
+    #[test]
+    fn test_block_scale_in_parentheses() {
+        let input = "\
+Block ye (Q= 464.6)   # Yukawa couplings
+    3  3 1.00e-01
+Block yu ( q=91.1876 )
+    3  3 8.9e-01
+Block yd (Q= 10
+    3  3 1.4e-01
+";
+        let slha = Slha::parse(&input[..input.find("Block yd").unwrap()]).unwrap();
+        assert_eq!(slha.get_raw_block("ye").unwrap().scale, Some(464.6));
+        assert_eq!(slha.get_raw_block("yu").unwrap().scale, Some(91.1876));
+        let err = Slha::parse(input).unwrap_err();
+        if let Error(ErrorKind::InvalidBlock(name), _) = err {
+            assert_eq!(&name, "yd");
+        } else {
+            panic!("Wrong error variant {:?} instead of InvalidBlock", err);
+        }
+    }
+
     #[test]
     fn test_duplicate_block() {
         // Example file from appendix D.1 of the slha1 paper(arXiv:hep-ph/0311123)
@@ -3657,6 +11869,24 @@ Block flup Q= 4.64649125e+03
         }
     }
 
+    #[test]
+    fn test_forbidden_scale() {
+        let input = "\
+Block MODSEL Q= 1  # Select model
+     1    1   # sugra
+Block SMINPUTS   # Standard Model inputs
+     3      0.1172  # alpha_s(MZ) SM MSbar
+";
+        let slha = Slha::parse(input).unwrap();
+        assert!(slha.validate_scales(&["sminputs"]).is_ok());
+        let err = slha.validate_scales(&["sminputs", "MODSEL"]).unwrap_err();
+        if let Error(ErrorKind::ForbiddenScale(name), _) = err {
+            assert_eq!(name, "modsel");
+        } else {
+            panic!("Wrong error variant {:?} instead of ForbiddenScale", err);
+        }
+    }
+
     #[test]
     fn test_duplicate_decay() {
         // Pieces of the example file from appendix D.3 of the slha1 paper(arXiv:hep-ph/0311123)
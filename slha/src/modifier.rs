@@ -15,25 +15,33 @@
 //!
 //! # Available modifiers
 //!
-//! There are currently two wrappers available to modify the deserialization of single blocks:
+//! There are currently four wrappers available to modify the deserialization of single blocks:
 //!
 //! * `TakeFirst` will ignore all but the first occurence of a block.
 //! * `TakeLast` allows blocks to be 'overridden', i.e. the last occurence of a block is returned.
+//! * `HighestScale` returns the occurence of a block with the highest scale.
+//! * `LowestScale` returns the occurence of a block with the lowest scale.
 //!
 //! The `VecUnchecked` type allows to collect all occurences of a block, without the sanity checks
 //! that are performed when using a plain `Vec`.
 //!
+//! The `Sum` wrapper adds up the values of all occurences of a block.
+//!
+//! The `ExactLen` wrapper checks that a block contains exactly the expected number of entries.
+//!
 //! # Adding more (internal)
 //!
 //! To define additional wrapper types like this, it is sufficient to have them implement the
 //! `internal::WrappedBlock` trait.
 //! Any type implementing this trait can be used as a field type by the `derive` macro.
 
-use {SlhaBlock, RawBlock};
+use {Block, BlockSingle, Line, Parseable, SlhaBlock, RawBlock};
 use internal::WrappedBlock;
 use errors::*;
 
-use std::ops::Deref;
+use std::collections::hash_map::Entry;
+use std::hash::Hash;
+use std::ops::{AddAssign, Deref};
 
 /// An alternative to `Vec` that does not check for duplicate blocks.
 ///
@@ -232,3 +240,307 @@ impl<T> Deref for TakeLast<T> {
         value
     }
 }
+
+/// A modifier that selects the occurence of a block with the highest scale.
+///
+/// Blocks without a scale are treated as having a lower scale than any block with a scale.
+/// If several occurences share the highest scale, the first of them is returned.
+///
+/// # Examples
+///
+/// ```rust
+/// extern crate slha;
+/// #[macro_use]
+/// extern crate slha_derive;
+///
+/// use slha::{SlhaDeserialize, Block};
+/// use slha::modifier::HighestScale;
+///
+/// fn main() {
+///    let input = "\
+/// Block ye Q= 20
+///     3  3 9.0e-02
+/// Block ye Q= 40
+///     3  3 7.0e-02
+/// Block ye
+///     3  3 1.0e-01
+/// Block ye Q= 30
+///     3  3 8.0e-02
+/// ";
+///
+///     #[derive(Debug, SlhaDeserialize)]
+///     struct MySlha {
+///         ye: HighestScale<Block<(u8, u8), f64>>,
+///     }
+///
+///     let slha = MySlha::deserialize(input).unwrap();
+///     assert_eq!(slha.ye.scale, Some(40.));
+///     assert_eq!(slha.ye.map[&(3, 3)], 7.0e-02);
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct HighestScale<T>(pub T);
+impl<T> WrappedBlock<Error> for HighestScale<T>
+where
+    T: SlhaBlock,
+{
+    type Wrapper = Option<T>;
+    fn parse_into<'a>(block: &RawBlock<'a>, wrapped: &mut Option<T>, name: &str) -> Result<()> {
+        let block: T = block.to_block(name)?;
+        match *wrapped {
+            Some(ref current) if block.scale() <= current.scale() => (),
+            _ => *wrapped = Some(block),
+        }
+        Ok(())
+    }
+    fn unwrap(name: &str, wrapped: Option<T>) -> Result<HighestScale<T>> {
+        match wrapped {
+            Some(block) => Ok(HighestScale(block)),
+            None => Err(ErrorKind::MissingBlock(name.to_string()).into()),
+        }
+    }
+}
+impl<T> Deref for HighestScale<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        let HighestScale(ref value) = *self;
+        value
+    }
+}
+
+/// A modifier that selects the occurence of a block with the lowest scale.
+///
+/// Blocks without a scale are treated as having a lower scale than any block with a scale.
+/// If several occurences share the lowest scale, the first of them is returned.
+///
+/// # Examples
+///
+/// ```rust
+/// extern crate slha;
+/// #[macro_use]
+/// extern crate slha_derive;
+///
+/// use slha::{SlhaDeserialize, Block};
+/// use slha::modifier::LowestScale;
+///
+/// fn main() {
+///    let input = "\
+/// Block ye Q= 20
+///     3  3 9.0e-02
+/// Block ye Q= 40
+///     3  3 7.0e-02
+/// Block ye Q= 10
+///     3  3 1.0e-01
+/// ";
+///
+///     #[derive(Debug, SlhaDeserialize)]
+///     struct MySlha {
+///         ye: LowestScale<Block<(u8, u8), f64>>,
+///     }
+///
+///     let slha = MySlha::deserialize(input).unwrap();
+///     assert_eq!(slha.ye.scale, Some(10.));
+///     assert_eq!(slha.ye.map[&(3, 3)], 1.0e-01);
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LowestScale<T>(pub T);
+impl<T> WrappedBlock<Error> for LowestScale<T>
+where
+    T: SlhaBlock,
+{
+    type Wrapper = Option<T>;
+    fn parse_into<'a>(block: &RawBlock<'a>, wrapped: &mut Option<T>, name: &str) -> Result<()> {
+        let block: T = block.to_block(name)?;
+        match *wrapped {
+            Some(ref current) if block.scale() >= current.scale() => (),
+            _ => *wrapped = Some(block),
+        }
+        Ok(())
+    }
+    fn unwrap(name: &str, wrapped: Option<T>) -> Result<LowestScale<T>> {
+        match wrapped {
+            Some(block) => Ok(LowestScale(block)),
+            None => Err(ErrorKind::MissingBlock(name.to_string()).into()),
+        }
+    }
+}
+impl<T> Deref for LowestScale<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        let LowestScale(ref value) = *self;
+        value
+    }
+}
+
+/// A trait for blocks whose occurences can be added up by the `Sum` modifier.
+pub trait Summable {
+    /// Adds the contents of `other` to `self`.
+    fn add_block(&mut self, other: Self);
+}
+impl<V> Summable for BlockSingle<V>
+where
+    V: AddAssign,
+{
+    fn add_block(&mut self, other: BlockSingle<V>) {
+        self.value += other.value;
+    }
+}
+impl<K, V> Summable for Block<K, V>
+where
+    K: Hash + Eq,
+    V: AddAssign,
+{
+    fn add_block(&mut self, other: Block<K, V>) {
+        for (key, value) in other.map {
+            match self.map.entry(key) {
+                Entry::Occupied(entry) => *entry.into_mut() += value,
+                Entry::Vacant(entry) => {
+                    entry.insert(value);
+                }
+            }
+        }
+    }
+}
+
+/// A modifier that adds up all occurences of a block in an SLHA file.
+///
+/// For a `BlockSingle` the result contains the sum of the values of all occurences.
+/// For a `Block` the values are added up key by key, where keys that only appear in some of the
+/// occurences are treated as if they were zero in the others.
+/// The scale of the result is the scale of the first occurence of the block.
+///
+/// # Examples
+///
+/// ```rust
+/// extern crate slha;
+/// #[macro_use]
+/// extern crate slha_derive;
+///
+/// use slha::{SlhaDeserialize, Block, BlockSingle};
+/// use slha::modifier::Sum;
+///
+/// fn main() {
+///    let input = "\
+/// Block WIDTHQCD
+///      1.5
+/// Block PARTIAL
+///      1     0.5
+///      2     0.25
+/// Block WIDTHQCD
+///      0.25
+/// Block PARTIAL
+///      2     0.5
+///      3     1.0
+/// ";
+///
+///     #[derive(Debug, SlhaDeserialize)]
+///     struct MySlha {
+///         widthqcd: Sum<BlockSingle<f64>>,
+///         partial: Sum<Block<i8, f64>>,
+///     }
+///
+///     let slha = MySlha::deserialize(input).unwrap();
+///     assert_eq!(slha.widthqcd.value, 1.75);
+///     assert_eq!(slha.partial.map[&1], 0.5);
+///     assert_eq!(slha.partial.map[&2], 0.75);
+///     assert_eq!(slha.partial.map[&3], 1.0);
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Sum<T>(pub T);
+impl<T> WrappedBlock<Error> for Sum<T>
+where
+    T: SlhaBlock + Summable,
+{
+    type Wrapper = Option<T>;
+    fn parse_into<'a>(block: &RawBlock<'a>, wrapped: &mut Option<T>, name: &str) -> Result<()> {
+        let block = block.to_block(name)?;
+        match *wrapped {
+            Some(ref mut sum) => sum.add_block(block),
+            None => *wrapped = Some(block),
+        }
+        Ok(())
+    }
+    fn unwrap(name: &str, wrapped: Option<T>) -> Result<Sum<T>> {
+        match wrapped {
+            Some(block) => Ok(Sum(block)),
+            None => Err(ErrorKind::MissingBlock(name.to_string()).into()),
+        }
+    }
+}
+impl<T> Deref for Sum<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        let Sum(ref value) = *self;
+        value
+    }
+}
+
+/// A block that must contain exactly `N` entries.
+///
+/// This can be used for blocks with a fixed size, like the 2x2 mixing matrices, to detect
+/// missing or additional entries already while reading the SLHA file.
+/// A `WrongNumberOfEntries` error is returned if the number of entries differs from `N`.
+///
+/// Since `ExactLen` implements `SlhaBlock`, it can be used everywhere a plain `Block` can be used,
+/// including inside of `Option`, `Vec` and the other modifiers.
+/// Note that the parser used by `slha-derive` does not understand const generic arguments, so
+/// inside of a struct that `SlhaDeserialize` is derived for, the type has to be given through a
+/// type alias.
+///
+/// # Examples
+///
+/// ```rust
+/// extern crate slha;
+/// #[macro_use]
+/// extern crate slha_derive;
+///
+/// use slha::{SlhaDeserialize, Block};
+/// use slha::modifier::ExactLen;
+///
+/// fn main() {
+///    let input = "\
+/// Block stopmix  # stop mixing matrix
+///   1  1     5.37975095e-01   # O_{11}
+///   1  2     8.42960733e-01   # O_{12}
+///   2  1     8.42960733e-01   # O_{21}
+///   2  2    -5.37975095e-01   # O_{22}
+/// ";
+///
+///     type Mixing2x2 = ExactLen<4, Block<(u8, u8), f64>>;
+///
+///     #[derive(Debug, SlhaDeserialize)]
+///     struct MySlha {
+///         stopmix: Mixing2x2,
+///     }
+///
+///     let slha = MySlha::deserialize(input).unwrap();
+///     assert_eq!(slha.stopmix.map[&(2, 2)], -5.37975095e-01);
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExactLen<const N: usize, B>(pub B);
+impl<const N: usize, K, V> SlhaBlock for ExactLen<N, Block<K, V>>
+where
+    K: Hash + Eq + Parseable,
+    V: Parseable,
+{
+    fn parse<'a>(lines: &[Line<'a>], scale: Option<f64>) -> Result<Self> {
+        let block = Block::parse(lines, scale)?;
+        if block.map.len() != N {
+            bail!(ErrorKind::WrongNumberOfEntries(N, block.map.len()));
+        }
+        Ok(ExactLen(block))
+    }
+    fn scale(&self) -> Option<f64> {
+        self.0.scale
+    }
+}
+impl<const N: usize, B> Deref for ExactLen<N, B> {
+    type Target = B;
+    fn deref(&self) -> &B {
+        let ExactLen(ref block) = *self;
+        block
+    }
+}
@@ -0,0 +1,318 @@
+// Copyright 2017 Torsten Weber
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains a declarative description of the structure of an SLHA file.
+//!
+//! A `Schema` lists blocks and decay tables that have to be present in a file, together with
+//! constraints on the number of their entries.
+//! It is checked against an `Slha` object using only the raw data, i.e. without parsing the blocks
+//! into concrete types, so the same schema can be reused for files that are read in different
+//! ways.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use slha::Slha;
+//! use slha::schema::Schema;
+//!
+//! let input = "\
+//! Block MASS
+//!    6    173.2
+//! Block stopmix  # stop mixing matrix
+//!    1  1     5.37975095e-01   # O_{11}
+//!    1  2     8.42960733e-01   # O_{12}
+//!    2  1     8.42960733e-01   # O_{21}
+//!    2  2    -5.37975095e-01   # O_{22}
+//! DECAY 6 1.35
+//!    1.0   2   5   24
+//! ";
+//!
+//! let schema = Schema::new()
+//!     .block("stopmix")
+//!     .exact_entries(4)
+//!     .block("mass")
+//!     .min_entries(1)
+//!     .decay(6)
+//!     .min_entries(1);
+//! let slha = Slha::parse(input).unwrap();
+//! assert!(schema.validate(&slha).is_ok());
+//! ```
+
+use Slha;
+use errors::*;
+
+/// A set of structural constraints on the blocks and decay tables of an SLHA file.
+///
+/// A schema is built by naming a block or decay table using `block` or `decay`, followed by the
+/// constraints on the number of its entries, which apply to the block or decay table named last.
+/// Every block and decay table named in the schema has to be present in the file.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Schema {
+    rules: Vec<Rule>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Rule {
+    target: Target,
+    min: Option<usize>,
+    max: Option<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Target {
+    Block(String),
+    Decay(i64),
+}
+
+impl Schema {
+    /// Creates an empty schema, which accepts every SLHA file.
+    pub fn new() -> Schema {
+        Schema::default()
+    }
+
+    /// Requires the block `name` to be present.
+    ///
+    /// The constraints following this call apply to every occurrence of the block.
+    /// As everywhere else, the name is case insensitive unless the file was read with
+    /// `case_sensitive` set.
+    pub fn block(mut self, name: &str) -> Schema {
+        self.rules.push(Rule {
+            target: Target::Block(name.to_string()),
+            min: None,
+            max: None,
+        });
+        self
+    }
+
+    /// Requires the decay table of the particle `pdg_id` to be present.
+    ///
+    /// The constraints following this call apply to the number of decay modes in the table.
+    pub fn decay(mut self, pdg_id: i64) -> Schema {
+        self.rules.push(Rule {
+            target: Target::Decay(pdg_id),
+            min: None,
+            max: None,
+        });
+        self
+    }
+
+    /// Requires the current block or decay table to have exactly `n` entries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if neither `block` nor `decay` has been called before.
+    pub fn exact_entries(self, n: usize) -> Schema {
+        self.min_entries(n).max_entries(n)
+    }
+
+    /// Requires the current block or decay table to have at least `n` entries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if neither `block` nor `decay` has been called before.
+    pub fn min_entries(mut self, n: usize) -> Schema {
+        self.current().min = Some(n);
+        self
+    }
+
+    /// Requires the current block or decay table to have at most `n` entries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if neither `block` nor `decay` has been called before.
+    pub fn max_entries(mut self, n: usize) -> Schema {
+        self.current().max = Some(n);
+        self
+    }
+
+    /// Checks that `slha` satisfies all constraints of the schema.
+    ///
+    /// The blocks are only counted, not parsed, so a block may still fail to be read into a
+    /// concrete type.
+    /// Lazily read decay tables, see `Slha::parse_blocks_only`, are parsed to count their decay
+    /// modes, and an error is returned if they can not be parsed.
+    ///
+    /// # Errors
+    ///
+    /// The constraints are checked in the order in which they were added to the schema and the
+    /// first violation is returned.
+    /// A missing block is reported as a `MissingBlock` error and a missing decay table as a
+    /// `MissingDecay` error.
+    /// If a lazily read decay table could not be parsed, the `InvalidDecay` error returned by
+    /// `Slha::try_get_decay` is passed on.
+    /// If the number of entries is wrong, a `WrongNumberOfEntries`, `TooFewEntries` or
+    /// `TooManyEntries` error is returned, wrapped in an `InvalidBlock` or `InvalidDecay` error.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use slha::Slha;
+    /// use slha::schema::Schema;
+    /// use slha::errors::{Error, ErrorKind};
+    ///
+    /// let input = "\
+    /// Block MASS
+    ///    6    173.2
+    /// ";
+    ///
+    /// let slha = Slha::parse(input).unwrap();
+    /// let schema = Schema::new().block("mass").max_entries(5).block("stopmix");
+    /// match schema.validate(&slha) {
+    ///     Err(Error(ErrorKind::MissingBlock(ref name), _)) => assert_eq!(name, "stopmix"),
+    ///     other => panic!("Unexpected result {:?}", other),
+    /// }
+    /// ```
+    pub fn validate(&self, slha: &Slha) -> Result<()> {
+        for rule in &self.rules {
+            match rule.target {
+                Target::Block(ref name) => {
+                    let blocks = slha.get_raw_blocks(name);
+                    if blocks.is_empty() {
+                        bail!(ErrorKind::MissingBlock(name.clone()));
+                    }
+                    for block in blocks {
+                        rule.check(block.lines.len()).chain_err(
+                            || ErrorKind::InvalidBlock(name.clone()),
+                        )?;
+                    }
+                }
+                Target::Decay(pdg_id) => {
+                    let table = match slha.try_get_decay(pdg_id) {
+                        Some(table) => table?,
+                        None => bail!(ErrorKind::MissingDecay(pdg_id)),
+                    };
+                    rule.check(table.decays.len()).chain_err(
+                        || ErrorKind::InvalidDecay(pdg_id),
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn current(&mut self) -> &mut Rule {
+        self.rules.last_mut().expect(
+            "Schema: `block` or `decay` has to be called before adding constraints",
+        )
+    }
+}
+
+impl Rule {
+    fn check(&self, found: usize) -> Result<()> {
+        match (self.min, self.max) {
+            (Some(min), Some(max)) if min == max && found != min => {
+                bail!(ErrorKind::WrongNumberOfEntries(min, found))
+            }
+            (Some(min), _) if found < min => bail!(ErrorKind::TooFewEntries(min, found)),
+            (_, Some(max)) if found > max => bail!(ErrorKind::TooManyEntries(max, found)),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Schema;
+    use {ParseOptions, Slha};
+    use errors::{Error, ErrorKind};
+
+    const INPUT: &str = "\
+Block MASS
+   6    173.2
+   5    4.25
+Block stopmix  # stop mixing matrix
+   1  1     5.37975095e-01   # O_{11}
+   1  2     8.42960733e-01   # O_{12}
+   2  2    -5.37975095e-01   # O_{22}
+DECAY 6 1.35
+   1.0   2   5   24
+";
+
+    #[test]
+    fn test_schema_malformed_stopmix() {
+        let slha = Slha::parse(INPUT).unwrap();
+        let schema = Schema::new().block("mass").min_entries(1);
+        assert!(schema.validate(&slha).is_ok());
+
+        let schema = schema.block("stopmix").exact_entries(4);
+        let err = schema.validate(&slha).unwrap_err();
+        match err {
+            Error(ErrorKind::InvalidBlock(ref name), _) => assert_eq!(name, "stopmix"),
+            ref err => panic!("Wrong error variant {:?} instead of InvalidBlock", err),
+        }
+        assert_eq!(
+            err.iter().nth(1).unwrap().to_string(),
+            "Expected 4 entries in the block, but found 3"
+        );
+    }
+
+    #[test]
+    fn test_schema_min_max() {
+        let slha = Slha::parse(INPUT).unwrap();
+        let err = Schema::new()
+            .block("MASS")
+            .min_entries(3)
+            .validate(&slha)
+            .unwrap_err();
+        assert_eq!(
+            err.iter().nth(1).unwrap().to_string(),
+            "Expected at least 3 entries, but found 2"
+        );
+        let err = Schema::new()
+            .decay(6)
+            .max_entries(0)
+            .validate(&slha)
+            .unwrap_err();
+        match err {
+            Error(ErrorKind::InvalidDecay(6), _) => (),
+            ref err => panic!("Wrong error variant {:?} instead of InvalidDecay", err),
+        }
+        assert_eq!(
+            err.iter().nth(1).unwrap().to_string(),
+            "Expected at most 0 entries, but found 1"
+        );
+        match Schema::new().decay(6).decay(25).validate(&slha) {
+            Err(Error(ErrorKind::MissingDecay(25), _)) => (),
+            other => panic!("Unexpected result {:?} instead of MissingDecay", other),
+        }
+        assert!(
+            Schema::new()
+                .block("mass")
+                .min_entries(1)
+                .max_entries(2)
+                .decay(6)
+                .exact_entries(1)
+                .validate(&slha)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_schema_invalid_lazy_decay() {
+        let input = "\
+DECAY 6 1.35
+   1.0   2   5   foo
+";
+        let slha = Slha::parse_blocks_only(input, &ParseOptions::default()).unwrap();
+        let err = Schema::new().decay(6).validate(&slha).unwrap_err();
+        match err {
+            Error(ErrorKind::InvalidDecay(6), _) => (),
+            ref err => panic!("Wrong error variant {:?} instead of InvalidDecay", err),
+        }
+        assert_eq!(
+            err.iter().nth(1).unwrap().to_string(),
+            "Failed to parse the 1th data line in the body"
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_schema_constraint_without_target() {
+        let _ = Schema::new().min_entries(1);
+    }
+}
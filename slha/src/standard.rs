@@ -0,0 +1,338 @@
+// Copyright 2017 Torsten Weber
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Typed versions of commonly used blocks defined in the SLHA standards.
+//!
+//! The types in this module implement `SlhaBlock` and can therefore be used everywhere a `Block`
+//! can be used, e.g. as field types in structs that `SlhaDeserialize` is derived for or with the
+//! `get_block` methods of an `Slha` object.
+//! Instead of a map from the integer index to the value, the entries with an index defined in the
+//! standard are accessible through fields with a descriptive name.
+//! Entries with indices that are not defined in the standard are kept in the `other` map, so no
+//! information from the file is lost.
+//!
+//! # Examples
+//!
+//! ```rust
+//! # extern crate slha;
+//! # #[macro_use]
+//! # extern crate slha_derive;
+//! #
+//! use slha::SlhaDeserialize;
+//! use slha::standard::{Gauge, Hmix};
+//!
+//! #[derive(Debug, SlhaDeserialize)]
+//! struct Slha {
+//!     gauge: Gauge,
+//!     hmix: Hmix,
+//! }
+//! #
+//! # fn main() {
+//! let input = "\
+//! Block gauge Q= 4.64649125e+02
+//!      1     3.60872342e-01   # g'(Q)MSSM DRbar
+//!      2     6.46479280e-01   # g(Q)MSSM DRbar
+//!      3     1.09623002e+00   # g3(Q)MSSM DRbar
+//! Block hmix Q= 4.64649125e+02  # Higgs mixing parameters
+//!      1     3.58660361e+02   # mu(Q)MSSM DRbar
+//!      2     9.75139550e+00   # tan beta(Q)MSSM DRbar
+//! ";
+//!
+//! let slha = Slha::deserialize(input).unwrap();
+//! assert_eq!(slha.gauge.scale, Some(4.64649125e+02));
+//! assert_eq!(slha.gauge.g3, Some(1.09623002e+00));
+//! assert_eq!(slha.hmix.mu, Some(3.58660361e+02));
+//! assert_eq!(slha.hmix.tan_beta, Some(9.75139550e+00));
+//! assert_eq!(slha.hmix.vev, None);
+//! # }
+//! ```
+
+use {Block, Line, SlhaBlock};
+use errors::*;
+
+use std::collections::HashMap;
+
+macro_rules! standard_block {
+    (
+        $(#[$attr:meta])*
+        pub struct $name:ident {
+            $(
+                $(#[$field_attr:meta])*
+                $field:ident = $index:expr,
+            )*
+        }
+    ) => {
+        $(#[$attr])*
+        #[derive(Clone, Debug, PartialEq)]
+        pub struct $name {
+            /// The scale at which this block is defined, if any.
+            pub scale: Option<f64>,
+            $(
+                $(#[$field_attr])*
+                pub $field: Option<f64>,
+            )*
+            /// All entries with an index that is not defined in the standard.
+            pub other: HashMap<i64, f64>,
+        }
+        impl SlhaBlock for $name {
+            fn parse<'a>(lines: &[Line<'a>], scale: Option<f64>) -> Result<Self> {
+                let Block { mut map, scale } = Block::<i64, f64>::parse(lines, scale)?;
+                Ok($name {
+                    scale,
+                    $(
+                        $field: map.remove(&$index),
+                    )*
+                    other: map,
+                })
+            }
+            fn scale(&self) -> Option<f64> {
+                self.scale
+            }
+        }
+    }
+}
+
+standard_block! {
+    /// The `SMINPUTS` block containing the Standard Model input parameters.
+    ///
+    /// This includes the light fermion masses added in SLHA2 (indices 8 and 11 to 24).
+    pub struct SmInputs {
+        /// The inverse electromagnetic coupling 1/alpha_em(m_Z) in the MSbar scheme (index 1).
+        alpha_em_inv = 1,
+        /// The Fermi constant G_F in GeV^-2 (index 2).
+        g_fermi = 2,
+        /// The strong coupling alpha_s(m_Z) in the MSbar scheme (index 3).
+        alpha_s_mz = 3,
+        /// The pole mass of the Z boson (index 4).
+        m_z = 4,
+        /// The bottom mass m_b(m_b) in the MSbar scheme (index 5).
+        m_b = 5,
+        /// The pole mass of the top quark (index 6).
+        m_t = 6,
+        /// The pole mass of the tau lepton (index 7).
+        m_tau = 7,
+        /// The pole mass of the third generation neutrino (index 8).
+        m_nu3 = 8,
+        /// The pole mass of the electron (index 11).
+        m_e = 11,
+        /// The pole mass of the first generation neutrino (index 12).
+        m_nu1 = 12,
+        /// The pole mass of the muon (index 13).
+        m_mu = 13,
+        /// The pole mass of the second generation neutrino (index 14).
+        m_nu2 = 14,
+        /// The down quark mass m_d(2 GeV) in the MSbar scheme (index 21).
+        m_d = 21,
+        /// The up quark mass m_u(2 GeV) in the MSbar scheme (index 22).
+        m_u = 22,
+        /// The strange quark mass m_s(2 GeV) in the MSbar scheme (index 23).
+        m_s = 23,
+        /// The charm mass m_c(m_c) in the MSbar scheme (index 24).
+        m_c = 24,
+    }
+}
+
+standard_block! {
+    /// The `GAUGE` block containing the gauge couplings.
+    pub struct Gauge {
+        /// The U(1)_Y coupling g' (index 1).
+        g_prime = 1,
+        /// The SU(2)_L coupling g (index 2).
+        g = 2,
+        /// The SU(3)_C coupling g3 (index 3).
+        g3 = 3,
+    }
+}
+
+standard_block! {
+    /// The `HMIX` block containing the parameters of the Higgs sector.
+    pub struct Hmix {
+        /// The mu parameter (index 1).
+        mu = 1,
+        /// The ratio of the vacuum expectation values tan(beta) (index 2).
+        tan_beta = 2,
+        /// The vacuum expectation value v (index 3).
+        vev = 3,
+        /// The squared mass of the pseudoscalar Higgs m_A^2 (index 4).
+        m_a_squared = 4,
+    }
+}
+
+standard_block! {
+    /// The `MINPAR` block containing the input parameters of minimal models.
+    ///
+    /// The names of the fields are those of the mSUGRA model.
+    /// Other models, like mGMSB, use the same indices for different parameters.
+    pub struct Minpar {
+        /// The common scalar mass m0 (index 1).
+        m0 = 1,
+        /// The common gaugino mass m12 (index 2).
+        m12 = 2,
+        /// The ratio of the vacuum expectation values tan(beta) (index 3).
+        tan_beta = 3,
+        /// The sign of the mu parameter (index 4).
+        sign_mu = 4,
+        /// The common trilinear coupling A0 (index 5).
+        a0 = 5,
+    }
+}
+
+standard_block! {
+    /// The `EXTPAR` block containing non-minimal input parameters.
+    pub struct Extpar {
+        /// The input scale (index 0).
+        input_scale = 0,
+        /// The bino mass M1 (index 1).
+        m1 = 1,
+        /// The wino mass M2 (index 2).
+        m2 = 2,
+        /// The gluino mass M3 (index 3).
+        m3 = 3,
+        /// The top trilinear coupling A_t (index 11).
+        a_t = 11,
+        /// The bottom trilinear coupling A_b (index 12).
+        a_b = 12,
+        /// The tau trilinear coupling A_tau (index 13).
+        a_tau = 13,
+        /// The down type Higgs mass parameter m_Hd^2 (index 21).
+        m_hd_squared = 21,
+        /// The up type Higgs mass parameter m_Hu^2 (index 22).
+        m_hu_squared = 22,
+        /// The mu parameter (index 23).
+        mu = 23,
+        /// The squared mass of the pseudoscalar Higgs m_A^2 (index 24).
+        m_a_squared = 24,
+        /// The ratio of the vacuum expectation values tan(beta) (index 25).
+        tan_beta = 25,
+        /// The pole mass of the pseudoscalar Higgs (index 26).
+        m_a_pole = 26,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Gauge, Hmix, Minpar, Extpar, SmInputs};
+    use Slha;
+
+    #[test]
+    fn test_minpar() {
+        // Example file from appendix D.1 of the slha1 paper(arXiv:hep-ph/0311123)
+        let input = "\
+# SUSY Les Houches Accord 1.0 - example input file
+# Snowmsas point 1a
+Block MODSEL  # Select model
+     1    1   # sugra
+Block SMINPUTS   # Standard Model inputs
+     3      0.1172  # alpha_s(MZ) SM MSbar
+     5      4.25    # Mb(mb) SM MSbar
+     6    174.3     # Mtop(pole)
+Block MINPAR  # SUSY breaking input parameters
+     3     10.0     # tanb
+     4      1.0     # sign(mu)
+     1    100.0     # m0
+     2    250.0     # m12
+     5   -100.0     # A0 ";
+        let slha = Slha::parse(input).unwrap();
+        let minpar: Minpar = slha.get_block("minpar").unwrap().unwrap();
+        assert_eq!(minpar.scale, None);
+        assert_eq!(minpar.m0, Some(100.0));
+        assert_eq!(minpar.m12, Some(250.0));
+        assert_eq!(minpar.tan_beta, Some(10.0));
+        assert_eq!(minpar.sign_mu, Some(1.0));
+        assert_eq!(minpar.a0, Some(-100.0));
+        assert!(minpar.other.is_empty());
+    }
+
+    #[test]
+    fn test_gauge_hmix() {
+        // Pieces of the example file from appendix D.2 of the slha1 paper(arXiv:hep-ph/0311123)
+        let input = "
+Block gauge Q= 4.64649125e+02
+     1     3.60872342e-01   # g’(Q)MSSM DRbar
+     2     6.46479280e-01   # g(Q)MSSM DRbar
+     3     1.09623002e+00   # g3(Q)MSSM DRbar
+Block hmix Q= 4.64649125e+02  # Higgs mixing parameters
+     1     3.58660361e+02   # mu(Q)MSSM DRbar
+     2     9.75139550e+00   # tan beta(Q)MSSM DRbar
+     3     2.44923506e+02   # higgs vev(Q)MSSM DRbar
+     4     1.69697051e+04   # [m3^2/cosBsinB](Q)MSSM DRbar
+";
+        let slha = Slha::parse(input).unwrap();
+        let gauge: Gauge = slha.get_block("gauge").unwrap().unwrap();
+        assert_eq!(gauge.scale, Some(4.64649125e+02));
+        assert_eq!(gauge.g_prime, Some(3.60872342e-01));
+        assert_eq!(gauge.g, Some(6.46479280e-01));
+        assert_eq!(gauge.g3, Some(1.09623002e+00));
+        assert!(gauge.other.is_empty());
+        let hmix: Hmix = slha.get_block("hmix").unwrap().unwrap();
+        assert_eq!(hmix.scale, Some(4.64649125e+02));
+        assert_eq!(hmix.mu, Some(3.58660361e+02));
+        assert_eq!(hmix.tan_beta, Some(9.75139550e+00));
+        assert_eq!(hmix.vev, Some(2.44923506e+02));
+        assert_eq!(hmix.m_a_squared, Some(1.69697051e+04));
+        assert!(hmix.other.is_empty());
+    }
+
+    #[test]
+    fn test_extpar_other() {
+        let input = "\
+Block EXTPAR
+     0    1.0e+03   # Input scale
+     1    1.0e+02   # M1
+     2    2.0e+02   # M2
+    23    3.5e+02   # mu
+   101    7.0e+00   # Not in the standard
+";
+        let slha = Slha::parse(input).unwrap();
+        let extpar: Extpar = slha.get_block("extpar").unwrap().unwrap();
+        assert_eq!(extpar.input_scale, Some(1.0e+03));
+        assert_eq!(extpar.m1, Some(1.0e+02));
+        assert_eq!(extpar.m2, Some(2.0e+02));
+        assert_eq!(extpar.m3, None);
+        assert_eq!(extpar.mu, Some(3.5e+02));
+        assert_eq!(extpar.other.len(), 1);
+        assert_eq!(extpar.other[&101], 7.0);
+    }
+
+    #[test]
+    fn test_sminputs() {
+        // Example file from appendix D.2 of the slha1 paper(arXiv:hep-ph/0311123), extended with
+        // some of the light fermion masses from SLHA2
+        let input = "\
+Block SMINPUTS  # Standard Model inputs
+     1     1.27934000e+02   # alpha_em^-1(M_Z)^MSbar
+     2     1.16637000e-05   # G_F [GeV^-2]
+     3     1.17200000e-01   # alpha_S(M_Z)^MSbar
+     4     9.11876000e+01   # M_Z pole mass
+     5     4.25000000e+00   # mb(mb)^MSbar
+     6     1.74300000e+02   # mt pole mass
+     7     1.77700000e+00   # mtau pole mass
+    11     5.10998902e-04   # me pole mass
+    13     1.05658357e-01   # mmu pole mass
+    24     1.27000000e+00   # mc(mc)^MSbar
+    25     1.00000000e+00   # Not in the standard
+";
+        let slha = Slha::parse(input).unwrap();
+        let sminputs: SmInputs = slha.get_block("sminputs").unwrap().unwrap();
+        assert_eq!(sminputs.scale, None);
+        assert_eq!(sminputs.alpha_em_inv, Some(1.27934000e+02));
+        assert_eq!(sminputs.g_fermi, Some(1.16637000e-05));
+        assert_eq!(sminputs.alpha_s_mz, Some(1.17200000e-01));
+        assert_eq!(sminputs.m_z, Some(9.11876000e+01));
+        assert_eq!(sminputs.m_b, Some(4.25000000e+00));
+        assert_eq!(sminputs.m_t, Some(1.74300000e+02));
+        assert_eq!(sminputs.m_tau, Some(1.77700000e+00));
+        assert_eq!(sminputs.m_nu3, None);
+        assert_eq!(sminputs.m_e, Some(5.10998902e-04));
+        assert_eq!(sminputs.m_mu, Some(1.05658357e-01));
+        assert_eq!(sminputs.m_c, Some(1.27000000e+00));
+        assert_eq!(sminputs.m_s, None);
+        assert_eq!(sminputs.other.len(), 1);
+        assert_eq!(sminputs.other[&25], 1.0);
+    }
+}
@@ -0,0 +1,766 @@
+// Copyright 2017 Torsten Weber
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module contains additional types that implement the `Parseable` trait.
+//!
+//! The numeric types, `String` and tuples of these are enough to read all blocks defined in the
+//! SLHA standards.
+//! The types in this module can be used as keys or values of a `Block` to read blocks with a more
+//! unusual structure, or to perform additional checks while reading a block.
+
+use {ParseResult, Parseable, int_error};
+use errors::*;
+use internal::next_word;
+use std::convert::TryFrom;
+use std::num::IntErrorKind;
+
+/// A pair of values where the first one must not be larger than the second one.
+///
+/// This can be used to read e.g. index ranges, which are given by a lower and an upper bound.
+/// An `UnsortedPair` error is returned if the two values are out of order.
+///
+/// # Examples
+///
+/// ```rust
+/// use slha::{Slha, Block};
+/// use slha::value::SortedPair;
+///
+/// let input = "\
+/// BLOCK RANGES
+///    1    1   3
+///    2    4   4
+/// ";
+///
+/// let slha = Slha::parse(input).unwrap();
+/// let ranges: Block<u8, SortedPair<i64>> = slha.get_block("ranges").unwrap().unwrap();
+/// assert_eq!(ranges.map[&1], SortedPair { low: 1, high: 3 });
+/// assert_eq!(ranges.map[&2], SortedPair { low: 4, high: 4 });
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SortedPair<T> {
+    /// The first, smaller value.
+    pub low: T,
+    /// The second, larger value.
+    pub high: T,
+}
+impl<T> Parseable for SortedPair<T>
+where
+    T: Parseable + Ord,
+{
+    fn parse<'input>(input: &'input str) -> ParseResult<'input, SortedPair<T>> {
+        let (rest, (low, high)) = match <(T, T)>::parse(input) {
+            ParseResult::Done(rest, value) => (rest, value),
+            ParseResult::Error(err) => return ParseResult::Error(err),
+        };
+        if low > high {
+            return ParseResult::Error(ErrorKind::UnsortedPair.into());
+        }
+        ParseResult::Done(rest, SortedPair { low, high })
+    }
+}
+
+/// A value followed by an arbitrary number of additional words.
+///
+/// Normally it is an error if there is unparsed input left at the end of a line.
+/// `WithRest` instead parses a value of type `V` and collects all remaining words of the line into
+/// the `rest` field.
+/// This allows to read the known part of a line while keeping any trailing columns that are not
+/// modelled in the type.
+///
+/// # Examples
+///
+/// ```rust
+/// use slha::{Slha, Block};
+/// use slha::value::WithRest;
+///
+/// let input = "\
+/// BLOCK MASS
+///    6    173.2   1.35   pole
+///    5    4.25
+/// ";
+///
+/// let slha = Slha::parse(input).unwrap();
+/// let mass: Block<i64, WithRest<f64>> = slha.get_block("mass").unwrap().unwrap();
+/// assert_eq!(mass.map[&6].value, 173.2);
+/// assert_eq!(mass.map[&6].rest, vec!["1.35".to_string(), "pole".to_string()]);
+/// assert_eq!(mass.map[&5].value, 4.25);
+/// assert!(mass.map[&5].rest.is_empty());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct WithRest<V> {
+    /// The parsed value.
+    pub value: V,
+    /// The words following the value.
+    pub rest: Vec<String>,
+}
+impl<V> Parseable for WithRest<V>
+where
+    V: Parseable,
+{
+    fn parse<'input>(input: &'input str) -> ParseResult<'input, WithRest<V>> {
+        let (rest, value) = match V::parse(input) {
+            ParseResult::Done(rest, value) => (rest, value),
+            ParseResult::Error(err) => return ParseResult::Error(err),
+        };
+        let rest = rest.split_whitespace().map(|word| word.to_string()).collect();
+        ParseResult::Done("", WithRest { value, rest })
+    }
+}
+
+/// A list of values preceded by the number of values.
+///
+/// This reads lines of the form `n v1 v2 ... vn`, where the leading count `n` gives the number of
+/// values of type `T` that follow.
+/// A `NotEnoughValues` error is returned if the line ends before all `n` values have been read,
+/// and an `IntOutOfRange` error if `n` does not fit into a `usize`.
+/// Any input following the last value is left unparsed.
+///
+/// # Examples
+///
+/// ```rust
+/// use slha::{Slha, Block};
+/// use slha::value::CountedVec;
+///
+/// let input = "\
+/// BLOCK LISTS
+///    1    3   0.1   0.2   0.3
+///    2    0
+/// ";
+///
+/// let slha = Slha::parse(input).unwrap();
+/// let lists: Block<i8, CountedVec<f64>> = slha.get_block("lists").unwrap().unwrap();
+/// assert_eq!(lists.map[&1].values, vec![0.1, 0.2, 0.3]);
+/// assert!(lists.map[&2].values.is_empty());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CountedVec<T> {
+    /// The values following the count.
+    pub values: Vec<T>,
+}
+impl<T> Parseable for CountedVec<T>
+where
+    T: Parseable,
+{
+    fn parse<'input>(input: &'input str) -> ParseResult<'input, CountedVec<T>> {
+        let (mut rest, count) = match u64::parse(input) {
+            ParseResult::Done(rest, count) => (rest, count),
+            ParseResult::Error(err) => return ParseResult::Error(err),
+        };
+        let count = match usize::try_from(count) {
+            Ok(count) => count,
+            Err(_) => {
+                let word = input[..input.len() - rest.len()].trim();
+                let err = word.parse::<usize>()
+                    .expect_err("BUG: The count was checked to be too large for a usize.");
+                return ParseResult::Error(int_error(err, word, "usize"));
+            }
+        };
+        // The count is read from the file, so no space is reserved for the values up front.
+        let mut values = Vec::new();
+        for found in 0..count {
+            if rest.trim().is_empty() {
+                return ParseResult::Error(ErrorKind::NotEnoughValues(count, found).into());
+            }
+            let value = match T::parse(rest) {
+                ParseResult::Done(r, value) => {
+                    rest = r;
+                    value
+                }
+                ParseResult::Error(err) => return ParseResult::Error(err),
+            };
+            values.push(value);
+        }
+        ParseResult::Done(rest, CountedVec { values })
+    }
+}
+
+/// A list of pairs of values that extends to the end of the line.
+///
+/// This reads lines of the form `a1 b1 a2 b2 ...`, where each pair consists of a value of type
+/// `A` followed by a value of type `B`.
+/// Pairs are read until the end of the line, so no count of the pairs is needed.
+/// If the line ends after the first value of a pair, the error of parsing `B` is returned.
+///
+/// # Examples
+///
+/// ```rust
+/// use slha::{Slha, Block};
+/// use slha::value::Pairs;
+///
+/// let input = "\
+/// BLOCK COUPLINGS
+///    1    11   0.1   22   0.2
+///    2
+/// ";
+///
+/// let slha = Slha::parse(input).unwrap();
+/// let couplings: Block<i8, Pairs<i64, f64>> = slha.get_block("couplings").unwrap().unwrap();
+/// assert_eq!(couplings.map[&1].pairs, vec![(11, 0.1), (22, 0.2)]);
+/// assert!(couplings.map[&2].pairs.is_empty());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Pairs<A, B> {
+    /// The pairs in the order in which they appear on the line.
+    pub pairs: Vec<(A, B)>,
+}
+impl<A, B> Parseable for Pairs<A, B>
+where
+    A: Parseable,
+    B: Parseable,
+{
+    fn parse<'input>(input: &'input str) -> ParseResult<'input, Pairs<A, B>> {
+        let mut rest = input;
+        let mut pairs = Vec::new();
+        while !rest.trim().is_empty() {
+            let pair = match <(A, B)>::parse(rest) {
+                ParseResult::Done(r, pair) => {
+                    rest = r;
+                    pair
+                }
+                ParseResult::Error(err) => return ParseResult::Error(err),
+            };
+            pairs.push(pair);
+        }
+        ParseResult::Done("", Pairs { pairs })
+    }
+}
+
+/// A value that is either of type `A` or of type `B`.
+///
+/// This can be used to read blocks where the type of the value depends on the key, like e.g.
+/// `SPINFO`, where some lines contain a program name or version and others contain numeric codes.
+/// Parsing first tries to read the whole remaining input as a value of type `A`.
+/// If this fails, including if `A` can only be read from the start of the input, a value of
+/// type `B` is read instead, and if this fails as well, the error of `B` is returned.
+/// Since the rest of the input is consumed, `Either` can only be used as the last part of a
+/// value.
+///
+/// Since a successful parse of `A` is always taken, `A` should be the more restrictive type.
+/// In particular, `String` accepts any non-empty input and should only be used as `B`.
+///
+/// # Examples
+///
+/// ```rust
+/// use slha::{Slha, Block};
+/// use slha::value::Either;
+///
+/// let input = "\
+/// BLOCK SPINFO
+///    1    SOFTSUSY
+///    2    4.0.1
+///    4    1
+/// ";
+///
+/// let slha = Slha::parse(input).unwrap();
+/// let spinfo: Block<i8, Either<i64, String>> = slha.get_block("spinfo").unwrap().unwrap();
+/// assert_eq!(spinfo.map[&1], Either::Right("SOFTSUSY".to_string()));
+/// assert_eq!(spinfo.map[&2], Either::Right("4.0.1".to_string()));
+/// assert_eq!(spinfo.map[&4], Either::Left(1));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Either<A, B> {
+    /// A value of the first type.
+    Left(A),
+    /// A value of the second type, if the input could not be read as the first type.
+    Right(B),
+}
+impl<A, B> Parseable for Either<A, B>
+where
+    A: Parseable,
+    B: Parseable,
+{
+    fn parse<'input>(input: &'input str) -> ParseResult<'input, Either<A, B>> {
+        if let Ok(value) = A::parse(input).end() {
+            return ParseResult::Done("", Either::Left(value));
+        }
+        match B::parse(input) {
+            ParseResult::Done(rest, value) => ParseResult::Done(rest, Either::Right(value)),
+            ParseResult::Error(err) => ParseResult::Error(err),
+        }
+    }
+}
+
+/// An unsigned integer used as a set of bit flags.
+///
+/// Besides decimal integers, the value can be given in binary with a `0b` prefix or in
+/// hexadecimal with a `0x` prefix.
+/// This is useful to read flags that are packed into a bitmask in custom blocks.
+///
+/// `Bits` implements `Parseable` for `u8`, `u16`, `u32` and `u64`.
+///
+/// # Examples
+///
+/// ```rust
+/// use slha::{Slha, Block};
+/// use slha::value::Bits;
+///
+/// let input = "\
+/// BLOCK FLAGS
+///    1    0b1010
+///    2    0xff
+///    3    12
+/// ";
+///
+/// let slha = Slha::parse(input).unwrap();
+/// let flags: Block<i8, Bits<u32>> = slha.get_block("flags").unwrap().unwrap();
+/// assert_eq!(flags.map[&1].bits(), 10);
+/// assert!(flags.map[&1].contains(0b10));
+/// assert!(!flags.map[&1].contains(0b1));
+/// assert_eq!(flags.map[&2].bits(), 255);
+/// assert!(flags.map[&3].contains(0b1100));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Bits<T>(T);
+
+macro_rules! impl_bits {
+    ($int:ty) => {
+        impl Bits<$int> {
+            /// Returns the integer containing the flags.
+            pub fn bits(&self) -> $int {
+                self.0
+            }
+
+            /// Checks whether all bits that are set in `flag` are also set here.
+            pub fn contains(&self, flag: $int) -> bool {
+                self.0 & flag == flag
+            }
+        }
+
+        impl Parseable for Bits<$int> {
+            fn parse<'input>(input: &'input str) -> ParseResult<'input, Bits<$int>> {
+                let (word, rest) = match next_word(input) {
+                    Some(a) => a,
+                    None => return ParseResult::Error(ErrorKind::UnexpectedEol.into()),
+                };
+                let (digits, radix) = if word.starts_with("0b") || word.starts_with("0B") {
+                    (&word[2..], 2)
+                } else if word.starts_with("0x") || word.starts_with("0X") {
+                    (&word[2..], 16)
+                } else {
+                    (word, 10)
+                };
+                match <$int>::from_str_radix(digits, radix) {
+                    Ok(bits) => ParseResult::Done(rest, Bits(bits)),
+                    Err(err) => ParseResult::Error(int_error(err, word, stringify!($int))),
+                }
+            }
+        }
+    }
+}
+impl_bits!(u8);
+impl_bits!(u16);
+impl_bits!(u32);
+impl_bits!(u64);
+
+/// An integer that is clamped to the range of its type instead of failing on overflow.
+///
+/// Integers that are too large are read as the largest value of the type, integers that are too
+/// small as the smallest one, so e.g. `300` is read as `127` for `Saturating<i8>`.
+/// For unsigned types this includes all negative integers, so e.g. `-5` is read as `0` for
+/// `Saturating<u8>`.
+/// Whether this happened can be checked using `is_clamped`.
+/// All other errors, e.g. if the word is not an integer at all, are still reported.
+///
+/// `Saturating` implements `Parseable` for all integer types that are supported by `Block`.
+///
+/// # Examples
+///
+/// ```rust
+/// use slha::{Slha, Block};
+/// use slha::value::Saturating;
+///
+/// let input = "\
+/// BLOCK COUNTS
+///    1    42
+///    2    -1000
+/// ";
+///
+/// let slha = Slha::parse(input).unwrap();
+/// let counts: Block<i8, Saturating<i8>> = slha.get_block("counts").unwrap().unwrap();
+/// assert_eq!(counts.map[&1].value(), 42);
+/// assert!(!counts.map[&1].is_clamped());
+/// assert_eq!(counts.map[&2].value(), -128);
+/// assert!(counts.map[&2].is_clamped());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Saturating<T> {
+    value: T,
+    clamped: bool,
+}
+
+impl<T: Copy> Saturating<T> {
+    /// Returns the (possibly clamped) value.
+    pub fn value(&self) -> T {
+        self.value
+    }
+
+    /// Checks whether the value in the file was out of range and had to be clamped.
+    pub fn is_clamped(&self) -> bool {
+        self.clamped
+    }
+}
+
+macro_rules! impl_saturating {
+    ($int:ty) => {
+        impl Parseable for Saturating<$int> {
+            fn parse<'input>(input: &'input str) -> ParseResult<'input, Saturating<$int>> {
+                let (word, rest) = match next_word(input) {
+                    Some(a) => a,
+                    None => return ParseResult::Error(ErrorKind::UnexpectedEol.into()),
+                };
+                let (value, clamped) = match word.parse::<$int>() {
+                    Ok(value) => (value, false),
+                    Err(ref err) if *err.kind() == IntErrorKind::PosOverflow => {
+                        (<$int>::MAX, true)
+                    }
+                    Err(ref err) if *err.kind() == IntErrorKind::NegOverflow => {
+                        (<$int>::MIN, true)
+                    }
+                    // Only unsigned types fail to parse a negative integer without an overflow.
+                    // `-0` is not out of range, so it is not reported as clamped.
+                    Err(_) if is_negative_integer(word) => {
+                        (<$int>::MIN, word[1..].bytes().any(|b| b != b'0'))
+                    }
+                    Err(err) => return ParseResult::Error(int_error(err, word, stringify!($int))),
+                };
+                ParseResult::Done(rest, Saturating { value, clamped })
+            }
+        }
+    }
+}
+impl_saturating!(i8);
+impl_saturating!(i16);
+impl_saturating!(i32);
+impl_saturating!(i64);
+impl_saturating!(u8);
+impl_saturating!(u16);
+impl_saturating!(u32);
+impl_saturating!(u64);
+
+/// Checks whether `word` is a `-` followed by at least one decimal digit and nothing else.
+fn is_negative_integer(word: &str) -> bool {
+    match word.strip_prefix('-') {
+        Some(digits) => !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Bits, CountedVec, Either, Pairs, Saturating, SortedPair, WithRest};
+    use {Block, Parseable, ParseResult, Slha};
+    use errors::{Error, ErrorKind};
+
+    #[test]
+    fn test_sorted_pair() {
+        match SortedPair::<i64>::parse("1 3") {
+            ParseResult::Done(rest, value) => {
+                assert_eq!(rest, "");
+                assert_eq!(value, SortedPair { low: 1, high: 3 });
+            }
+            ParseResult::Error(err) => panic!("Unexpected error: {}", err),
+        }
+        match SortedPair::<i64>::parse("  -2 -2  foo") {
+            ParseResult::Done(rest, value) => {
+                assert_eq!(rest, "  foo");
+                assert_eq!(value, SortedPair { low: -2, high: -2 });
+            }
+            ParseResult::Error(err) => panic!("Unexpected error: {}", err),
+        }
+    }
+
+    #[test]
+    fn test_sorted_pair_unsorted() {
+        match SortedPair::<i64>::parse("3 1") {
+            ParseResult::Done(_, value) => panic!("Unexpected success: {:?}", value),
+            ParseResult::Error(Error(ErrorKind::UnsortedPair, _)) => (),
+            ParseResult::Error(err) => panic!("Wrong error variant {:?} instead of UnsortedPair", err),
+        }
+    }
+
+    #[test]
+    fn test_with_rest() {
+        let input = "\
+BLOCK TEST
+   1   0.5   foo 3   bar   # Comment
+   2   1.5
+   3   2.5   4
+";
+        let slha = Slha::parse(input).unwrap();
+        let test: Block<i8, WithRest<f64>> = slha.get_block("test").unwrap().unwrap();
+        assert_eq!(test.map.len(), 3);
+        assert_eq!(
+            test.map[&1],
+            WithRest {
+                value: 0.5,
+                rest: vec!["foo".to_string(), "3".to_string(), "bar".to_string()],
+            }
+        );
+        assert_eq!(
+            test.map[&2],
+            WithRest {
+                value: 1.5,
+                rest: Vec::new(),
+            }
+        );
+        assert_eq!(
+            test.map[&3],
+            WithRest {
+                value: 2.5,
+                rest: vec!["4".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn test_with_rest_invalid_value() {
+        let input = "\
+BLOCK TEST
+   1   foo   0.5
+";
+        let slha = Slha::parse(input).unwrap();
+        let test: Result<Block<i8, WithRest<f64>>, Error> = slha.get_block("test").unwrap();
+        assert!(test.is_err());
+    }
+
+    #[test]
+    fn test_counted_vec() {
+        let input = "\
+BLOCK TEST
+   1   3   0.1   0.2   0.3   # Comment
+   2   0
+   3   1   2.5
+";
+        let slha = Slha::parse(input).unwrap();
+        let test: Block<i8, CountedVec<f64>> = slha.get_block("test").unwrap().unwrap();
+        assert_eq!(test.map.len(), 3);
+        assert_eq!(test.map[&1], CountedVec { values: vec![0.1, 0.2, 0.3] });
+        assert_eq!(test.map[&2], CountedVec { values: Vec::new() });
+        assert_eq!(test.map[&3], CountedVec { values: vec![2.5] });
+    }
+
+    #[test]
+    fn test_counted_vec_not_enough_values() {
+        match CountedVec::<f64>::parse("3 0.1 0.2") {
+            ParseResult::Done(_, value) => panic!("Unexpected success: {:?}", value),
+            ParseResult::Error(Error(ErrorKind::NotEnoughValues(3, 2), _)) => (),
+            ParseResult::Error(err) => {
+                panic!("Wrong error variant {:?} instead of NotEnoughValues", err)
+            }
+        }
+        match CountedVec::<f64>::parse("1 0.1 0.2") {
+            ParseResult::Done(rest, value) => {
+                assert_eq!(rest, " 0.2");
+                assert_eq!(value, CountedVec { values: vec![0.1] });
+            }
+            ParseResult::Error(err) => panic!("Unexpected error: {}", err),
+        }
+        let input = "\
+BLOCK TEST
+   1   2   0.1
+";
+        let slha = Slha::parse(input).unwrap();
+        let test: Result<Block<i8, CountedVec<f64>>, Error> = slha.get_block("test").unwrap();
+        assert!(test.is_err());
+
+        let input = "\
+BLOCK TEST
+   1   18446744073709551615   1.0
+";
+        let slha = Slha::parse(input).unwrap();
+        let test: Result<Block<i8, CountedVec<f64>>, Error> = slha.get_block("test").unwrap();
+        assert!(test.is_err());
+        match CountedVec::<f64>::parse("18446744073709551615 1.0") {
+            ParseResult::Error(Error(ErrorKind::NotEnoughValues(count, 1), _)) => {
+                assert_eq!(count as u64, 18446744073709551615)
+            }
+            ParseResult::Error(err) => {
+                panic!("Wrong error variant {:?} instead of NotEnoughValues", err)
+            }
+            ParseResult::Done(_, value) => panic!("Unexpected success: {:?}", value),
+        }
+        match CountedVec::<f64>::parse("18446744073709551616 1.0") {
+            ParseResult::Error(Error(ErrorKind::IntOutOfRange(_, ref value, "u64"), _)) => {
+                assert_eq!(value, "18446744073709551616")
+            }
+            ParseResult::Error(err) => {
+                panic!("Wrong error variant {:?} instead of IntOutOfRange", err)
+            }
+            ParseResult::Done(_, value) => panic!("Unexpected success: {:?}", value),
+        }
+        #[cfg(target_pointer_width = "32")]
+        match CountedVec::<f64>::parse("4294967296 1.0") {
+            ParseResult::Error(Error(ErrorKind::IntOutOfRange(_, ref value, "usize"), _)) => {
+                assert_eq!(value, "4294967296")
+            }
+            ParseResult::Error(err) => {
+                panic!("Wrong error variant {:?} instead of IntOutOfRange", err)
+            }
+            ParseResult::Done(_, value) => panic!("Unexpected success: {:?}", value),
+        }
+    }
+
+    #[test]
+    fn test_pairs() {
+        let input = "\
+BLOCK TEST
+   1   11   0.1   22   0.2   # Comment
+   2
+   3   -5   1e3
+";
+        let slha = Slha::parse(input).unwrap();
+        let test: Block<i8, Pairs<i64, f64>> = slha.get_block("test").unwrap().unwrap();
+        assert_eq!(test.map.len(), 3);
+        assert_eq!(test.map[&1], Pairs { pairs: vec![(11, 0.1), (22, 0.2)] });
+        assert_eq!(test.map[&2], Pairs { pairs: Vec::new() });
+        assert_eq!(test.map[&3], Pairs { pairs: vec![(-5, 1e3)] });
+    }
+
+    #[test]
+    fn test_pairs_incomplete() {
+        match Pairs::<i64, f64>::parse("11 0.1 22") {
+            ParseResult::Done(_, value) => panic!("Unexpected success: {:?}", value),
+            ParseResult::Error(Error(ErrorKind::UnexpectedEol, _)) => (),
+            ParseResult::Error(err) => {
+                panic!("Wrong error variant {:?} instead of UnexpectedEol", err)
+            }
+        }
+        match Pairs::<i64, f64>::parse("11 0.1 0.2 0.3") {
+            ParseResult::Done(_, value) => panic!("Unexpected success: {:?}", value),
+            ParseResult::Error(_) => (),
+        }
+    }
+
+    #[test]
+    fn test_either() {
+        let input = "\
+BLOCK SPINFO
+   1   SOFTSUSY   # Program
+   2   4.0.1      # Version
+   3   Some warning message
+   4   1
+   5   -12
+   6   2 problems found
+";
+        let slha = Slha::parse(input).unwrap();
+        let spinfo: Block<i8, Either<i64, String>> = slha.get_block("spinfo").unwrap().unwrap();
+        assert_eq!(spinfo.map.len(), 6);
+        assert_eq!(spinfo.map[&1], Either::Right("SOFTSUSY".to_string()));
+        assert_eq!(spinfo.map[&2], Either::Right("4.0.1".to_string()));
+        assert_eq!(spinfo.map[&3], Either::Right("Some warning message".to_string()));
+        assert_eq!(spinfo.map[&4], Either::Left(1));
+        assert_eq!(spinfo.map[&5], Either::Left(-12));
+        assert_eq!(spinfo.map[&6], Either::Right("2 problems found".to_string()));
+    }
+
+    #[test]
+    fn test_either_both_fail() {
+        match Either::<i64, f64>::parse("foo") {
+            ParseResult::Done(_, value) => panic!("Unexpected success: {:?}", value),
+            ParseResult::Error(Error(ErrorKind::InvalidFloat(_), _)) => (),
+            ParseResult::Error(err) => panic!("Wrong error variant {:?} instead of InvalidFloat", err),
+        }
+    }
+
+    #[test]
+    fn test_bits() {
+        let input = "\
+BLOCK FLAGS
+   1   0b1010
+   2   0XfF
+   3   6
+";
+        let slha = Slha::parse(input).unwrap();
+        let flags: Block<i8, Bits<u32>> = slha.get_block("flags").unwrap().unwrap();
+        assert_eq!(flags.map[&1].bits(), 0b1010);
+        assert!(flags.map[&1].contains(0b1000));
+        assert!(flags.map[&1].contains(0b1010));
+        assert!(!flags.map[&1].contains(0b0100));
+        assert!(!flags.map[&1].contains(0b1011));
+        assert_eq!(flags.map[&2].bits(), 0xff);
+        assert_eq!(flags.map[&3].bits(), 6);
+    }
+
+    #[test]
+    fn test_bits_invalid() {
+        match Bits::<u8>::parse("0b102") {
+            ParseResult::Done(_, value) => panic!("Unexpected success: {:?}", value),
+            ParseResult::Error(Error(ErrorKind::InvalidInt(_), _)) => (),
+            ParseResult::Error(err) => panic!("Wrong error variant {:?} instead of InvalidInt", err),
+        }
+        match Bits::<u8>::parse("0x100") {
+            ParseResult::Done(_, value) => panic!("Unexpected success: {:?}", value),
+            ParseResult::Error(Error(ErrorKind::IntOutOfRange(_, ref value, "u8"), _)) => {
+                assert_eq!(value, "0x100")
+            }
+            ParseResult::Error(err) => {
+                panic!("Wrong error variant {:?} instead of IntOutOfRange", err)
+            }
+        }
+    }
+
+    #[test]
+    fn test_saturating() {
+        let input = "\
+BLOCK COUNTS
+   1   42
+   2   1000000000000000000000
+   3   -1000000000000000000000
+   4   -3
+";
+        let slha = Slha::parse(input).unwrap();
+        let counts: Block<i8, Saturating<i64>> = slha.get_block("counts").unwrap().unwrap();
+        assert_eq!(counts.map[&1].value(), 42);
+        assert!(!counts.map[&1].is_clamped());
+        assert_eq!(counts.map[&2].value(), i64::MAX);
+        assert!(counts.map[&2].is_clamped());
+        assert_eq!(counts.map[&3].value(), i64::MIN);
+        assert!(counts.map[&3].is_clamped());
+        assert_eq!(counts.map[&4].value(), -3);
+
+        match Saturating::<u8>::parse("300 7") {
+            ParseResult::Done(rest, value) => {
+                assert_eq!(rest.trim(), "7");
+                assert_eq!(value.value(), 255);
+                assert!(value.is_clamped());
+            }
+            ParseResult::Error(err) => panic!("Unexpected error: {:?}", err),
+        }
+        match Saturating::<u8>::parse("-1 7") {
+            ParseResult::Done(rest, value) => {
+                assert_eq!(rest.trim(), "7");
+                assert_eq!(value.value(), 0);
+                assert!(value.is_clamped());
+            }
+            ParseResult::Error(err) => panic!("Unexpected error: {:?}", err),
+        }
+        match Saturating::<u64>::parse("-100000000000000000000000") {
+            ParseResult::Done(_, value) => {
+                assert_eq!(value.value(), 0);
+                assert!(value.is_clamped());
+            }
+            ParseResult::Error(err) => panic!("Unexpected error: {:?}", err),
+        }
+        match Saturating::<u16>::parse("-0") {
+            ParseResult::Done(_, value) => {
+                assert_eq!(value.value(), 0);
+                assert!(!value.is_clamped());
+            }
+            ParseResult::Error(err) => panic!("Unexpected error: {:?}", err),
+        }
+        for input in &["-", "-1.5", "--1", "-x"] {
+            match Saturating::<u8>::parse(input) {
+                ParseResult::Done(_, value) => panic!("Unexpected success: {:?}", value),
+                ParseResult::Error(Error(ErrorKind::InvalidInt(_), _)) => (),
+                ParseResult::Error(err) => {
+                    panic!("Wrong error variant {:?} instead of InvalidInt", err)
+                }
+            }
+        }
+    }
+}